@@ -0,0 +1,19 @@
+//! Typed endpoint wrappers: one client per Dify app type plus the request and
+//! response models of each endpoint.
+
+#[cfg(feature = "annotations")]
+pub mod annotation;
+mod app;
+#[cfg(feature = "audio")]
+pub mod audio;
+#[cfg(feature = "chat")]
+pub mod chat;
+#[cfg(feature = "completion")]
+pub mod completion;
+#[cfg(feature = "knowledge")]
+pub mod knowledge;
+pub mod models;
+pub mod paginator;
+pub mod stream;
+#[cfg(feature = "workflow")]
+pub mod workflow;
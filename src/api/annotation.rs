@@ -0,0 +1,118 @@
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::time::Duration;
+
+use super::models::{string_enum, Id};
+use crate::{compat, error::ResponseExt, DifyClient};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnnotationReplyAction {
+    Enable,
+    Disable,
+}
+
+impl std::fmt::Display for AnnotationReplyAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Enable => write!(f, "enable"),
+            Self::Disable => write!(f, "disable"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct AnnotationReplySettings {
+    pub embedding_provider_name: Option<String>,
+    pub embedding_model_name: Option<String>,
+    pub score_threshold: f64,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+string_enum!(AnnotationJobStatus {
+    Waiting => "waiting",
+    Processing => "processing",
+    Completed => "completed",
+    Error => "error",
+});
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct AnnotationJob {
+    pub job_id: Id,
+    pub job_status: AnnotationJobStatus,
+    pub error_msg: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl DifyClient {
+    pub async fn annotation_reply(
+        &self,
+        action: AnnotationReplyAction,
+        settings: &AnnotationReplySettings,
+    ) -> Result<AnnotationJob> {
+        let data = serde_json::to_value(settings)?;
+        let response = self
+            .send_request(
+                reqwest::Method::POST,
+                &format!("/apps/annotation-reply/{}", action),
+                Some(data),
+                None,
+                false,
+            )
+            .await?
+            .check_status()
+            .await?;
+        Ok(response.json().await?)
+    }
+
+    pub async fn get_annotation_job_status(
+        &self,
+        action: AnnotationReplyAction,
+        job_id: &str,
+    ) -> Result<AnnotationJob> {
+        let response = self
+            .send_request(
+                reqwest::Method::GET,
+                &format!("/apps/annotation-reply/{}/status/{}", action, job_id),
+                None,
+                None,
+                false,
+            )
+            .await?
+            .check_status()
+            .await?;
+        Ok(response.json().await?)
+    }
+
+    /// Polls the job status until it completes, fails, or `timeout` elapses.
+    pub async fn wait_for_annotation_job(
+        &self,
+        action: AnnotationReplyAction,
+        job_id: &str,
+        poll_interval: Duration,
+        timeout: Duration,
+    ) -> Result<AnnotationJob> {
+        let deadline = compat::Instant::now() + timeout;
+        loop {
+            let job = self.get_annotation_job_status(action, job_id).await?;
+            match job.job_status {
+                AnnotationJobStatus::Completed => return Ok(job),
+                AnnotationJobStatus::Error => bail!(
+                    "annotation job {} failed: {}",
+                    job_id,
+                    job.error_msg.unwrap_or_default()
+                ),
+                _ => {}
+            }
+
+            if compat::Instant::now() + poll_interval > deadline {
+                bail!("timed out waiting for annotation job {}", job_id);
+            }
+            compat::sleep(poll_interval).await;
+        }
+    }
+}
@@ -0,0 +1,288 @@
+use anyhow::Result;
+use bytes::Bytes;
+use reqwest::Response;
+use serde::Serialize;
+use serde_json::json;
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::io::AsyncRead;
+
+use super::{
+    models::{
+        AppInfo, AppMeta, AppParameters, AppSite, Introduction, MessageFeedback, Page, Rating,
+        ServerInfo, UploadedFile,
+    },
+    paginator::Paginator,
+};
+use crate::{
+    error::{ResponseExt, VersionMismatch},
+    DifyClient, FileSource,
+};
+
+#[derive(Serialize)]
+struct FeedbackPayload<'a> {
+    rating: Rating,
+    user: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content: Option<&'a str>,
+}
+
+impl DifyClient {
+    /// Rates a message, optionally with a written comment.
+    pub async fn message_feedback(
+        &self,
+        message_id: &str,
+        rating: Rating,
+        user: &str,
+        content: Option<&str>,
+    ) -> Result<()> {
+        let data = serde_json::to_value(FeedbackPayload {
+            rating,
+            user,
+            content,
+        })?;
+        self.send_request(
+            reqwest::Method::POST,
+            &format!("/messages/{}/feedbacks", message_id),
+            Some(data),
+            None,
+            false,
+        )
+        .await?
+        .check_status()
+        .await?;
+        Ok(())
+    }
+
+    /// Lists the message ratings of the app, newest first.
+    pub async fn get_app_feedbacks(&self, page: u32, limit: u32) -> Result<Page<MessageFeedback>> {
+        let params = json!({
+            "page": page,
+            "limit": limit
+        });
+        let response = self
+            .send_request(
+                reqwest::Method::GET,
+                "/app/feedbacks",
+                None,
+                Some(params),
+                false,
+            )
+            .await?
+            .check_status()
+            .await?;
+        Ok(response.json().await?)
+    }
+
+    pub fn paginate_app_feedbacks(&self, limit: u32) -> Paginator<'_, MessageFeedback> {
+        Paginator::by_page(move |page| self.get_app_feedbacks(page, limit))
+    }
+
+    pub async fn message_feedback_raw(
+        &self,
+        message_id: &str,
+        rating: bool,
+        user: &str,
+    ) -> Result<Response> {
+        let data = json!({
+            "rating": rating,
+            "user": user
+        });
+        self.send_request(
+            reqwest::Method::POST,
+            &format!("/messages/{}/feedbacks", message_id),
+            Some(data),
+            None,
+            false,
+        )
+        .await
+    }
+
+    pub async fn get_application_parameters(&self, user: &str) -> Result<Response> {
+        self.get_application_parameters_raw(user)
+            .await?
+            .check_status()
+            .await
+    }
+
+    pub async fn get_application_parameters_raw(&self, user: &str) -> Result<Response> {
+        let params = json!({
+            "user": user
+        });
+        self.send_request(
+            reqwest::Method::GET,
+            "/parameters",
+            None,
+            Some(params),
+            false,
+        )
+        .await
+    }
+
+    pub async fn get_parameters(&self, user: &str) -> Result<AppParameters> {
+        let response = self.get_application_parameters(user).await?;
+        Ok(response.json().await?)
+    }
+
+    pub async fn get_introduction(&self, user: &str) -> Result<Option<Introduction>> {
+        Ok(self.get_parameters(user).await?.introduction())
+    }
+
+    pub async fn get_info(&self) -> Result<AppInfo> {
+        let response = self
+            .send_request(reqwest::Method::GET, "/info", None, None, false)
+            .await?
+            .check_status()
+            .await?;
+        Ok(response.json().await?)
+    }
+
+    pub async fn get_meta(&self) -> Result<AppMeta> {
+        let response = self
+            .send_request(reqwest::Method::GET, "/meta", None, None, false)
+            .await?
+            .check_status()
+            .await?;
+        Ok(response.json().await?)
+    }
+
+    pub async fn get_site(&self) -> Result<AppSite> {
+        let response = self
+            .send_request(reqwest::Method::GET, "/site", None, None, false)
+            .await?
+            .check_status()
+            .await?;
+        Ok(response.json().await?)
+    }
+
+    /// Uploads a file for use in chat messages. Pass a [`FileSource`] with
+    /// [`FileSource::with_mime_type`] to override the guessed content type, or
+    /// with [`FileSource::with_progress`] to track the upload.
+    pub async fn file_upload(&self, user: &str, file: impl Into<FileSource>) -> Result<Response> {
+        self.file_upload_raw(user, file).await?.check_status().await
+    }
+
+    pub async fn upload_file(
+        &self,
+        user: &str,
+        file: impl Into<FileSource>,
+    ) -> Result<UploadedFile> {
+        let response = self
+            .file_upload_raw(user, file)
+            .await?
+            .check_status()
+            .await?;
+        Ok(response.json().await?)
+    }
+
+    pub async fn file_upload_raw(
+        &self,
+        user: &str,
+        file: impl Into<FileSource>,
+    ) -> Result<Response> {
+        self.send_request_with_files(
+            reqwest::Method::POST,
+            "/files/upload",
+            vec![("user", user.to_string())],
+            file.into(),
+        )
+        .await
+    }
+
+    /// Uploads an in-memory file. Without a `mime` type it is guessed from the
+    /// `filename` extension.
+    pub async fn file_upload_bytes(
+        &self,
+        user: &str,
+        filename: &str,
+        mime: Option<&str>,
+        bytes: impl Into<Bytes>,
+    ) -> Result<Response> {
+        let mut file = FileSource::bytes(filename, bytes);
+        if let Some(mime) = mime {
+            file = file.with_mime_type(mime);
+        }
+        self.send_request_with_files(
+            reqwest::Method::POST,
+            "/files/upload",
+            vec![("user", user.to_string())],
+            file,
+        )
+        .await?
+        .check_status()
+        .await
+    }
+
+    /// Uploads a file read from `reader` without buffering it in memory.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn file_upload_reader(
+        &self,
+        user: &str,
+        filename: &str,
+        mime: Option<&str>,
+        reader: impl AsyncRead + Send + Sync + 'static,
+    ) -> Result<Response> {
+        let mut file = FileSource::reader(filename, reader);
+        if let Some(mime) = mime {
+            file = file.with_mime_type(mime);
+        }
+        self.send_request_with_files(
+            reqwest::Method::POST,
+            "/files/upload",
+            vec![("user", user.to_string())],
+            file,
+        )
+        .await?
+        .check_status()
+        .await
+    }
+
+    pub async fn server_info(&self) -> Result<ServerInfo> {
+        let response = self
+            .send_request(reqwest::Method::GET, "/", None, None, false)
+            .await?
+            .check_status()
+            .await?;
+        Ok(response.json().await?)
+    }
+
+    /// Compares the server version with the version pinned in the config.
+    ///
+    /// Returns `Ok(None)` when no version is pinned or it matches. A mismatch
+    /// is logged and returned as `Ok(Some(_))`, or fails with a
+    /// [`VersionMismatch`] error when the pin is strict.
+    pub async fn check_server_version(&self) -> Result<Option<VersionMismatch>> {
+        let Some(expected) = self.version_config().expected.as_deref() else {
+            return Ok(None);
+        };
+
+        let info = self.server_info().await?;
+        if version_matches(expected, &info.server_version) {
+            return Ok(None);
+        }
+
+        let mismatch = VersionMismatch {
+            expected: expected.to_string(),
+            actual: info.server_version,
+        };
+        if self.version_config().strict {
+            return Err(mismatch.into());
+        }
+        tracing::warn!("{}", mismatch);
+        Ok(Some(mismatch))
+    }
+}
+
+/// Whether `actual` equals `expected` or starts with its components,
+/// ignoring a leading `v`.
+pub(crate) fn version_matches(expected: &str, actual: &str) -> bool {
+    let expected = expected.trim().trim_start_matches('v');
+    let actual = actual.trim().trim_start_matches('v');
+    if expected.is_empty() {
+        return true;
+    }
+
+    let mut actual_parts = actual.split('.');
+    expected
+        .split('.')
+        .all(|part| actual_parts.next() == Some(part))
+}
@@ -0,0 +1,175 @@
+use anyhow::Result;
+use bytes::Bytes;
+use futures_util::{Stream, StreamExt};
+use reqwest::{header, Response};
+use serde::Serialize;
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use super::models::AudioToTextResponse;
+use crate::{compat::BoxStream, error::ResponseExt, raw, DifyClient, FileSource};
+
+/// What `text_to_audio` should speak: an existing message or raw text.
+#[derive(Debug, Clone, Copy)]
+pub enum TextToAudioInput<'a> {
+    MessageId(&'a str),
+    Text(&'a str),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AudioFormat {
+    Mp3,
+    Wav,
+    Other(String),
+}
+
+impl AudioFormat {
+    pub fn from_content_type(content_type: &str) -> Self {
+        let mime = content_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+        match mime.as_str() {
+            "audio/mpeg" | "audio/mp3" => Self::Mp3,
+            "audio/wav" | "audio/wave" | "audio/x-wav" => Self::Wav,
+            _ => Self::Other(mime),
+        }
+    }
+
+    fn from_response(response: &Response) -> Self {
+        response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(Self::from_content_type)
+            .unwrap_or(Self::Mp3)
+    }
+
+    pub fn extension(&self) -> Option<&'static str> {
+        match self {
+            Self::Mp3 => Some("mp3"),
+            Self::Wav => Some("wav"),
+            Self::Other(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Audio {
+    pub format: AudioFormat,
+    pub bytes: Bytes,
+}
+
+impl Audio {
+    async fn from_response(response: Response) -> Result<Self> {
+        let format = AudioFormat::from_response(&response);
+        Ok(Self {
+            format,
+            bytes: response.bytes().await?,
+        })
+    }
+}
+
+/// Audio bytes as they arrive, for progressive playback.
+pub struct AudioStream {
+    format: AudioFormat,
+    inner: BoxStream<'static, reqwest::Result<Bytes>>,
+}
+
+impl AudioStream {
+    fn new(response: Response) -> Self {
+        Self {
+            format: AudioFormat::from_response(&response),
+            inner: Box::pin(raw::bytes_stream(response)),
+        }
+    }
+
+    pub fn format(&self) -> &AudioFormat {
+        &self.format
+    }
+}
+
+impl Stream for AudioStream {
+    type Item = Result<Bytes>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner
+            .poll_next_unpin(cx)
+            .map(|chunk| chunk.map(|chunk| chunk.map_err(Into::into)))
+    }
+}
+
+#[derive(Serialize)]
+struct TextToAudioPayload<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message_id: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<&'a str>,
+    user: &'a str,
+    streaming: bool,
+}
+
+impl DifyClient {
+    pub async fn audio_to_text(&self, user: &str, audio: impl Into<FileSource>) -> Result<String> {
+        let response = self
+            .send_request_with_files(
+                reqwest::Method::POST,
+                "/audio-to-text",
+                vec![("user", user.to_string())],
+                audio.into(),
+            )
+            .await?
+            .check_status()
+            .await?;
+
+        let transcription: AudioToTextResponse = response.json().await?;
+        Ok(transcription.text)
+    }
+
+    pub async fn text_to_audio(&self, input: TextToAudioInput<'_>, user: &str) -> Result<Audio> {
+        let response = self.send_text_to_audio(input, user, false).await?;
+        Audio::from_response(response).await
+    }
+
+    pub async fn text_to_audio_stream(
+        &self,
+        input: TextToAudioInput<'_>,
+        user: &str,
+    ) -> Result<AudioStream> {
+        let response = self.send_text_to_audio(input, user, true).await?;
+        Ok(AudioStream::new(response))
+    }
+
+    async fn send_text_to_audio(
+        &self,
+        input: TextToAudioInput<'_>,
+        user: &str,
+        streaming: bool,
+    ) -> Result<Response> {
+        let (message_id, text) = match input {
+            TextToAudioInput::MessageId(message_id) => (Some(message_id), None),
+            TextToAudioInput::Text(text) => (None, Some(text)),
+        };
+        let data = serde_json::to_value(TextToAudioPayload {
+            message_id,
+            text,
+            user,
+            streaming,
+        })?;
+
+        self.send_request(
+            reqwest::Method::POST,
+            "/text-to-audio",
+            Some(data),
+            None,
+            streaming,
+        )
+        .await?
+        .check_status()
+        .await
+    }
+}
@@ -0,0 +1,430 @@
+use anyhow::{bail, Result};
+use futures_util::StreamExt;
+use reqwest::Response;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+#[cfg(feature = "fs")]
+use std::path::Path;
+use std::{future::Future, sync::Arc};
+
+#[cfg(feature = "fs")]
+use super::models::FileType;
+use super::{
+    models::{FileInput, HistoryMessage, Id, Page, ResponseMode, SuggestedQuestionsResponse},
+    stream::{ChatStream, ChatStreamEvent},
+};
+use crate::{
+    compat::{BoxFuture, MaybeSend},
+    error::{ConfigError, ConfigProblem, ResponseExt},
+    DifyClient, DifyConfig,
+};
+
+#[derive(Clone)]
+pub struct ChatClient {
+    dify_client: Arc<DifyClient>,
+}
+
+/// A chat message for [`ChatClient::chat_request`] and
+/// [`ChatClient::chat_request_stream`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ChatMessageRequest {
+    pub inputs: Value,
+    pub query: String,
+    pub response_mode: ResponseMode,
+    pub user: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conversation_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub files: Option<Vec<FileInput>>,
+}
+
+impl ChatMessageRequest {
+    pub fn new(inputs: Value, query: &str, user: &str) -> Self {
+        Self {
+            inputs,
+            query: query.to_string(),
+            response_mode: ResponseMode::Block,
+            user: user.to_string(),
+            conversation_id: None,
+            files: None,
+        }
+    }
+
+    pub fn response_mode(mut self, response_mode: ResponseMode) -> Self {
+        self.response_mode = response_mode;
+        self
+    }
+
+    pub fn conversation_id(mut self, conversation_id: &str) -> Self {
+        self.conversation_id = Some(conversation_id.to_string());
+        self
+    }
+
+    pub fn files(mut self, files: Vec<FileInput>) -> Self {
+        self.files = Some(files);
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut problems = Vec::new();
+        if !(self.inputs.is_object() || self.inputs.is_null()) {
+            problems.push(ConfigProblem::InputsNotObject);
+        }
+        if self.query.trim().is_empty() {
+            problems.push(ConfigProblem::EmptyField { field: "query" });
+        }
+        if self.user.trim().is_empty() {
+            problems.push(ConfigProblem::EmptyField { field: "user" });
+        }
+        ConfigError::check(problems)
+    }
+}
+
+#[derive(Serialize)]
+struct ChatMessagePayload<'a> {
+    inputs: Value,
+    query: &'a str,
+    user: &'a str,
+    response_mode: ResponseMode,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    conversation_id: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    files: Option<Value>,
+}
+
+impl ChatClient {
+    pub fn new(api_key: &str, base_url: Option<&str>) -> Self {
+        Self {
+            dify_client: Arc::new(DifyClient::new(api_key, base_url)),
+        }
+    }
+
+    pub fn from_config(config: &DifyConfig) -> Result<Self> {
+        Ok(Self {
+            dify_client: Arc::new(DifyClient::from_config(config)?),
+        })
+    }
+
+    /// The shared transport, for building clients of other app types on it.
+    pub fn dify_client(&self) -> &Arc<DifyClient> {
+        &self.dify_client
+    }
+
+    pub(crate) fn client(&self) -> &DifyClient {
+        &self.dify_client
+    }
+
+    pub async fn create_chat_message(
+        &self,
+        inputs: Value,
+        query: &str,
+        user: &str,
+        response_mode: ResponseMode,
+        conversation_id: Option<&str>,
+        files: Option<Vec<FileInput>>,
+    ) -> Result<Response> {
+        let files = files.map(serde_json::to_value).transpose()?;
+        self.create_chat_message_raw(inputs, query, user, response_mode, conversation_id, files)
+            .await?
+            .check_status()
+            .await
+    }
+
+    pub async fn chat_request(&self, request: &ChatMessageRequest) -> Result<Response> {
+        request.validate()?;
+        self.create_chat_message(
+            request.inputs.clone(),
+            &request.query,
+            &request.user,
+            request.response_mode,
+            request.conversation_id.as_deref(),
+            request.files.clone(),
+        )
+        .await
+    }
+
+    /// Sends `request` in streaming mode, whatever its `response_mode`, like
+    /// [`create_chat_message_stream`](Self::create_chat_message_stream).
+    pub async fn chat_request_stream(&self, request: &ChatMessageRequest) -> Result<ChatStream> {
+        request.validate()?;
+        self.create_chat_message_stream(
+            request.inputs.clone(),
+            &request.query,
+            &request.user,
+            request.conversation_id.as_deref(),
+            request.files.clone(),
+        )
+        .await
+    }
+
+    /// Uploads every file in `paths` and sends them along with the message.
+    /// The file type of each upload is derived from its MIME type.
+    #[cfg(feature = "fs")]
+    pub async fn create_chat_message_with_local_files(
+        &self,
+        inputs: Value,
+        query: &str,
+        user: &str,
+        response_mode: ResponseMode,
+        conversation_id: Option<&str>,
+        paths: &[impl AsRef<Path>],
+    ) -> Result<Response> {
+        let mut files = Vec::with_capacity(paths.len());
+        for path in paths {
+            let uploaded = self.dify_client.upload_file(user, path.as_ref()).await?;
+            files.push(FileInput::local_file(
+                FileType::from_mime_type(&uploaded.mime_type),
+                &uploaded.id,
+            ));
+        }
+
+        self.create_chat_message(
+            inputs,
+            query,
+            user,
+            response_mode,
+            conversation_id,
+            Some(files),
+        )
+        .await
+    }
+
+    pub async fn create_chat_message_raw(
+        &self,
+        inputs: Value,
+        query: &str,
+        user: &str,
+        response_mode: ResponseMode,
+        conversation_id: Option<&str>,
+        files: Option<Value>,
+    ) -> Result<Response> {
+        let streaming = response_mode == ResponseMode::Stream;
+        let query = self.dify_client.moderate(query)?;
+        let data = serde_json::to_value(ChatMessagePayload {
+            inputs,
+            query: &query,
+            user,
+            response_mode,
+            conversation_id,
+            files,
+        })?;
+
+        self.dify_client
+            .send_request(
+                reqwest::Method::POST,
+                "/chat-messages",
+                Some(data),
+                None,
+                streaming,
+            )
+            .await
+    }
+
+    /// Sends the message in streaming mode. With
+    /// [`RetryConfig::stream_reconnects`](crate::RetryConfig::stream_reconnects)
+    /// set, the message is sent again when the connection drops before the
+    /// first event arrived; later drops end the stream with a
+    /// [`StreamInterrupted`](crate::StreamInterrupted) error.
+    pub async fn create_chat_message_stream(
+        &self,
+        inputs: Value,
+        query: &str,
+        user: &str,
+        conversation_id: Option<&str>,
+        files: Option<Vec<FileInput>>,
+    ) -> Result<ChatStream> {
+        let reconnects = self.dify_client.retry_config().stream_reconnects;
+        let restart = (reconnects > 0).then(|| {
+            let chat = self.clone();
+            let (inputs, query, user) = (inputs.clone(), query.to_string(), user.to_string());
+            let conversation_id = conversation_id.map(str::to_string);
+            let files = files.clone();
+            move || -> BoxFuture<'static, Result<Response>> {
+                let (chat, inputs, files) = (chat.clone(), inputs.clone(), files.clone());
+                let (query, user, conversation_id) =
+                    (query.clone(), user.clone(), conversation_id.clone());
+                Box::pin(async move {
+                    chat.create_chat_message(
+                        inputs,
+                        &query,
+                        &user,
+                        ResponseMode::Stream,
+                        conversation_id.as_deref(),
+                        files,
+                    )
+                    .await
+                })
+            }
+        });
+
+        let response = self
+            .create_chat_message(
+                inputs,
+                query,
+                user,
+                ResponseMode::Stream,
+                conversation_id,
+                files,
+            )
+            .await?;
+
+        let mut stream = self
+            .dify_client
+            .event_stream(response)
+            .collect_answer(ChatStreamEvent::answer);
+        if let Some(restart) = restart {
+            stream = stream.reconnect(reconnects, restart);
+        }
+        let chat = self.clone();
+        let user = user.to_string();
+        stream = stream.with_suggestions(move |message_id| {
+            let (chat, user) = (chat.clone(), user.clone());
+            Box::pin(async move { chat.get_suggested_questions(&message_id, &user).await })
+        });
+        let output = self.dify_client.output().clone();
+        if output.is_empty() {
+            return Ok(stream);
+        }
+        Ok(stream.map_events(move |event| output.chat_event(event)))
+    }
+
+    /// Sends `query` and returns the whole answer with the id of the
+    /// conversation, to pass as `conversation_id` for the next turn. The
+    /// answer is streamed and concatenated, so long answers don't run into
+    /// the timeouts of blocking requests.
+    pub async fn simple_chat(
+        &self,
+        query: &str,
+        user: &str,
+        conversation_id: Option<&str>,
+    ) -> Result<(String, Id)> {
+        let mut stream = self
+            .create_chat_message_stream(json!({}), query, user, conversation_id, None)
+            .await?;
+        let mut answer = String::new();
+        let mut conversation_id = Id::from(conversation_id.unwrap_or_default());
+        while let Some(event) = stream.next().await {
+            let event_conversation_id = match event? {
+                ChatStreamEvent::Message(event) | ChatStreamEvent::AgentMessage(event) => {
+                    answer.push_str(&event.answer);
+                    event.conversation_id
+                }
+                ChatStreamEvent::MessageReplace(event) => {
+                    answer = event.answer;
+                    event.conversation_id
+                }
+                ChatStreamEvent::MessageEnd(event) => event.conversation_id,
+                ChatStreamEvent::Error(event) => bail!("{}: {}", event.code, event.message),
+                _ => continue,
+            };
+            if !event_conversation_id.is_empty() {
+                conversation_id = event_conversation_id;
+            }
+        }
+        Ok((answer, conversation_id))
+    }
+
+    pub async fn stop_generation(&self, task_id: &str, user: &str) -> Result<Response> {
+        let data = json!({
+            "user": user
+        });
+        self.dify_client
+            .send_request(
+                reqwest::Method::POST,
+                &format!("/chat-messages/{}/stop", task_id),
+                Some(data),
+                None,
+                false,
+            )
+            .await?
+            .check_status()
+            .await
+    }
+
+    /// Ends `stream` once `cancel` completes, like
+    /// [`EventStream::with_cancel`](super::stream::EventStream::with_cancel),
+    /// and stops the generation on the server so an abandoned answer isn't
+    /// generated, and billed, to the end.
+    pub fn stop_on_cancel(
+        &self,
+        stream: ChatStream,
+        user: &str,
+        cancel: impl Future<Output = ()> + MaybeSend + 'static,
+    ) -> ChatStream {
+        let chat = self.clone();
+        let user = user.to_string();
+        stream
+            .with_cancel(cancel)
+            .stop_on_cancel(ChatStreamEvent::task_id, move |task_id| {
+                Box::pin(async move {
+                    if let Err(err) = chat.stop_generation(&task_id, &user).await {
+                        tracing::warn!("failed to stop task {}: {:#}", task_id, err);
+                    }
+                })
+            })
+    }
+
+    pub async fn get_suggested_questions(
+        &self,
+        message_id: &str,
+        user: &str,
+    ) -> Result<Vec<String>> {
+        let params = json!({
+            "user": user
+        });
+        let response = self
+            .dify_client
+            .send_request(
+                reqwest::Method::GET,
+                &format!("/messages/{}/suggested", message_id),
+                None,
+                Some(params),
+                false,
+            )
+            .await?
+            .check_status()
+            .await?;
+
+        let suggested: SuggestedQuestionsResponse = response.json().await?;
+        Ok(suggested.data)
+    }
+
+    /// Lists the messages of a conversation, oldest first. Pass the id of the
+    /// first message of a page as `first_id` to load the page before it.
+    pub async fn get_messages(
+        &self,
+        conversation_id: &str,
+        user: &str,
+        first_id: Option<&str>,
+        limit: u32,
+    ) -> Result<Page<HistoryMessage>> {
+        let mut params = json!({
+            "conversation_id": conversation_id,
+            "user": user,
+            "limit": limit
+        });
+        if let Some(first_id) = first_id {
+            params["first_id"] = Value::String(first_id.to_string());
+        }
+        let response = self
+            .dify_client
+            .send_request(reqwest::Method::GET, "/messages", None, Some(params), false)
+            .await?
+            .check_status()
+            .await?;
+        Ok(response.json().await?)
+    }
+}
+
+impl From<DifyClient> for ChatClient {
+    fn from(value: DifyClient) -> Self {
+        Arc::new(value).into()
+    }
+}
+
+impl From<Arc<DifyClient>> for ChatClient {
+    fn from(value: Arc<DifyClient>) -> Self {
+        ChatClient { dify_client: value }
+    }
+}
@@ -0,0 +1,255 @@
+use anyhow::Result;
+use reqwest::Response;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{future::Future, sync::Arc};
+
+use super::{
+    models::{FileInput, ResponseMode},
+    stream::{CompletionStream, CompletionStreamEvent},
+};
+use crate::{
+    compat::{BoxFuture, MaybeSend},
+    error::{ConfigError, ConfigProblem, ResponseExt},
+    DifyClient, DifyConfig,
+};
+
+#[derive(Clone)]
+pub struct CompletionClient {
+    dify_client: Arc<DifyClient>,
+}
+
+/// A completion message for [`CompletionClient::completion_request`] and
+/// [`CompletionClient::completion_request_stream`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct CompletionMessageRequest {
+    pub inputs: Value,
+    pub response_mode: ResponseMode,
+    pub user: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub files: Option<Vec<FileInput>>,
+}
+
+impl CompletionMessageRequest {
+    pub fn new(inputs: Value, user: &str) -> Self {
+        Self {
+            inputs,
+            response_mode: ResponseMode::Block,
+            user: user.to_string(),
+            files: None,
+        }
+    }
+
+    pub fn response_mode(mut self, response_mode: ResponseMode) -> Self {
+        self.response_mode = response_mode;
+        self
+    }
+
+    pub fn files(mut self, files: Vec<FileInput>) -> Self {
+        self.files = Some(files);
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut problems = Vec::new();
+        if !(self.inputs.is_object() || self.inputs.is_null()) {
+            problems.push(ConfigProblem::InputsNotObject);
+        }
+        if self.user.trim().is_empty() {
+            problems.push(ConfigProblem::EmptyField { field: "user" });
+        }
+        ConfigError::check(problems)
+    }
+}
+
+#[derive(Serialize)]
+struct CompletionMessagePayload<'a> {
+    inputs: Value,
+    response_mode: &'a str,
+    user: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    files: Option<Value>,
+}
+
+impl CompletionClient {
+    pub fn new(api_key: &str, base_url: Option<&str>) -> Self {
+        Self {
+            dify_client: Arc::new(DifyClient::new(api_key, base_url)),
+        }
+    }
+
+    pub fn from_config(config: &DifyConfig) -> Result<Self> {
+        Ok(Self {
+            dify_client: Arc::new(DifyClient::from_config(config)?),
+        })
+    }
+
+    /// The shared transport, for building clients of other app types on it.
+    pub fn dify_client(&self) -> &Arc<DifyClient> {
+        &self.dify_client
+    }
+
+    pub async fn create_completion_message(
+        &self,
+        inputs: Value,
+        response_mode: ResponseMode,
+        user: &str,
+        files: Option<Vec<FileInput>>,
+    ) -> Result<Response> {
+        let files = files.map(serde_json::to_value).transpose()?;
+        self.create_completion_message_raw(inputs, response_mode.as_str(), user, files)
+            .await?
+            .check_status()
+            .await
+    }
+
+    pub async fn completion_request(&self, request: &CompletionMessageRequest) -> Result<Response> {
+        request.validate()?;
+        self.create_completion_message(
+            request.inputs.clone(),
+            request.response_mode,
+            &request.user,
+            request.files.clone(),
+        )
+        .await
+    }
+
+    /// Sends `request` in streaming mode, whatever its `response_mode`, like
+    /// [`create_completion_message_stream`](Self::create_completion_message_stream).
+    pub async fn completion_request_stream(
+        &self,
+        request: &CompletionMessageRequest,
+    ) -> Result<CompletionStream> {
+        request.validate()?;
+        self.create_completion_message_stream(
+            request.inputs.clone(),
+            &request.user,
+            request.files.clone(),
+        )
+        .await
+    }
+
+    /// Sends the message in streaming mode. Drops are handled like in
+    /// [`ChatClient::create_chat_message_stream`](crate::ChatClient::create_chat_message_stream).
+    pub async fn create_completion_message_stream(
+        &self,
+        inputs: Value,
+        user: &str,
+        files: Option<Vec<FileInput>>,
+    ) -> Result<CompletionStream> {
+        let reconnects = self.dify_client.retry_config().stream_reconnects;
+        let restart = (reconnects > 0).then(|| {
+            let completion = self.clone();
+            let (inputs, user, files) = (inputs.clone(), user.to_string(), files.clone());
+            move || -> BoxFuture<'static, Result<Response>> {
+                let (completion, inputs, files) =
+                    (completion.clone(), inputs.clone(), files.clone());
+                let user = user.clone();
+                Box::pin(async move {
+                    completion
+                        .create_completion_message(inputs, ResponseMode::Stream, &user, files)
+                        .await
+                })
+            }
+        });
+
+        let response = self
+            .create_completion_message(inputs, ResponseMode::Stream, user, files)
+            .await?;
+        let mut stream = self
+            .dify_client
+            .event_stream(response)
+            .collect_answer(CompletionStreamEvent::answer);
+        if let Some(restart) = restart {
+            stream = stream.reconnect(reconnects, restart);
+        }
+        let output = self.dify_client.output().clone();
+        if output.is_empty() {
+            return Ok(stream);
+        }
+        Ok(stream.map_events(move |event| output.completion_event(event)))
+    }
+
+    /// Stops a streaming message. `task_id` is available from the message,
+    /// message_end and error events, see [`CompletionStreamEvent::task_id`].
+    pub async fn stop_generation(&self, task_id: &str, user: &str) -> Result<Response> {
+        let data = json!({
+            "user": user
+        });
+        self.dify_client
+            .send_request(
+                reqwest::Method::POST,
+                &format!("/completion-messages/{}/stop", task_id),
+                Some(data),
+                None,
+                false,
+            )
+            .await?
+            .check_status()
+            .await
+    }
+
+    /// Ends `stream` once `cancel` completes and stops the generation on the
+    /// server, like
+    /// [`ChatClient::stop_on_cancel`](crate::ChatClient::stop_on_cancel).
+    pub fn stop_on_cancel(
+        &self,
+        stream: CompletionStream,
+        user: &str,
+        cancel: impl Future<Output = ()> + MaybeSend + 'static,
+    ) -> CompletionStream {
+        let completion = self.clone();
+        let user = user.to_string();
+        stream
+            .with_cancel(cancel)
+            .stop_on_cancel(CompletionStreamEvent::task_id, move |task_id| {
+                Box::pin(async move {
+                    if let Err(err) = completion.stop_generation(&task_id, &user).await {
+                        tracing::warn!("failed to stop task {}: {:#}", task_id, err);
+                    }
+                })
+            })
+    }
+
+    pub async fn create_completion_message_raw(
+        &self,
+        mut inputs: Value,
+        response_mode: &str,
+        user: &str,
+        files: Option<Value>,
+    ) -> Result<Response> {
+        if let Some(Value::String(query)) = inputs.get_mut("query") {
+            *query = self.dify_client.moderate(query)?.into_owned();
+        }
+
+        let data = serde_json::to_value(CompletionMessagePayload {
+            inputs,
+            response_mode,
+            user,
+            files,
+        })?;
+
+        self.dify_client
+            .send_request(
+                reqwest::Method::POST,
+                "/completion-messages",
+                Some(data),
+                None,
+                response_mode == "streaming",
+            )
+            .await
+    }
+}
+
+impl From<DifyClient> for CompletionClient {
+    fn from(value: DifyClient) -> Self {
+        Arc::new(value).into()
+    }
+}
+
+impl From<Arc<DifyClient>> for CompletionClient {
+    fn from(value: Arc<DifyClient>) -> Self {
+        CompletionClient { dify_client: value }
+    }
+}
@@ -0,0 +1,1071 @@
+use anyhow::{bail, Result};
+use reqwest::Response;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::{sync::Arc, time::Duration};
+
+use super::{
+    models::{IndexingStatus, Page, SegmentStatus},
+    paginator::Paginator,
+};
+use crate::{compat, error::ResponseExt, DatasetWriteGuard, DifyClient, DifyConfig, FileSource};
+
+pub mod models;
+mod split;
+
+pub use split::split_text;
+
+use models::{
+    ChildChunk, ChildChunkResponse, CreateDatasetRequest, Dataset, DatasetMetadata, DatasetTag,
+    DatasetTagsResponse, Document, DocumentIndexingStatus, DocumentMetadata, DocumentResponse,
+    IndexingFailed, IndexingStatusResponse, IndexingTechnique, KnowledgeTag, MetadataField,
+    MetadataType, MetadataValue, NewSegment, ProcessRule, RetrievalModel, RetrievalRecord,
+    RetrieveResponse, Segment, SegmentResponse, SegmentsResponse, SplitDocument,
+    SplitDocumentsFailed, UpdateDatasetRequest, UpdateSegmentRequest, UploadFile,
+};
+
+#[derive(Clone)]
+pub struct KnowledgeBaseClient {
+    dify_client: Arc<DifyClient>,
+    dataset_id: Option<String>,
+}
+
+impl KnowledgeBaseClient {
+    pub fn new(api_key: &str, base_url: Option<&str>, dataset_id: Option<&str>) -> Self {
+        Self {
+            dify_client: Arc::new(DifyClient::new(api_key, base_url)),
+            dataset_id: dataset_id.map(String::from),
+        }
+    }
+
+    pub fn from_config(config: &DifyConfig, dataset_id: Option<&str>) -> Result<Self> {
+        Ok(Self {
+            dify_client: Arc::new(DifyClient::from_config(config)?),
+            dataset_id: dataset_id.map(String::from),
+        })
+    }
+
+    /// Builds a client on a shared transport, e.g. one per dataset.
+    pub fn with_client(dify_client: Arc<DifyClient>, dataset_id: Option<&str>) -> Self {
+        Self {
+            dify_client,
+            dataset_id: dataset_id.map(String::from),
+        }
+    }
+
+    pub fn dify_client(&self) -> &Arc<DifyClient> {
+        &self.dify_client
+    }
+
+    pub fn dataset_id(&self) -> Option<&str> {
+        self.dataset_id.as_deref()
+    }
+
+    #[cfg(feature = "ingest")]
+    pub(crate) fn client(&self) -> &DifyClient {
+        &self.dify_client
+    }
+
+    fn get_dataset_id(&self) -> Result<&str> {
+        self.dataset_id
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("dataset_id is not set"))
+    }
+
+    /// Keeps the write helpers of this client and its clones off the dataset
+    /// until the guard is dropped. Fails with [`DatasetBusy`](crate::DatasetBusy)
+    /// after waiting `dataset_write_wait_secs`; without that limit set the
+    /// guard holds nothing.
+    ///
+    /// Only the write helpers (split documents, URL and PDF ingestion,
+    /// segment imports, re-embedding) take this lock; the plain endpoints
+    /// ignore it. It is not reentrant, so drop the guard before calling one
+    /// of those helpers on the same dataset.
+    pub async fn lock_dataset(&self) -> Result<DatasetWriteGuard> {
+        self.dify_client.lock_dataset(self.get_dataset_id()?).await
+    }
+
+    pub async fn create_dataset(&self, name: &str) -> Result<Response> {
+        self.create_dataset_raw(name).await?.check_status().await
+    }
+
+    pub async fn create_dataset_raw(&self, name: &str) -> Result<Response> {
+        let data = json!({
+            "name": name
+        });
+        self.dify_client
+            .send_request(reqwest::Method::POST, "/datasets", Some(data), None, false)
+            .await
+    }
+
+    pub async fn create_dataset_with_config(
+        &self,
+        request: &CreateDatasetRequest,
+    ) -> Result<Dataset> {
+        let data = serde_json::to_value(request)?;
+        let response = self
+            .dify_client
+            .send_request(reqwest::Method::POST, "/datasets", Some(data), None, false)
+            .await?
+            .check_status()
+            .await?;
+        Ok(response.json().await?)
+    }
+
+    pub async fn list_datasets(
+        &self,
+        page: u32,
+        limit: u32,
+        keyword: Option<&str>,
+    ) -> Result<Page<Dataset>> {
+        let mut params = json!({
+            "page": page,
+            "limit": limit
+        });
+        if let Some(keyword) = keyword {
+            params["keyword"] = Value::String(keyword.to_string());
+        }
+
+        let response = self
+            .dify_client
+            .send_request(reqwest::Method::GET, "/datasets", None, Some(params), false)
+            .await?
+            .check_status()
+            .await?;
+        Ok(response.json().await?)
+    }
+
+    pub fn paginate_datasets<'a>(
+        &'a self,
+        limit: u32,
+        keyword: Option<&'a str>,
+    ) -> Paginator<'a, Dataset> {
+        Paginator::by_page(move |page| self.list_datasets(page, limit, keyword))
+    }
+
+    pub async fn get_dataset(&self, dataset_id: &str) -> Result<Dataset> {
+        let response = self
+            .dify_client
+            .send_request(
+                reqwest::Method::GET,
+                &format!("/datasets/{}", dataset_id),
+                None,
+                None,
+                false,
+            )
+            .await?
+            .check_status()
+            .await?;
+        Ok(response.json().await?)
+    }
+
+    pub async fn update_dataset(
+        &self,
+        dataset_id: &str,
+        request: &UpdateDatasetRequest,
+    ) -> Result<Dataset> {
+        let data = serde_json::to_value(request)?;
+        let response = self
+            .dify_client
+            .send_request(
+                reqwest::Method::PATCH,
+                &format!("/datasets/{}", dataset_id),
+                Some(data),
+                None,
+                false,
+            )
+            .await?
+            .check_status()
+            .await?;
+        Ok(response.json().await?)
+    }
+
+    pub async fn delete_dataset(&self, dataset_id: &str) -> Result<()> {
+        self.dify_client
+            .send_request(
+                reqwest::Method::DELETE,
+                &format!("/datasets/{}", dataset_id),
+                None,
+                None,
+                false,
+            )
+            .await?
+            .check_status()
+            .await?;
+        Ok(())
+    }
+
+    pub async fn create_document_by_text(
+        &self,
+        name: &str,
+        text: &str,
+        process_rule: ProcessRule,
+        indexing_technique: IndexingTechnique,
+    ) -> Result<DocumentResponse> {
+        let data = serde_json::to_value(DocumentCreatePayload {
+            name: Some(name),
+            text: Some(text),
+            indexing_technique,
+            process_rule,
+        })?;
+        let response = self
+            .dify_client
+            .send_request(
+                reqwest::Method::POST,
+                &format!(
+                    "/datasets/{}/document/create-by-text",
+                    self.get_dataset_id()?
+                ),
+                Some(data),
+                None,
+                false,
+            )
+            .await?
+            .check_status()
+            .await?;
+        Ok(response.json().await?)
+    }
+
+    /// Like [`create_document_by_text`](Self::create_document_by_text), but
+    /// splits `text` longer than `max_len` bytes with [`split_text`] into
+    /// documents named `"{name} (1/3)"` and so on. `metadata` is set on every
+    /// created document.
+    ///
+    /// When a part or the metadata fails, the error carries a
+    /// [`SplitDocumentsFailed`] with the documents created so far, so they can
+    /// be retried or deleted.
+    pub async fn create_documents_by_text_split(
+        &self,
+        name: &str,
+        text: &str,
+        process_rule: ProcessRule,
+        indexing_technique: IndexingTechnique,
+        max_len: usize,
+        metadata: &[MetadataValue],
+    ) -> Result<Vec<SplitDocument>> {
+        let _guard = self.lock_dataset().await?;
+        let parts = split_text(text, max_len);
+        let count = parts.len();
+
+        let mut documents = Vec::with_capacity(count);
+        let mut offset = 0;
+        for (index, part) in parts.into_iter().enumerate() {
+            let part_name = if count == 1 {
+                name.to_string()
+            } else {
+                format!("{} ({}/{})", name, index + 1, count)
+            };
+            let response = match self
+                .create_document_by_text(
+                    &part_name,
+                    part,
+                    process_rule.clone(),
+                    indexing_technique.clone(),
+                )
+                .await
+            {
+                Ok(response) => response,
+                Err(err) => {
+                    return Err(err.context(SplitDocumentsFailed {
+                        documents,
+                        parts: count,
+                    }))
+                }
+            };
+            documents.push(SplitDocument {
+                name: part_name,
+                offset,
+                len: part.len(),
+                response,
+            });
+            offset += part.len();
+        }
+
+        if !metadata.is_empty() {
+            let updates: Vec<_> = documents
+                .iter()
+                .map(|document| DocumentMetadata {
+                    document_id: document.response.document.id.to_string(),
+                    metadata_list: metadata.to_vec(),
+                })
+                .collect();
+            if let Err(err) = self.update_documents_metadata(&updates).await {
+                return Err(err.context(SplitDocumentsFailed {
+                    documents,
+                    parts: count,
+                }));
+            }
+        }
+
+        Ok(documents)
+    }
+
+    pub async fn create_document_by_file(
+        &self,
+        file: impl Into<FileSource>,
+        process_rule: ProcessRule,
+        indexing_technique: IndexingTechnique,
+    ) -> Result<DocumentResponse> {
+        let data = serde_json::to_string(&DocumentCreatePayload {
+            name: None,
+            text: None,
+            indexing_technique,
+            process_rule,
+        })?;
+        let response = self
+            .dify_client
+            .send_request_with_files(
+                reqwest::Method::POST,
+                &format!(
+                    "/datasets/{}/document/create-by-file",
+                    self.get_dataset_id()?
+                ),
+                vec![("data", data)],
+                file.into(),
+            )
+            .await?
+            .check_status()
+            .await?;
+        Ok(response.json().await?)
+    }
+
+    pub async fn list_documents(
+        &self,
+        keyword: Option<&str>,
+        page: u32,
+        limit: u32,
+    ) -> Result<Page<Document>> {
+        let mut params = json!({
+            "page": page,
+            "limit": limit
+        });
+        if let Some(keyword) = keyword {
+            params["keyword"] = Value::String(keyword.to_string());
+        }
+
+        let response = self
+            .dify_client
+            .send_request(
+                reqwest::Method::GET,
+                &format!("/datasets/{}/documents", self.get_dataset_id()?),
+                None,
+                Some(params),
+                false,
+            )
+            .await?
+            .check_status()
+            .await?;
+        Ok(response.json().await?)
+    }
+
+    pub fn paginate_documents<'a>(
+        &'a self,
+        keyword: Option<&'a str>,
+        limit: u32,
+    ) -> Paginator<'a, Document> {
+        Paginator::by_page(move |page| self.list_documents(keyword, page, limit))
+    }
+
+    pub async fn update_document_by_text(
+        &self,
+        document_id: &str,
+        name: Option<&str>,
+        text: Option<&str>,
+        process_rule: Option<ProcessRule>,
+    ) -> Result<DocumentResponse> {
+        let data = serde_json::to_value(DocumentUpdatePayload {
+            name,
+            text,
+            process_rule,
+        })?;
+        let response = self
+            .dify_client
+            .send_request(
+                reqwest::Method::POST,
+                &format!(
+                    "/datasets/{}/documents/{}/update-by-text",
+                    self.get_dataset_id()?,
+                    document_id
+                ),
+                Some(data),
+                None,
+                false,
+            )
+            .await?
+            .check_status()
+            .await?;
+        Ok(response.json().await?)
+    }
+
+    pub async fn update_document_by_file(
+        &self,
+        document_id: &str,
+        file: impl Into<FileSource>,
+        name: Option<&str>,
+        process_rule: Option<ProcessRule>,
+    ) -> Result<DocumentResponse> {
+        let data = serde_json::to_string(&DocumentUpdatePayload {
+            name,
+            text: None,
+            process_rule,
+        })?;
+        let response = self
+            .dify_client
+            .send_request_with_files(
+                reqwest::Method::POST,
+                &format!(
+                    "/datasets/{}/documents/{}/update-by-file",
+                    self.get_dataset_id()?,
+                    document_id
+                ),
+                vec![("data", data)],
+                file.into(),
+            )
+            .await?
+            .check_status()
+            .await?;
+        Ok(response.json().await?)
+    }
+
+    pub async fn delete_document(&self, document_id: &str) -> Result<()> {
+        self.dify_client
+            .send_request(
+                reqwest::Method::DELETE,
+                &format!(
+                    "/datasets/{}/documents/{}",
+                    self.get_dataset_id()?,
+                    document_id
+                ),
+                None,
+                None,
+                false,
+            )
+            .await?
+            .check_status()
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_document_upload_file(&self, document_id: &str) -> Result<UploadFile> {
+        let response = self
+            .dify_client
+            .send_request(
+                reqwest::Method::GET,
+                &format!(
+                    "/datasets/{}/documents/{}/upload-file",
+                    self.get_dataset_id()?,
+                    document_id
+                ),
+                None,
+                None,
+                false,
+            )
+            .await?
+            .check_status()
+            .await?;
+        Ok(response.json().await?)
+    }
+
+    pub async fn add_segments(
+        &self,
+        document_id: &str,
+        segments: &[NewSegment],
+    ) -> Result<Vec<Segment>> {
+        let data = json!({
+            "segments": segments
+        });
+        let response = self
+            .dify_client
+            .send_request(
+                reqwest::Method::POST,
+                &self.segments_endpoint(document_id)?,
+                Some(data),
+                None,
+                false,
+            )
+            .await?
+            .check_status()
+            .await?;
+        let segments: SegmentsResponse = response.json().await?;
+        Ok(segments.data)
+    }
+
+    pub async fn list_segments(
+        &self,
+        document_id: &str,
+        keyword: Option<&str>,
+        status: Option<SegmentStatus>,
+        page: u32,
+        limit: u32,
+    ) -> Result<Page<Segment>> {
+        let mut params = json!({
+            "page": page,
+            "limit": limit
+        });
+        if let Some(keyword) = keyword {
+            params["keyword"] = Value::String(keyword.to_string());
+        }
+        if let Some(status) = status {
+            params["status"] = Value::String(status.to_string());
+        }
+
+        let response = self
+            .dify_client
+            .send_request(
+                reqwest::Method::GET,
+                &self.segments_endpoint(document_id)?,
+                None,
+                Some(params),
+                false,
+            )
+            .await?
+            .check_status()
+            .await?;
+        Ok(response.json().await?)
+    }
+
+    pub fn paginate_segments<'a>(
+        &'a self,
+        document_id: &'a str,
+        keyword: Option<&'a str>,
+        status: Option<SegmentStatus>,
+        limit: u32,
+    ) -> Paginator<'a, Segment> {
+        Paginator::by_page(move |page| {
+            self.list_segments(document_id, keyword, status.clone(), page, limit)
+        })
+    }
+
+    pub async fn update_segment(
+        &self,
+        document_id: &str,
+        segment_id: &str,
+        request: &UpdateSegmentRequest,
+    ) -> Result<Segment> {
+        let data = json!({
+            "segment": request
+        });
+        let response = self
+            .dify_client
+            .send_request(
+                reqwest::Method::POST,
+                &format!("{}/{}", self.segments_endpoint(document_id)?, segment_id),
+                Some(data),
+                None,
+                false,
+            )
+            .await?
+            .check_status()
+            .await?;
+        let segment: SegmentResponse = response.json().await?;
+        Ok(segment.data)
+    }
+
+    pub async fn delete_segment(&self, document_id: &str, segment_id: &str) -> Result<()> {
+        self.dify_client
+            .send_request(
+                reqwest::Method::DELETE,
+                &format!("{}/{}", self.segments_endpoint(document_id)?, segment_id),
+                None,
+                None,
+                false,
+            )
+            .await?
+            .check_status()
+            .await?;
+        Ok(())
+    }
+
+    pub async fn create_child_chunk(
+        &self,
+        document_id: &str,
+        segment_id: &str,
+        content: &str,
+    ) -> Result<ChildChunk> {
+        let data = json!({
+            "content": content
+        });
+        let response = self
+            .dify_client
+            .send_request(
+                reqwest::Method::POST,
+                &self.child_chunks_endpoint(document_id, segment_id)?,
+                Some(data),
+                None,
+                false,
+            )
+            .await?
+            .check_status()
+            .await?;
+        let chunk: ChildChunkResponse = response.json().await?;
+        Ok(chunk.data)
+    }
+
+    pub async fn list_child_chunks(
+        &self,
+        document_id: &str,
+        segment_id: &str,
+        keyword: Option<&str>,
+        page: u32,
+        limit: u32,
+    ) -> Result<Page<ChildChunk>> {
+        let mut params = json!({
+            "page": page,
+            "limit": limit
+        });
+        if let Some(keyword) = keyword {
+            params["keyword"] = Value::String(keyword.to_string());
+        }
+
+        let response = self
+            .dify_client
+            .send_request(
+                reqwest::Method::GET,
+                &self.child_chunks_endpoint(document_id, segment_id)?,
+                None,
+                Some(params),
+                false,
+            )
+            .await?
+            .check_status()
+            .await?;
+        Ok(response.json().await?)
+    }
+
+    pub async fn update_child_chunk(
+        &self,
+        document_id: &str,
+        segment_id: &str,
+        child_chunk_id: &str,
+        content: &str,
+    ) -> Result<ChildChunk> {
+        let data = json!({
+            "content": content
+        });
+        let response = self
+            .dify_client
+            .send_request(
+                reqwest::Method::PATCH,
+                &format!(
+                    "{}/{}",
+                    self.child_chunks_endpoint(document_id, segment_id)?,
+                    child_chunk_id
+                ),
+                Some(data),
+                None,
+                false,
+            )
+            .await?
+            .check_status()
+            .await?;
+        let chunk: ChildChunkResponse = response.json().await?;
+        Ok(chunk.data)
+    }
+
+    pub async fn delete_child_chunk(
+        &self,
+        document_id: &str,
+        segment_id: &str,
+        child_chunk_id: &str,
+    ) -> Result<()> {
+        self.dify_client
+            .send_request(
+                reqwest::Method::DELETE,
+                &format!(
+                    "{}/{}",
+                    self.child_chunks_endpoint(document_id, segment_id)?,
+                    child_chunk_id
+                ),
+                None,
+                None,
+                false,
+            )
+            .await?
+            .check_status()
+            .await?;
+        Ok(())
+    }
+
+    fn segments_endpoint(&self, document_id: &str) -> Result<String> {
+        Ok(format!(
+            "/datasets/{}/documents/{}/segments",
+            self.get_dataset_id()?,
+            document_id
+        ))
+    }
+
+    fn child_chunks_endpoint(&self, document_id: &str, segment_id: &str) -> Result<String> {
+        Ok(format!(
+            "{}/{}/child_chunks",
+            self.segments_endpoint(document_id)?,
+            segment_id
+        ))
+    }
+
+    /// Runs a retrieval (hit test) against the dataset without going through
+    /// an app. Uses the dataset's own retrieval settings when
+    /// `retrieval_model` is `None`.
+    pub async fn retrieve(
+        &self,
+        query: &str,
+        retrieval_model: Option<&RetrievalModel>,
+    ) -> Result<Vec<RetrievalRecord>> {
+        let mut data = json!({
+            "query": query
+        });
+        if let Some(retrieval_model) = retrieval_model {
+            data["retrieval_model"] = serde_json::to_value(retrieval_model)?;
+        }
+
+        let response = self
+            .dify_client
+            .send_request(
+                reqwest::Method::POST,
+                &format!("/datasets/{}/retrieve", self.get_dataset_id()?),
+                Some(data),
+                None,
+                false,
+            )
+            .await?
+            .check_status()
+            .await?;
+        let retrieved: RetrieveResponse = response.json().await?;
+        Ok(retrieved.records)
+    }
+
+    pub async fn create_metadata_field(
+        &self,
+        name: &str,
+        field_type: MetadataType,
+    ) -> Result<MetadataField> {
+        let data = json!({
+            "type": field_type,
+            "name": name
+        });
+        let response = self
+            .dify_client
+            .send_request(
+                reqwest::Method::POST,
+                &format!("/datasets/{}/metadata", self.get_dataset_id()?),
+                Some(data),
+                None,
+                false,
+            )
+            .await?
+            .check_status()
+            .await?;
+        Ok(response.json().await?)
+    }
+
+    pub async fn list_metadata_fields(&self) -> Result<DatasetMetadata> {
+        let response = self
+            .dify_client
+            .send_request(
+                reqwest::Method::GET,
+                &format!("/datasets/{}/metadata", self.get_dataset_id()?),
+                None,
+                None,
+                false,
+            )
+            .await?
+            .check_status()
+            .await?;
+        Ok(response.json().await?)
+    }
+
+    pub async fn rename_metadata_field(
+        &self,
+        metadata_id: &str,
+        name: &str,
+    ) -> Result<MetadataField> {
+        let data = json!({
+            "name": name
+        });
+        let response = self
+            .dify_client
+            .send_request(
+                reqwest::Method::PATCH,
+                &format!(
+                    "/datasets/{}/metadata/{}",
+                    self.get_dataset_id()?,
+                    metadata_id
+                ),
+                Some(data),
+                None,
+                false,
+            )
+            .await?
+            .check_status()
+            .await?;
+        Ok(response.json().await?)
+    }
+
+    pub async fn delete_metadata_field(&self, metadata_id: &str) -> Result<()> {
+        self.dify_client
+            .send_request(
+                reqwest::Method::DELETE,
+                &format!(
+                    "/datasets/{}/metadata/{}",
+                    self.get_dataset_id()?,
+                    metadata_id
+                ),
+                None,
+                None,
+                false,
+            )
+            .await?
+            .check_status()
+            .await?;
+        Ok(())
+    }
+
+    /// Enables or disables the built-in metadata fields (document name,
+    /// uploader, upload date, ...).
+    pub async fn set_built_in_metadata(&self, enabled: bool) -> Result<()> {
+        let action = if enabled { "enable" } else { "disable" };
+        self.dify_client
+            .send_request(
+                reqwest::Method::POST,
+                &format!(
+                    "/datasets/{}/metadata/built-in/{}",
+                    self.get_dataset_id()?,
+                    action
+                ),
+                None,
+                None,
+                false,
+            )
+            .await?
+            .check_status()
+            .await?;
+        Ok(())
+    }
+
+    pub async fn update_documents_metadata(&self, documents: &[DocumentMetadata]) -> Result<()> {
+        let data = json!({
+            "operation_data": documents
+        });
+        self.dify_client
+            .send_request(
+                reqwest::Method::POST,
+                &format!("/datasets/{}/documents/metadata", self.get_dataset_id()?),
+                Some(data),
+                None,
+                false,
+            )
+            .await?
+            .check_status()
+            .await?;
+        Ok(())
+    }
+
+    pub async fn create_tag(&self, name: &str) -> Result<KnowledgeTag> {
+        let data = json!({
+            "name": name
+        });
+        let response = self
+            .dify_client
+            .send_request(
+                reqwest::Method::POST,
+                "/datasets/tags",
+                Some(data),
+                None,
+                false,
+            )
+            .await?
+            .check_status()
+            .await?;
+        Ok(response.json().await?)
+    }
+
+    pub async fn list_tags(&self) -> Result<Vec<KnowledgeTag>> {
+        let response = self
+            .dify_client
+            .send_request(reqwest::Method::GET, "/datasets/tags", None, None, false)
+            .await?
+            .check_status()
+            .await?;
+        Ok(response.json().await?)
+    }
+
+    pub async fn rename_tag(&self, tag_id: &str, name: &str) -> Result<KnowledgeTag> {
+        let data = json!({
+            "tag_id": tag_id,
+            "name": name
+        });
+        let response = self
+            .dify_client
+            .send_request(
+                reqwest::Method::PATCH,
+                "/datasets/tags",
+                Some(data),
+                None,
+                false,
+            )
+            .await?
+            .check_status()
+            .await?;
+        Ok(response.json().await?)
+    }
+
+    pub async fn delete_tag(&self, tag_id: &str) -> Result<()> {
+        let data = json!({
+            "tag_id": tag_id
+        });
+        self.dify_client
+            .send_request(
+                reqwest::Method::DELETE,
+                "/datasets/tags",
+                Some(data),
+                None,
+                false,
+            )
+            .await?
+            .check_status()
+            .await?;
+        Ok(())
+    }
+
+    /// Binds tags to the client's dataset.
+    pub async fn bind_tags(&self, tag_ids: &[&str]) -> Result<()> {
+        let data = json!({
+            "tag_ids": tag_ids,
+            "target_id": self.get_dataset_id()?
+        });
+        self.dify_client
+            .send_request(
+                reqwest::Method::POST,
+                "/datasets/tags/binding",
+                Some(data),
+                None,
+                false,
+            )
+            .await?
+            .check_status()
+            .await?;
+        Ok(())
+    }
+
+    pub async fn unbind_tag(&self, tag_id: &str) -> Result<()> {
+        let data = json!({
+            "tag_id": tag_id,
+            "target_id": self.get_dataset_id()?
+        });
+        self.dify_client
+            .send_request(
+                reqwest::Method::POST,
+                "/datasets/tags/unbinding",
+                Some(data),
+                None,
+                false,
+            )
+            .await?
+            .check_status()
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_dataset_tags(&self) -> Result<Vec<DatasetTag>> {
+        let response = self
+            .dify_client
+            .send_request(
+                reqwest::Method::GET,
+                &format!("/datasets/{}/tags", self.get_dataset_id()?),
+                None,
+                None,
+                false,
+            )
+            .await?
+            .check_status()
+            .await?;
+        let tags: DatasetTagsResponse = response.json().await?;
+        Ok(tags.data)
+    }
+
+    pub async fn get_indexing_status(&self, batch: &str) -> Result<Vec<DocumentIndexingStatus>> {
+        let response = self
+            .dify_client
+            .send_request(
+                reqwest::Method::GET,
+                &format!(
+                    "/datasets/{}/documents/{}/indexing-status",
+                    self.get_dataset_id()?,
+                    batch
+                ),
+                None,
+                None,
+                false,
+            )
+            .await?
+            .check_status()
+            .await?;
+        let status: IndexingStatusResponse = response.json().await?;
+        Ok(status.data)
+    }
+
+    /// Polls the indexing status of `batch` until every document is completed.
+    ///
+    /// Fails with [`IndexingFailed`] as soon as documents report an error or are
+    /// paused, since paused documents only resume when someone resumes them,
+    /// and fails when `timeout` elapses.
+    pub async fn wait_until_indexed(
+        &self,
+        batch: &str,
+        poll_interval: Duration,
+        timeout: Duration,
+    ) -> Result<Vec<DocumentIndexingStatus>> {
+        let deadline = compat::Instant::now() + timeout;
+        loop {
+            let documents = self.get_indexing_status(batch).await?;
+            let failed: Vec<_> = documents
+                .iter()
+                .filter(|document| {
+                    matches!(
+                        document.indexing_status,
+                        IndexingStatus::Error | IndexingStatus::Paused
+                    )
+                })
+                .cloned()
+                .collect();
+            if !failed.is_empty() {
+                return Err(IndexingFailed {
+                    batch: batch.to_string(),
+                    documents: failed,
+                }
+                .into());
+            }
+            if !documents.is_empty()
+                && documents
+                    .iter()
+                    .all(|document| document.indexing_status == IndexingStatus::Completed)
+            {
+                return Ok(documents);
+            }
+
+            if compat::Instant::now() + poll_interval > deadline {
+                bail!("timed out waiting for batch {} to be indexed", batch);
+            }
+            compat::sleep(poll_interval).await;
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct DocumentCreatePayload<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<&'a str>,
+    indexing_technique: IndexingTechnique,
+    process_rule: ProcessRule,
+}
+
+#[derive(Serialize)]
+struct DocumentUpdatePayload<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    process_rule: Option<ProcessRule>,
+}
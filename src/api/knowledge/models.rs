@@ -0,0 +1,653 @@
+use serde::{de, Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
+use std::fmt;
+
+use crate::api::models::{
+    string_enum, DisplayStatus, Id, IndexingStatus, SegmentStatus, Timestamp,
+};
+
+string_enum!(DatasetPermission {
+    OnlyMe => "only_me",
+    AllTeamMembers => "all_team_members",
+    PartialMembers => "partial_members",
+});
+
+string_enum!(IndexingTechnique {
+    HighQuality => "high_quality",
+    Economy => "economy",
+});
+
+string_enum!(ChildChunkType {
+    Automatic => "automatic",
+    Customized => "customized",
+});
+
+string_enum!(MetadataType {
+    String => "string",
+    Number => "number",
+    Time => "time",
+});
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct Dataset {
+    pub id: Id,
+    pub name: String,
+    pub description: Option<String>,
+    pub provider: String,
+    pub permission: DatasetPermission,
+    pub data_source_type: Option<String>,
+    pub indexing_technique: Option<IndexingTechnique>,
+    pub app_count: u64,
+    pub document_count: u64,
+    pub word_count: u64,
+    pub created_by: Id,
+    pub created_at: Timestamp,
+    pub updated_by: Id,
+    pub updated_at: Timestamp,
+    pub embedding_model: Option<String>,
+    pub embedding_model_provider: Option<String>,
+    pub embedding_available: Option<bool>,
+    pub retrieval_model_dict: Option<Value>,
+    pub tags: Vec<Value>,
+    pub doc_form: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct UpdateDatasetRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indexing_technique: Option<IndexingTechnique>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permission: Option<DatasetPermission>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub embedding_model_provider: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub embedding_model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retrieval_model: Option<RetrievalModel>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub partial_member_list: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct CreateDatasetRequest {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indexing_technique: Option<IndexingTechnique>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permission: Option<DatasetPermission>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_knowledge_api_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_knowledge_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub embedding_model_provider: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub embedding_model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retrieval_model: Option<RetrievalModel>,
+}
+
+impl CreateDatasetRequest {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+string_enum!(SearchMethod {
+    KeywordSearch => "keyword_search",
+    SemanticSearch => "semantic_search",
+    FullTextSearch => "full_text_search",
+    HybridSearch => "hybrid_search",
+});
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct RetrievalModel {
+    pub search_method: SearchMethod,
+    #[serde(default)]
+    pub reranking_enable: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reranking_mode: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reranking_model: Option<RerankingModel>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weights: Option<Value>,
+    #[serde(default)]
+    pub top_k: u32,
+    #[serde(default)]
+    pub score_threshold_enabled: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score_threshold: Option<f64>,
+}
+
+impl Default for RetrievalModel {
+    fn default() -> Self {
+        Self {
+            search_method: SearchMethod::SemanticSearch,
+            reranking_enable: false,
+            reranking_mode: None,
+            reranking_model: None,
+            weights: None,
+            top_k: 3,
+            score_threshold_enabled: false,
+            score_threshold: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct RerankingModel {
+    pub reranking_provider_name: String,
+    pub reranking_model_name: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum ProcessRule {
+    Automatic,
+    Custom { rules: ProcessRuleRules },
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ProcessRuleRules {
+    #[serde(default)]
+    pub pre_processing_rules: Vec<PreProcessingRule>,
+    pub segmentation: Segmentation,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+string_enum!(PreProcessingRuleId {
+    RemoveExtraSpaces => "remove_extra_spaces",
+    RemoveUrlsEmails => "remove_urls_emails",
+});
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct PreProcessingRule {
+    pub id: PreProcessingRuleId,
+    pub enabled: bool,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct Segmentation {
+    pub separator: String,
+    pub max_tokens: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_overlap: Option<u32>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Default for Segmentation {
+    fn default() -> Self {
+        Self {
+            separator: "\n".to_string(),
+            max_tokens: 500,
+            chunk_overlap: None,
+            extra: Map::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct Document {
+    pub id: Id,
+    pub position: u32,
+    pub data_source_type: String,
+    pub data_source_info: Option<Value>,
+    pub dataset_process_rule_id: Option<Id>,
+    pub name: String,
+    pub created_from: String,
+    pub created_by: Id,
+    pub created_at: Timestamp,
+    pub tokens: u64,
+    pub indexing_status: IndexingStatus,
+    pub error: Option<String>,
+    pub enabled: bool,
+    pub disabled_at: Option<Timestamp>,
+    pub disabled_by: Option<Id>,
+    pub archived: bool,
+    pub display_status: Option<DisplayStatus>,
+    pub word_count: u64,
+    pub hit_count: u64,
+    pub doc_form: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct DocumentResponse {
+    pub document: Document,
+    pub batch: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// One document created from a part of a longer text, see
+/// [`KnowledgeBaseClient::create_documents_by_text_split`](crate::KnowledgeBaseClient::create_documents_by_text_split).
+#[derive(Debug, Clone)]
+pub struct SplitDocument {
+    pub name: String,
+    /// Byte offset of the part in the original text.
+    pub offset: usize,
+    pub len: usize,
+    pub response: DocumentResponse,
+}
+
+/// [`KnowledgeBaseClient::create_documents_by_text_split`](crate::KnowledgeBaseClient::create_documents_by_text_split)
+/// failed after it started creating documents. Get it with
+/// `err.downcast_ref::<SplitDocumentsFailed>()`; the cause follows it in the
+/// error chain.
+#[derive(Debug, Clone)]
+pub struct SplitDocumentsFailed {
+    /// The documents created before the failure, which stay on the server.
+    /// Their metadata has not been set.
+    pub documents: Vec<SplitDocument>,
+    /// The number of parts the text was split into.
+    pub parts: usize,
+}
+
+impl fmt::Display for SplitDocumentsFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.documents.len() < self.parts {
+            write!(
+                f,
+                "created {} of {} documents",
+                self.documents.len(),
+                self.parts
+            )
+        } else {
+            write!(
+                f,
+                "created all {} documents, but failed to set their metadata",
+                self.parts
+            )
+        }
+    }
+}
+
+/// [`KnowledgeBaseClient::wait_until_indexed`](crate::KnowledgeBaseClient::wait_until_indexed)
+/// stopped because documents of the batch failed or were paused. Get it with
+/// `err.downcast_ref::<IndexingFailed>()`.
+#[derive(Debug, Clone)]
+pub struct IndexingFailed {
+    pub batch: String,
+    /// The documents with an `error` or `paused` status.
+    pub documents: Vec<DocumentIndexingStatus>,
+}
+
+impl fmt::Display for IndexingFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "indexing of batch {} stopped:", self.batch)?;
+        for (i, document) in self.documents.iter().enumerate() {
+            let separator = if i == 0 { " " } else { "; " };
+            match document.indexing_status {
+                IndexingStatus::Paused => {
+                    write!(f, "{}document {} was paused", separator, document.id)?
+                }
+                _ => write!(
+                    f,
+                    "{}document {} failed: {}",
+                    separator,
+                    document.id,
+                    document.error.as_deref().unwrap_or_default()
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for IndexingFailed {}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct DocumentIndexingStatus {
+    pub id: Id,
+    pub indexing_status: IndexingStatus,
+    pub processing_started_at: Option<Timestamp>,
+    pub parsing_completed_at: Option<Timestamp>,
+    pub cleaning_completed_at: Option<Timestamp>,
+    pub splitting_completed_at: Option<Timestamp>,
+    pub completed_at: Option<Timestamp>,
+    pub paused_at: Option<Timestamp>,
+    pub error: Option<String>,
+    pub stopped_at: Option<Timestamp>,
+    pub completed_segments: u64,
+    pub total_segments: u64,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct IndexingStatusResponse {
+    pub data: Vec<DocumentIndexingStatus>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct Segment {
+    pub id: Id,
+    pub position: u32,
+    pub document_id: Id,
+    pub content: String,
+    pub answer: Option<String>,
+    pub word_count: u64,
+    pub tokens: u64,
+    pub keywords: Vec<String>,
+    pub index_node_id: Option<String>,
+    pub index_node_hash: Option<String>,
+    pub hit_count: u64,
+    pub enabled: bool,
+    pub disabled_at: Option<Timestamp>,
+    pub disabled_by: Option<Id>,
+    pub status: SegmentStatus,
+    pub created_by: Id,
+    pub created_at: Timestamp,
+    pub indexing_at: Option<Timestamp>,
+    pub completed_at: Option<Timestamp>,
+    pub error: Option<String>,
+    pub stopped_at: Option<Timestamp>,
+    /// The owning document, only included in retrieval results.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub document: Option<SegmentDocument>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct SegmentDocument {
+    pub id: Id,
+    pub data_source_type: String,
+    pub name: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A segment to add to a document. `answer` is only used by documents in
+/// Q&A mode.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct NewSegment {
+    pub content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub answer: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+}
+
+impl NewSegment {
+    pub fn new(content: &str) -> Self {
+        Self {
+            content: content.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct UpdateSegmentRequest {
+    pub content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub answer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keywords: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+}
+
+impl UpdateSegmentRequest {
+    pub fn new(content: &str) -> Self {
+        Self {
+            content: content.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct SegmentsResponse {
+    pub data: Vec<Segment>,
+    pub doc_form: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct SegmentResponse {
+    pub data: Segment,
+    pub doc_form: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A child chunk of a segment, in datasets using parent-child (hierarchical)
+/// chunking.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct ChildChunk {
+    pub id: Id,
+    pub segment_id: Id,
+    pub content: String,
+    pub position: u32,
+    pub word_count: u64,
+    #[serde(rename = "type")]
+    pub chunk_type: ChildChunkType,
+    pub created_at: Timestamp,
+    pub updated_at: Option<Timestamp>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct ChildChunkResponse {
+    pub data: ChildChunk,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct RetrievalRecord {
+    pub segment: Segment,
+    pub score: Option<f64>,
+    pub tsne_position: Option<Value>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A retrieval record in the framework-neutral `content`/`metadata`/`score`
+/// shape used by RAG libraries.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetrievedDocument {
+    pub content: String,
+    pub metadata: Map<String, Value>,
+    pub score: Option<f64>,
+}
+
+impl From<RetrievalRecord> for RetrievedDocument {
+    fn from(record: RetrievalRecord) -> Self {
+        let segment = record.segment;
+        let mut metadata = Map::new();
+        metadata.insert("segment_id".into(), segment.id.into_string().into());
+        metadata.insert(
+            "document_id".into(),
+            segment.document_id.into_string().into(),
+        );
+        metadata.insert("position".into(), segment.position.into());
+        metadata.insert("keywords".into(), segment.keywords.into());
+        if let Some(answer) = segment.answer {
+            metadata.insert("answer".into(), answer.into());
+        }
+        if let Some(document) = segment.document {
+            metadata.insert("document_name".into(), document.name.into());
+            metadata.insert("data_source_type".into(), document.data_source_type.into());
+        }
+
+        Self {
+            content: segment.content,
+            metadata,
+            score: record.score,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct RetrieveResponse {
+    pub query: RetrieveQuery,
+    pub records: Vec<RetrievalRecord>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct RetrieveQuery {
+    pub content: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A metadata field defined on a dataset.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct MetadataField {
+    pub id: Id,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub field_type: MetadataType,
+    /// Number of documents with a value for the field, only set when listing.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub count: Option<u64>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct DatasetMetadata {
+    pub doc_metadata: Vec<MetadataField>,
+    pub built_in_field_enabled: bool,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// The metadata values to set on one document.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct DocumentMetadata {
+    pub document_id: String,
+    pub metadata_list: Vec<MetadataValue>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct MetadataValue {
+    pub id: String,
+    pub name: String,
+    pub value: Value,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A knowledge-type tag used to organize datasets.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct KnowledgeTag {
+    pub id: Id,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub tag_type: String,
+    /// The number of datasets bound to the tag. Dify returns it as a string
+    /// when listing tags.
+    #[serde(deserialize_with = "lenient_count")]
+    pub binding_count: u64,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A tag as listed for one dataset.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct DatasetTag {
+    pub id: Id,
+    pub name: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct DatasetTagsResponse {
+    pub data: Vec<DatasetTag>,
+    pub total: u64,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// The source file a document was created from.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct UploadFile {
+    pub id: Id,
+    pub name: String,
+    pub size: u64,
+    pub extension: String,
+    pub url: Option<String>,
+    pub download_url: Option<String>,
+    pub mime_type: Option<String>,
+    pub created_by: Id,
+    pub created_at: Timestamp,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+fn lenient_count<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    match Value::deserialize(deserializer)? {
+        Value::Null => Ok(0),
+        Value::Number(number) => number
+            .as_u64()
+            .ok_or_else(|| de::Error::custom("count out of range")),
+        Value::String(value) => value
+            .trim()
+            .parse()
+            .map_err(|_| de::Error::invalid_value(de::Unexpected::Str(&value), &"a count")),
+        other => Err(de::Error::custom(format!("invalid count: {}", other))),
+    }
+}
@@ -0,0 +1,36 @@
+/// Splits `text` into parts of at most `max_len` bytes, preferring to break
+/// after a blank line, then after a line break, then after whitespace. Parts
+/// are only cut inside a word when it is longer than `max_len` by itself.
+pub fn split_text(text: &str, max_len: usize) -> Vec<&str> {
+    let max_len = max_len.max(1);
+    let mut parts = Vec::new();
+    let mut rest = text;
+    while rest.len() > max_len {
+        let mut limit = max_len;
+        while !rest.is_char_boundary(limit) {
+            limit -= 1;
+        }
+        if limit == 0 {
+            // a single character wider than `max_len`
+            limit = rest.chars().next().map_or(rest.len(), char::len_utf8);
+        }
+
+        let window = &rest[..limit];
+        let end = ["\n\n", "\n", " "]
+            .iter()
+            .find_map(|separator| {
+                window
+                    .rfind(separator)
+                    .map(|index| index + separator.len())
+                    .filter(|&end| end > 0)
+            })
+            .unwrap_or(limit);
+
+        parts.push(&rest[..end]);
+        rest = &rest[end..];
+    }
+    if !rest.is_empty() || parts.is_empty() {
+        parts.push(rest);
+    }
+    parts
+}
@@ -0,0 +1,686 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// Declares an enum for a string field of the API, keeping values this crate
+/// does not know about yet in `Other` instead of failing to deserialize.
+macro_rules! string_enum {
+    ($(#[$meta:meta])* $name:ident { $($variant:ident => $value:literal),+ $(,)? }) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($variant,)+
+            Other(String),
+        }
+
+        impl $name {
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(Self::$variant => $value,)+
+                    Self::Other(value) => value,
+                }
+            }
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self::Other(String::new())
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(value: &str) -> Self {
+                match value {
+                    $($value => Self::$variant,)+
+                    other => Self::Other(other.to_string()),
+                }
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl serde::Serialize for $name {
+            fn serialize<S: serde::Serializer>(
+                &self,
+                serializer: S,
+            ) -> std::result::Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(
+                deserializer: D,
+            ) -> std::result::Result<Self, D::Error> {
+                let value = <String as serde::Deserialize>::deserialize(deserializer)?;
+                Ok(Self::from(value.as_str()))
+            }
+        }
+    };
+}
+
+pub(crate) use string_enum;
+
+pub use crate::id::Id;
+pub use crate::timestamp::Timestamp;
+
+/// How chat, completion and workflow apps return their result.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ResponseMode {
+    #[default]
+    #[serde(rename = "blocking")]
+    Block,
+    #[serde(rename = "streaming")]
+    Stream,
+}
+impl ResponseMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Block => "blocking",
+            Self::Stream => "streaming",
+        }
+    }
+}
+
+impl std::fmt::Display for ResponseMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Parses the wire names, for callers migrating from string modes.
+impl std::str::FromStr for ResponseMode {
+    type Err = anyhow::Error;
+
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        match mode {
+            "blocking" => Ok(Self::Block),
+            "streaming" => Ok(Self::Stream),
+            _ => Err(anyhow::anyhow!("unknown response mode {:?}", mode)),
+        }
+    }
+}
+
+string_enum!(AppMode {
+    Completion => "completion",
+    Chat => "chat",
+    AgentChat => "agent-chat",
+    AdvancedChat => "advanced-chat",
+    Workflow => "workflow",
+});
+
+string_enum!(WorkflowStatus {
+    Running => "running",
+    Succeeded => "succeeded",
+    PartialSucceeded => "partial-succeeded",
+    Failed => "failed",
+    Stopped => "stopped",
+});
+
+string_enum!(IndexingStatus {
+    Waiting => "waiting",
+    Parsing => "parsing",
+    Cleaning => "cleaning",
+    Splitting => "splitting",
+    Indexing => "indexing",
+    Paused => "paused",
+    Error => "error",
+    Completed => "completed",
+});
+
+string_enum!(DisplayStatus {
+    Queuing => "queuing",
+    Indexing => "indexing",
+    Paused => "paused",
+    Error => "error",
+    Available => "available",
+    Disabled => "disabled",
+    Archived => "archived",
+});
+
+string_enum!(SegmentStatus {
+    Waiting => "waiting",
+    Indexing => "indexing",
+    Completed => "completed",
+    Error => "error",
+    Paused => "paused",
+});
+
+string_enum!(FileType {
+    Image => "image",
+    Document => "document",
+    Audio => "audio",
+    Video => "video",
+    Custom => "custom",
+});
+
+impl FileType {
+    /// Guesses the type from a MIME type, treating anything that is not an
+    /// image, audio or video as a document.
+    pub fn from_mime_type(mime_type: &str) -> Self {
+        match mime_type.split('/').next() {
+            Some("image") => Self::Image,
+            Some("audio") => Self::Audio,
+            Some("video") => Self::Video,
+            _ => Self::Document,
+        }
+    }
+}
+
+/// A file uploaded with [`DifyClient::upload_file`](crate::DifyClient::upload_file).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct UploadedFile {
+    pub id: Id,
+    pub name: String,
+    pub size: u64,
+    pub extension: String,
+    pub mime_type: String,
+    pub created_by: Id,
+    pub created_at: Timestamp,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A file passed to a chat, completion or workflow request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "transfer_method", rename_all = "snake_case")]
+pub enum FileInput {
+    RemoteUrl {
+        #[serde(rename = "type")]
+        file_type: FileType,
+        url: String,
+    },
+    /// A file uploaded with
+    /// [`DifyClient::file_upload`](crate::DifyClient::file_upload).
+    LocalFile {
+        #[serde(rename = "type")]
+        file_type: FileType,
+        upload_file_id: String,
+    },
+}
+
+impl FileInput {
+    pub fn remote_url(file_type: impl Into<FileType>, url: &str) -> Self {
+        Self::RemoteUrl {
+            file_type: file_type.into(),
+            url: url.to_string(),
+        }
+    }
+
+    pub fn local_file(file_type: impl Into<FileType>, upload_file_id: &str) -> Self {
+        Self::LocalFile {
+            file_type: file_type.into(),
+            upload_file_id: upload_file_id.to_string(),
+        }
+    }
+
+    pub fn file_type(&self) -> &FileType {
+        match self {
+            Self::RemoteUrl { file_type, .. } | Self::LocalFile { file_type, .. } => file_type,
+        }
+    }
+}
+
+/// The end-user rating of a message. `Revoke` clears a previous rating.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Rating {
+    Like,
+    Dislike,
+    #[default]
+    Revoke,
+}
+
+impl Rating {
+    pub fn as_str(&self) -> Option<&'static str> {
+        match self {
+            Self::Like => Some("like"),
+            Self::Dislike => Some("dislike"),
+            Self::Revoke => None,
+        }
+    }
+}
+
+/// Serializes `Revoke` as `null`, which is how the API clears a rating.
+impl Serialize for Rating {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.as_str().serialize(serializer)
+    }
+}
+
+/// Reads `null` (and `"revoke"`) back as `Revoke`.
+impl<'de> Deserialize<'de> for Rating {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match Option::<String>::deserialize(deserializer)?.as_deref() {
+            Some("like") => Ok(Self::Like),
+            Some("dislike") => Ok(Self::Dislike),
+            Some("revoke") | None => Ok(Self::Revoke),
+            Some(other) => Err(serde::de::Error::unknown_variant(
+                other,
+                &["like", "dislike", "revoke"],
+            )),
+        }
+    }
+}
+
+/// An end-user rating of a message, as listed by
+/// [`DifyClient::get_app_feedbacks`](crate::DifyClient::get_app_feedbacks).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct MessageFeedback {
+    pub id: Id,
+    pub app_id: Id,
+    pub conversation_id: Id,
+    pub message_id: Id,
+    /// `Revoke` when the rating was cleared.
+    pub rating: Rating,
+    pub content: Option<String>,
+    pub from_source: String,
+    pub from_end_user_id: Option<Id>,
+    pub from_account_id: Option<Id>,
+    pub created_at: Timestamp,
+    pub updated_at: Timestamp,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// One page of a `page`/`limit` paginated list endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct Page<T> {
+    #[serde(default = "Vec::new")]
+    pub data: Vec<T>,
+    #[serde(default)]
+    pub has_more: bool,
+    #[serde(default)]
+    pub limit: u32,
+    #[serde(default)]
+    pub total: u64,
+    #[serde(default)]
+    pub page: u32,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ChatMessageResponse {
+    #[serde(default)]
+    pub task_id: Id,
+    pub message_id: Id,
+    pub conversation_id: Id,
+    #[serde(default)]
+    pub mode: AppMode,
+    pub answer: String,
+    #[serde(default)]
+    pub metadata: MessageMetadata,
+    #[serde(default)]
+    pub created_at: Timestamp,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl ChatMessageResponse {
+    /// Whether the answer is a saved annotation, see
+    /// [`MessageMetadata::annotation_id`].
+    pub fn is_annotation_reply(&self) -> bool {
+        self.metadata.is_annotation_reply()
+    }
+}
+
+/// Token usage, the retrieved chunks and the annotation reply of an answer,
+/// from a blocking response or the `message_end` event of a stream.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct MessageMetadata {
+    pub usage: Option<Usage>,
+    pub retriever_resources: Vec<RetrieverResource>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub annotation_reply: Option<AnnotationReply>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl MessageMetadata {
+    /// Whether the answer is a saved annotation rather than a model
+    /// generation.
+    pub fn is_annotation_reply(&self) -> bool {
+        self.annotation_reply.is_some()
+    }
+
+    pub fn annotation_id(&self) -> Option<&str> {
+        self.annotation_reply
+            .as_ref()
+            .map(|annotation| annotation.id.as_str())
+    }
+}
+
+/// The annotation that was returned as the answer.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct AnnotationReply {
+    pub id: Id,
+    /// The account that created the annotation.
+    pub account: Option<AnnotationAccount>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct AnnotationAccount {
+    pub id: Id,
+    pub name: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Prices are decimal strings in `currency`, e.g. `"0.0012890"`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct Usage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+    pub prompt_price: String,
+    pub completion_price: String,
+    pub total_price: String,
+    pub currency: String,
+    /// Seconds the model took.
+    pub latency: f64,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Usage {
+    /// `total_price` as a number, `None` when it is missing or malformed.
+    pub fn total_cost(&self) -> Option<f64> {
+        self.total_price.parse().ok()
+    }
+}
+
+/// A knowledge chunk the answer was based on.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct RetrieverResource {
+    pub position: u32,
+    pub dataset_id: Id,
+    pub dataset_name: String,
+    pub document_id: Id,
+    pub document_name: String,
+    pub segment_id: Id,
+    pub score: Option<f64>,
+    pub content: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A question and its answer from the history of a conversation.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct HistoryMessage {
+    pub id: Id,
+    pub conversation_id: Id,
+    pub inputs: Value,
+    pub query: String,
+    pub answer: String,
+    pub message_files: Vec<Value>,
+    pub feedback: Option<Value>,
+    pub retriever_resources: Vec<Value>,
+    pub created_at: Timestamp,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct SuggestedQuestionsResponse {
+    #[serde(default)]
+    pub result: String,
+    pub data: Vec<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct AudioToTextResponse {
+    pub text: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct AppInfo {
+    pub name: String,
+    pub description: String,
+    pub tags: Vec<String>,
+    pub mode: AppMode,
+    pub author_name: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct AppMeta {
+    pub tool_icons: HashMap<String, ToolIcon>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ToolIcon {
+    Url(String),
+    Emoji { background: String, content: String },
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct AppSite {
+    pub title: String,
+    pub chat_color_theme: Option<String>,
+    pub chat_color_theme_inverted: bool,
+    pub icon_type: Option<String>,
+    pub icon: Option<String>,
+    pub icon_background: Option<String>,
+    pub icon_url: Option<String>,
+    pub description: Option<String>,
+    pub copyright: Option<String>,
+    pub privacy_policy: Option<String>,
+    pub custom_disclaimer: Option<String>,
+    pub default_language: String,
+    pub show_workflow_steps: bool,
+    pub use_icon_as_answer_icon: bool,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct AppParameters {
+    pub opening_statement: String,
+    pub suggested_questions: Vec<String>,
+    pub suggested_questions_after_answer: FeatureToggle,
+    pub speech_to_text: FeatureToggle,
+    pub text_to_speech: FeatureToggle,
+    pub retriever_resource: FeatureToggle,
+    pub annotation_reply: FeatureToggle,
+    pub more_like_this: FeatureToggle,
+    pub sensitive_word_avoidance: FeatureToggle,
+    pub user_input_form: Vec<Value>,
+    pub file_upload: Map<String, Value>,
+    pub system_parameters: SystemParameters,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl AppParameters {
+    /// The greeting shown before the first message, `None` when not set.
+    pub fn opening_statement(&self) -> Option<&str> {
+        Some(self.opening_statement.trim()).filter(|statement| !statement.is_empty())
+    }
+
+    /// The questions offered to start a new conversation.
+    pub fn opening_questions(&self) -> &[String] {
+        &self.suggested_questions
+    }
+
+    /// The opening statement and questions, `None` when the app has neither.
+    pub fn introduction(&self) -> Option<Introduction> {
+        let introduction = Introduction {
+            opening_statement: self.opening_statement().map(String::from),
+            suggested_questions: self.suggested_questions.clone(),
+        };
+        (introduction.opening_statement.is_some() || !introduction.suggested_questions.is_empty())
+            .then_some(introduction)
+    }
+
+    /// Whether users may attach files of any kind to their messages.
+    pub fn supports_file_upload(&self) -> bool {
+        self.file_upload.values().any(is_enabled)
+            || self.file_upload.get("enabled").and_then(Value::as_bool) == Some(true)
+    }
+
+    pub fn image_upload_enabled(&self) -> bool {
+        self.file_upload.get("image").is_some_and(is_enabled)
+    }
+
+    pub fn suggested_questions_enabled(&self) -> bool {
+        self.suggested_questions_after_answer.enabled
+    }
+
+    pub fn speech_to_text_enabled(&self) -> bool {
+        self.speech_to_text.enabled
+    }
+
+    pub fn text_to_speech_enabled(&self) -> bool {
+        self.text_to_speech.enabled
+    }
+
+    pub fn retriever_resource_enabled(&self) -> bool {
+        self.retriever_resource.enabled
+    }
+
+    pub fn annotation_reply_enabled(&self) -> bool {
+        self.annotation_reply.enabled
+    }
+
+    pub fn more_like_this_enabled(&self) -> bool {
+        self.more_like_this.enabled
+    }
+}
+
+/// What a new conversation starts with, as shown by the Dify WebApp.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct Introduction {
+    pub opening_statement: Option<String>,
+    pub suggested_questions: Vec<String>,
+}
+
+fn is_enabled(value: &Value) -> bool {
+    value.get("enabled").and_then(Value::as_bool) == Some(true)
+}
+
+/// An app feature that is switched on or off in the app's settings, with any
+/// feature specific options kept in `extra`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct FeatureToggle {
+    pub enabled: bool,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Upload size limits of the Dify instance, in megabytes.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct SystemParameters {
+    pub file_size_limit: u64,
+    pub image_file_size_limit: u64,
+    pub audio_file_size_limit: u64,
+    pub video_file_size_limit: u64,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct WorkflowRun {
+    pub id: Id,
+    pub workflow_id: Id,
+    pub status: WorkflowStatus,
+    /// Older Dify versions return `inputs` and `outputs` as JSON encoded
+    /// strings.
+    pub inputs: Value,
+    pub outputs: Value,
+    pub error: Option<String>,
+    pub total_steps: u64,
+    pub total_tokens: u64,
+    pub elapsed_time: f64,
+    pub created_at: Timestamp,
+    pub finished_at: Option<Timestamp>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// One entry of the workflow execution log.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct WorkflowLog {
+    pub id: Id,
+    pub workflow_run: WorkflowLogRun,
+    pub created_from: String,
+    pub created_by_role: String,
+    pub created_by_account: Option<Value>,
+    pub created_by_end_user: Option<EndUser>,
+    pub created_at: Timestamp,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct WorkflowLogRun {
+    pub id: Id,
+    pub version: String,
+    pub status: WorkflowStatus,
+    pub error: Option<String>,
+    pub elapsed_time: f64,
+    pub total_tokens: u64,
+    pub total_steps: u64,
+    pub created_at: Timestamp,
+    pub finished_at: Option<Timestamp>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct EndUser {
+    pub id: Id,
+    #[serde(rename = "type")]
+    pub user_type: String,
+    pub is_anonymous: bool,
+    pub session_id: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// The response of the service API root, `GET /`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct ServerInfo {
+    pub welcome: String,
+    pub api_version: String,
+    pub server_version: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
@@ -0,0 +1,136 @@
+use anyhow::Result;
+use futures_util::Stream;
+use std::{
+    collections::VecDeque,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use super::models::Page;
+use crate::compat::{BoxFuture, MaybeSend};
+
+type FetchPage<'a, T> = Box<dyn FnMut(Cursor) -> BoxFuture<'a, Result<Page<T>>> + Send + 'a>;
+
+/// Where the next page of a [`Paginator`] starts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cursor {
+    /// A 1-based page number.
+    Page(u32),
+    /// The id of the last item of the previous page, `None` for the first.
+    LastId(Option<String>),
+}
+
+/// Streams every item of a paginated list endpoint, fetching the next page
+/// when the current one is used up.
+///
+/// ```no_run
+/// # async fn example(client: dify_client_rust::KnowledgeBaseClient) -> anyhow::Result<()> {
+/// use futures_util::StreamExt;
+///
+/// let mut datasets = client.paginate_datasets(100, None);
+/// while let Some(dataset) = datasets.next().await {
+///     println!("{}", dataset?.name);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct Paginator<'a, T> {
+    fetch: FetchPage<'a, T>,
+    next_id: Option<fn(&T) -> String>,
+    cursor: Cursor,
+    items: VecDeque<T>,
+    request: Option<BoxFuture<'a, Result<Page<T>>>>,
+    done: bool,
+}
+
+impl<'a, T> Paginator<'a, T> {
+    /// Pages by number, starting at page 1.
+    pub fn by_page<F, Fut>(mut fetch: F) -> Self
+    where
+        F: FnMut(u32) -> Fut + Send + 'a,
+        Fut: Future<Output = Result<Page<T>>> + MaybeSend + 'a,
+    {
+        Self::new(
+            Cursor::Page(1),
+            None,
+            Box::new(move |cursor| match cursor {
+                Cursor::Page(page) => Box::pin(fetch(page)),
+                Cursor::LastId(_) => unreachable!("page paginator got an id cursor"),
+            }),
+        )
+    }
+
+    /// Pages by the id of the last item seen, as returned by `id`.
+    pub fn by_last_id<F, Fut>(id: fn(&T) -> String, mut fetch: F) -> Self
+    where
+        F: FnMut(Option<String>) -> Fut + Send + 'a,
+        Fut: Future<Output = Result<Page<T>>> + MaybeSend + 'a,
+    {
+        Self::new(
+            Cursor::LastId(None),
+            Some(id),
+            Box::new(move |cursor| match cursor {
+                Cursor::LastId(last_id) => Box::pin(fetch(last_id)),
+                Cursor::Page(_) => unreachable!("id paginator got a page cursor"),
+            }),
+        )
+    }
+
+    fn new(cursor: Cursor, next_id: Option<fn(&T) -> String>, fetch: FetchPage<'a, T>) -> Self {
+        Self {
+            fetch,
+            next_id,
+            cursor,
+            items: VecDeque::new(),
+            request: None,
+            done: false,
+        }
+    }
+
+    fn advance(&mut self, page: &Page<T>) {
+        self.cursor = match (&self.cursor, self.next_id) {
+            (Cursor::Page(number), _) => Cursor::Page(number + 1),
+            (Cursor::LastId(_), Some(id)) => Cursor::LastId(page.data.last().map(id)),
+            (Cursor::LastId(_), None) => Cursor::LastId(None),
+        };
+    }
+}
+
+// No field is structurally pinned.
+impl<T> Unpin for Paginator<'_, T> {}
+
+impl<T> Stream for Paginator<'_, T> {
+    type Item = Result<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(item) = this.items.pop_front() {
+                return Poll::Ready(Some(Ok(item)));
+            }
+            if this.done {
+                return Poll::Ready(None);
+            }
+
+            let request = match &mut this.request {
+                Some(request) => request,
+                None => this.request.insert((this.fetch)(this.cursor.clone())),
+            };
+            match request.as_mut().poll(cx) {
+                Poll::Ready(Ok(page)) => {
+                    this.request = None;
+                    this.done = !page.has_more || page.data.is_empty();
+                    this.advance(&page);
+                    this.items.extend(page.data);
+                }
+                Poll::Ready(Err(err)) => {
+                    this.request = None;
+                    this.done = true;
+                    return Poll::Ready(Some(Err(err)));
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
@@ -0,0 +1,743 @@
+use anyhow::{anyhow, Result};
+use bytes::Bytes;
+use futures_util::{Stream, StreamExt};
+use reqwest::Response;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::{
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use super::models::{Id, MessageMetadata, Timestamp, WorkflowStatus};
+use crate::{
+    compat::{self, BoxFuture, BoxStream, MaybeSend},
+    error::StreamInterrupted,
+    raw,
+};
+
+/// Decodes a Dify server-sent events response into typed events.
+pub struct EventStream<T> {
+    inner: BoxStream<'static, reqwest::Result<Bytes>>,
+    buffer: Vec<u8>,
+    finished: bool,
+    map: Option<Arc<dyn Fn(T) -> T + Send + Sync>>,
+    idle: Option<(Duration, BoxFuture<'static, ()>)>,
+    cancel: Option<BoxFuture<'static, ()>>,
+    cancelled: bool,
+    stop: Option<StopOnCancel<T>>,
+    stopping: Option<BoxFuture<'static, ()>>,
+    resume: Option<Resume<T>>,
+    metadata: Option<MessageMetadata>,
+    message_id: Option<String>,
+    suggest: Option<Suggest>,
+    _event: PhantomData<fn() -> T>,
+}
+
+/// The part of a `message_end` event kept by every stream.
+#[derive(Deserialize)]
+struct MessageEnd {
+    event: String,
+    #[serde(default)]
+    message_id: String,
+    #[serde(default)]
+    metadata: MessageMetadata,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+type Suggest = Box<dyn Fn(String) -> BoxFuture<'static, Result<Vec<String>>> + Send>;
+#[cfg(target_arch = "wasm32")]
+type Suggest = Box<dyn Fn(String) -> BoxFuture<'static, Result<Vec<String>>>>;
+
+#[cfg(not(target_arch = "wasm32"))]
+type StopTask = Box<dyn FnOnce(String) -> BoxFuture<'static, ()> + Send>;
+#[cfg(target_arch = "wasm32")]
+type StopTask = Box<dyn FnOnce(String) -> BoxFuture<'static, ()>>;
+
+#[cfg(not(target_arch = "wasm32"))]
+type Restart = Box<dyn FnMut() -> BoxFuture<'static, Result<Response>> + Send>;
+#[cfg(target_arch = "wasm32")]
+type Restart = Box<dyn FnMut() -> BoxFuture<'static, Result<Response>>>;
+
+/// Collects the answer of a stream, and sends the request again when the
+/// connection drops before any event arrived.
+struct Resume<T> {
+    answer: fn(&T) -> Option<&str>,
+    partial: String,
+    /// Set by the first event, after which the request is never sent again:
+    /// the caller has seen the run start, and it may have run tools or nodes.
+    received: bool,
+    reconnects: u32,
+    restart: Option<Restart>,
+    restarting: Option<BoxFuture<'static, Result<Response>>>,
+}
+
+/// Stops the server-side task of a cancelled stream.
+struct StopOnCancel<T> {
+    task_id: fn(&T) -> Option<&str>,
+    seen: Option<String>,
+    stop: StopTask,
+}
+
+impl<T: DeserializeOwned> EventStream<T> {
+    pub fn new(response: Response) -> Self {
+        Self::from_bytes_stream(raw::bytes_stream(response))
+    }
+
+    pub fn from_bytes_stream<S>(stream: S) -> Self
+    where
+        S: Stream<Item = reqwest::Result<Bytes>> + MaybeSend + 'static,
+    {
+        Self {
+            inner: Box::pin(stream),
+            buffer: Vec::new(),
+            finished: false,
+            map: None,
+            idle: None,
+            cancel: None,
+            cancelled: false,
+            stop: None,
+            stopping: None,
+            resume: None,
+            metadata: None,
+            message_id: None,
+            suggest: None,
+            _event: PhantomData,
+        }
+    }
+
+    /// Fails the stream when no data arrives for `timeout`.
+    pub fn with_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle = Some((timeout, compat::sleep(timeout)));
+        self
+    }
+
+    /// Ends the stream once `cancel` completes, e.g.
+    /// `token.cancelled_owned()` of a tokio-util `CancellationToken`. Dropping
+    /// the connection does not stop the generation on the server, see
+    /// [`ChatClient::stop_on_cancel`](crate::ChatClient::stop_on_cancel).
+    pub fn with_cancel(mut self, cancel: impl Future<Output = ()> + MaybeSend + 'static) -> Self {
+        self.cancel = Some(Box::pin(cancel));
+        self
+    }
+
+    /// Whether the stream ended because it was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    /// Calls `stop` with the task id of the first event that has one when
+    /// the stream is cancelled, and ends the stream once it is done.
+    #[cfg_attr(
+        not(any(feature = "chat", feature = "completion", feature = "workflow")),
+        allow(dead_code)
+    )]
+    pub(crate) fn stop_on_cancel(
+        mut self,
+        task_id: fn(&T) -> Option<&str>,
+        stop: impl FnOnce(String) -> BoxFuture<'static, ()> + MaybeSend + 'static,
+    ) -> Self {
+        self.stop = Some(StopOnCancel {
+            task_id,
+            seen: None,
+            stop: Box::new(stop),
+        });
+        self
+    }
+
+    /// The token usage and retriever resources of the `message_end` event,
+    /// once the stream got there.
+    pub fn metadata(&self) -> Option<&MessageMetadata> {
+        self.metadata.as_ref()
+    }
+
+    /// Reads the rest of the stream and returns the metadata of its
+    /// `message_end` event, failing on the first error of the stream.
+    pub async fn finish(mut self) -> Result<Option<MessageMetadata>> {
+        while let Some(event) = self.next().await {
+            event?;
+        }
+        Ok(self.metadata)
+    }
+
+    /// Fetches the follow-up questions suggested for the answer once the
+    /// stream reached its `message_end` event. Needs a stream created by a
+    /// [`ChatClient`](crate::ChatClient) for an app with suggested questions
+    /// after answer enabled.
+    pub fn suggested_questions(&self) -> BoxFuture<'static, Result<Vec<String>>> {
+        match (&self.suggest, &self.message_id) {
+            (Some(suggest), Some(message_id)) => suggest(message_id.clone()),
+            (None, _) => {
+                Box::pin(async { Err(anyhow!("the stream was not created by a chat client")) })
+            }
+            (_, None) => {
+                Box::pin(async { Err(anyhow!("the stream has not reached its message_end event")) })
+            }
+        }
+    }
+
+    /// Lets [`suggested_questions`](Self::suggested_questions) fetch the
+    /// questions for a message id with `suggest`.
+    #[cfg_attr(not(feature = "chat"), allow(dead_code))]
+    pub(crate) fn with_suggestions(
+        mut self,
+        suggest: impl Fn(String) -> BoxFuture<'static, Result<Vec<String>>> + MaybeSend + 'static,
+    ) -> Self {
+        self.suggest = Some(Box::new(suggest));
+        self
+    }
+
+    /// Collects the text `answer` returns for each event, so a dropped
+    /// connection fails with [`StreamInterrupted`] carrying the partial answer.
+    #[cfg_attr(
+        not(any(feature = "chat", feature = "completion", feature = "workflow")),
+        allow(dead_code)
+    )]
+    pub(crate) fn collect_answer(mut self, answer: fn(&T) -> Option<&str>) -> Self {
+        self.resume = Some(Resume {
+            answer,
+            partial: String::new(),
+            received: false,
+            reconnects: 0,
+            restart: None,
+            restarting: None,
+        });
+        self
+    }
+
+    /// Reconnects up to `reconnects` times with the response of `restart`
+    /// when the connection drops before the first event. Needs
+    /// [`collect_answer`](Self::collect_answer) first.
+    #[cfg_attr(
+        not(any(feature = "chat", feature = "completion", feature = "workflow")),
+        allow(dead_code)
+    )]
+    pub(crate) fn reconnect(
+        mut self,
+        reconnects: u32,
+        restart: impl FnMut() -> BoxFuture<'static, Result<Response>> + MaybeSend + 'static,
+    ) -> Self {
+        if let Some(resume) = &mut self.resume {
+            resume.reconnects = reconnects;
+            resume.restart = Some(Box::new(restart));
+        }
+        self
+    }
+
+    /// Starts sending the request again if the stream can still reconnect.
+    fn try_reconnect(&mut self, err: &reqwest::Error) -> bool {
+        let Some(resume) = &mut self.resume else {
+            return false;
+        };
+        let Some(restart) = &mut resume.restart else {
+            return false;
+        };
+        if resume.received || resume.reconnects == 0 {
+            return false;
+        }
+        resume.reconnects -= 1;
+        tracing::warn!(
+            "stream dropped before the first event, reconnecting: {}",
+            err
+        );
+        resume.restarting = Some(restart());
+        true
+    }
+
+    /// Wraps the error that ended the stream in [`StreamInterrupted`] when
+    /// the answer is collected.
+    fn interrupted(&mut self, err: anyhow::Error) -> anyhow::Error {
+        match &mut self.resume {
+            Some(resume) => StreamInterrupted {
+                partial_answer: std::mem::take(&mut resume.partial),
+                reason: format!("{:#}", err),
+            }
+            .into(),
+            None => err,
+        }
+    }
+
+    /// Ends the stream if it was cancelled, starting the stop of its task.
+    fn poll_cancel(&mut self, cx: &mut Context<'_>) -> bool {
+        let Some(cancel) = &mut self.cancel else {
+            return false;
+        };
+        if cancel.as_mut().poll(cx).is_pending() {
+            return false;
+        }
+        self.cancel = None;
+        self.cancelled = true;
+        self.finished = true;
+        self.buffer.clear();
+        if let Some(stop) = self.stop.take() {
+            if let Some(task_id) = stop.seen {
+                self.stopping = Some((stop.stop)(task_id));
+            }
+        }
+        true
+    }
+
+    #[cfg_attr(not(any(feature = "chat", feature = "completion")), allow(dead_code))]
+    pub(crate) fn map_events(mut self, map: impl Fn(T) -> T + Send + Sync + 'static) -> Self {
+        self.map = Some(Arc::new(map));
+        self
+    }
+
+    fn next_event(&mut self) -> Option<Result<T>> {
+        while let Some(end) = self.buffer.windows(2).position(|window| window == b"\n\n") {
+            let block: Vec<u8> = self.buffer.drain(..end + 2).collect();
+            let block = String::from_utf8_lossy(&block);
+
+            let data = block
+                .lines()
+                .filter_map(|line| line.trim_end_matches('\r').strip_prefix("data:"))
+                .map(str::trim_start)
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            if data.is_empty() {
+                continue;
+            }
+
+            tracing::debug!("stream event: {}", data);
+            if data.contains("\"message_end\"") {
+                if let Ok(end) = serde_json::from_str::<MessageEnd>(&data) {
+                    if end.event == "message_end" {
+                        self.metadata = Some(end.metadata);
+                        self.message_id = Some(end.message_id).filter(|id| !id.is_empty());
+                    }
+                }
+            }
+            let event = serde_json::from_str(&data).map_err(Into::into);
+            return Some(match &self.map {
+                Some(map) => event.map(map.as_ref()),
+                None => event,
+            });
+        }
+
+        None
+    }
+}
+
+impl<T: DeserializeOwned> Stream for EventStream<T> {
+    type Item = Result<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(stopping) = &mut this.stopping {
+                if stopping.as_mut().poll(cx).is_pending() {
+                    return Poll::Pending;
+                }
+                this.stopping = None;
+                return Poll::Ready(None);
+            }
+            if !this.finished && this.poll_cancel(cx) {
+                continue;
+            }
+            if let Some(restarting) = this
+                .resume
+                .as_mut()
+                .and_then(|resume| resume.restarting.as_mut())
+            {
+                let Poll::Ready(result) = restarting.as_mut().poll(cx) else {
+                    return Poll::Pending;
+                };
+                if let Some(resume) = &mut this.resume {
+                    resume.restarting = None;
+                }
+                match result {
+                    Ok(response) => {
+                        this.inner = Box::pin(raw::bytes_stream(response));
+                        this.buffer.clear();
+                        // a cancel must stop the new task, not the dropped one
+                        if let Some(stop) = &mut this.stop {
+                            stop.seen = None;
+                        }
+                        if let Some((timeout, sleep)) = &mut this.idle {
+                            *sleep = compat::sleep(*timeout);
+                        }
+                        continue;
+                    }
+                    Err(err) => {
+                        this.finished = true;
+                        return Poll::Ready(Some(Err(this.interrupted(err))));
+                    }
+                }
+            }
+            if let Some(event) = this.next_event() {
+                if let Ok(event) = &event {
+                    if let Some(stop) = &mut this.stop {
+                        if stop.seen.is_none() {
+                            stop.seen = (stop.task_id)(event).map(String::from);
+                        }
+                    }
+                    if let Some(resume) = &mut this.resume {
+                        if let Some(answer) = (resume.answer)(event) {
+                            resume.partial.push_str(answer);
+                        }
+                    }
+                }
+                if let Some(resume) = &mut this.resume {
+                    resume.received = true;
+                }
+                return Poll::Ready(Some(event));
+            }
+            if this.finished {
+                return Poll::Ready(None);
+            }
+
+            match this.inner.as_mut().poll_next(cx) {
+                Poll::Ready(Some(Ok(chunk))) => {
+                    if let Some((timeout, sleep)) = &mut this.idle {
+                        *sleep = compat::sleep(*timeout);
+                    }
+                    this.buffer.extend_from_slice(&chunk)
+                }
+                Poll::Ready(Some(Err(err))) => {
+                    if this.try_reconnect(&err) {
+                        continue;
+                    }
+                    this.finished = true;
+                    return Poll::Ready(Some(Err(this.interrupted(err.into()))));
+                }
+                Poll::Ready(None) => {
+                    // flush a trailing event that is not terminated by a blank line
+                    this.finished = true;
+                    this.buffer.extend_from_slice(b"\n\n");
+                }
+                Poll::Pending => {
+                    if let Some((timeout, sleep)) = &mut this.idle {
+                        if sleep.as_mut().poll(cx).is_ready() {
+                            let err = anyhow!("no stream data received for {:?}", timeout);
+                            this.finished = true;
+                            return Poll::Ready(Some(Err(this.interrupted(err))));
+                        }
+                    }
+                    return Poll::Pending;
+                }
+            }
+        }
+    }
+}
+
+pub type ChatStream = EventStream<ChatStreamEvent>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ChatStreamEvent {
+    Message(MessageEvent),
+    MessageReplace(MessageEvent),
+    MessageEnd(MessageEndEvent),
+    /// An answer delta of an agent app.
+    AgentMessage(MessageEvent),
+    AgentThought(AgentThoughtEvent),
+    MessageFile(MessageFileEvent),
+    Error(ErrorEvent),
+    Ping,
+    #[serde(other)]
+    Unknown,
+}
+
+impl ChatStreamEvent {
+    /// The answer text carried by a message event.
+    pub fn answer(&self) -> Option<&str> {
+        match self {
+            Self::Message(event) | Self::AgentMessage(event) => Some(event.answer.as_str()),
+            _ => None,
+        }
+    }
+
+    pub fn task_id(&self) -> Option<&str> {
+        match self {
+            Self::Message(event) | Self::MessageReplace(event) | Self::AgentMessage(event) => {
+                Some(event.task_id.as_str())
+            }
+            Self::MessageEnd(event) => Some(event.task_id.as_str()),
+            Self::AgentThought(event) => Some(event.task_id.as_str()),
+            Self::Error(event) => Some(event.task_id.as_str()),
+            Self::MessageFile(_) | Self::Ping | Self::Unknown => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct MessageEvent {
+    pub task_id: Id,
+    pub message_id: Id,
+    pub conversation_id: Id,
+    pub answer: String,
+    pub created_at: Timestamp,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct MessageEndEvent {
+    pub task_id: Id,
+    pub message_id: Id,
+    pub conversation_id: Id,
+    pub metadata: MessageMetadata,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl MessageEndEvent {
+    /// Shorthand for [`MessageMetadata::is_annotation_reply`].
+    pub fn is_annotation_reply(&self) -> bool {
+        self.metadata.is_annotation_reply()
+    }
+}
+
+/// One reasoning step of an agent, with the tool it called and the result.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct AgentThoughtEvent {
+    pub id: Id,
+    pub task_id: Id,
+    pub message_id: Id,
+    pub conversation_id: Id,
+    pub position: u32,
+    pub thought: String,
+    pub observation: String,
+    /// The tools called in this step, separated by `;`.
+    pub tool: String,
+    pub tool_labels: Value,
+    /// The tool inputs as a JSON string, keyed by tool name.
+    pub tool_input: String,
+    /// Ids of the files created in this step, see [`MessageFileEvent`].
+    pub message_files: Vec<Id>,
+    pub created_at: Timestamp,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl AgentThoughtEvent {
+    pub fn tools(&self) -> impl Iterator<Item = &str> {
+        self.tool
+            .split(';')
+            .map(str::trim)
+            .filter(|tool| !tool.is_empty())
+    }
+}
+
+/// A file generated by the app, e.g. an image drawn by an agent tool.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct MessageFileEvent {
+    pub id: Id,
+    #[serde(rename = "type")]
+    pub file_type: String,
+    pub belongs_to: String,
+    pub url: String,
+    pub conversation_id: Id,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct ErrorEvent {
+    pub task_id: Id,
+    pub message_id: Id,
+    pub status: u16,
+    pub code: String,
+    pub message: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+pub type CompletionStream = EventStream<CompletionStreamEvent>;
+
+/// Events of a completion app. Unlike chat events they belong to no
+/// conversation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum CompletionStreamEvent {
+    Message(CompletionMessageEvent),
+    MessageReplace(CompletionMessageEvent),
+    MessageEnd(Box<CompletionMessageEndEvent>),
+    Error(ErrorEvent),
+    Ping,
+    #[serde(other)]
+    Unknown,
+}
+
+impl CompletionStreamEvent {
+    /// The answer text carried by a message event.
+    pub fn answer(&self) -> Option<&str> {
+        match self {
+            Self::Message(event) => Some(event.answer.as_str()),
+            _ => None,
+        }
+    }
+
+    pub fn task_id(&self) -> Option<&str> {
+        match self {
+            Self::Message(event) | Self::MessageReplace(event) => Some(event.task_id.as_str()),
+            Self::MessageEnd(event) => Some(event.task_id.as_str()),
+            Self::Error(event) => Some(event.task_id.as_str()),
+            Self::Ping | Self::Unknown => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct CompletionMessageEvent {
+    pub task_id: Id,
+    pub message_id: Id,
+    pub answer: String,
+    pub created_at: Timestamp,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct CompletionMessageEndEvent {
+    pub task_id: Id,
+    pub message_id: Id,
+    pub metadata: MessageMetadata,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl CompletionMessageEndEvent {
+    /// Shorthand for [`MessageMetadata::is_annotation_reply`].
+    pub fn is_annotation_reply(&self) -> bool {
+        self.metadata.is_annotation_reply()
+    }
+}
+
+pub type WorkflowStream = EventStream<WorkflowStreamEvent>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum WorkflowStreamEvent {
+    WorkflowStarted(WorkflowEvent<WorkflowStartedData>),
+    NodeStarted(WorkflowEvent<NodeStartedData>),
+    NodeFinished(WorkflowEvent<NodeFinishedData>),
+    TextChunk(WorkflowEvent<TextChunkData>),
+    WorkflowFinished(WorkflowEvent<WorkflowFinishedData>),
+    Error(ErrorEvent),
+    Ping,
+    #[serde(other)]
+    Unknown,
+}
+
+impl WorkflowStreamEvent {
+    /// The text of a text chunk event.
+    pub fn text(&self) -> Option<&str> {
+        match self {
+            Self::TextChunk(event) => Some(event.data.text.as_str()),
+            _ => None,
+        }
+    }
+
+    pub fn task_id(&self) -> Option<&str> {
+        match self {
+            Self::WorkflowStarted(event) => Some(event.task_id.as_str()),
+            Self::NodeStarted(event) => Some(event.task_id.as_str()),
+            Self::NodeFinished(event) => Some(event.task_id.as_str()),
+            Self::TextChunk(event) => Some(event.task_id.as_str()),
+            Self::WorkflowFinished(event) => Some(event.task_id.as_str()),
+            Self::Error(event) => Some(event.task_id.as_str()),
+            Self::Ping | Self::Unknown => None,
+        }
+    }
+}
+
+/// The envelope shared by workflow events; the event specific payload is in
+/// `data`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct WorkflowEvent<D> {
+    #[serde(default)]
+    pub task_id: Id,
+    #[serde(default)]
+    pub workflow_run_id: Id,
+    pub data: D,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct WorkflowStartedData {
+    pub id: Id,
+    pub workflow_id: Id,
+    pub sequence_number: u64,
+    pub inputs: Value,
+    pub created_at: Timestamp,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct NodeStartedData {
+    pub id: Id,
+    pub node_id: String,
+    pub node_type: String,
+    pub title: String,
+    pub index: u32,
+    pub predecessor_node_id: Option<String>,
+    pub inputs: Value,
+    pub created_at: Timestamp,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct NodeFinishedData {
+    pub id: Id,
+    pub node_id: String,
+    pub node_type: String,
+    pub title: String,
+    pub index: u32,
+    pub predecessor_node_id: Option<String>,
+    pub inputs: Value,
+    pub process_data: Value,
+    pub outputs: Value,
+    pub status: WorkflowStatus,
+    pub error: Option<String>,
+    pub elapsed_time: f64,
+    pub execution_metadata: Value,
+    pub created_at: Timestamp,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct TextChunkData {
+    pub text: String,
+    pub from_variable_selector: Vec<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct WorkflowFinishedData {
+    pub id: Id,
+    pub workflow_id: Id,
+    pub status: WorkflowStatus,
+    pub outputs: Value,
+    pub error: Option<String>,
+    pub elapsed_time: f64,
+    pub total_tokens: u64,
+    pub total_steps: u64,
+    pub created_at: Timestamp,
+    pub finished_at: Option<Timestamp>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
@@ -0,0 +1,314 @@
+use anyhow::Result;
+use reqwest::Response;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{future::Future, sync::Arc};
+
+use super::{
+    models::{FileInput, Page, ResponseMode, WorkflowLog, WorkflowRun, WorkflowStatus},
+    paginator::Paginator,
+    stream::{WorkflowStream, WorkflowStreamEvent},
+};
+use crate::{
+    compat::{BoxFuture, MaybeSend},
+    error::{ConfigError, ConfigProblem, ResponseExt},
+    DifyClient, DifyConfig,
+};
+
+#[derive(Clone)]
+pub struct WorkflowClient {
+    dify_client: Arc<DifyClient>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct WorkflowRunRequest {
+    pub inputs: Value,
+    pub response_mode: ResponseMode,
+    pub user: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub files: Option<Vec<FileInput>>,
+    /// Links the run to an external trace in Dify's tracing integrations.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_id: Option<String>,
+    /// Runs this published workflow version instead of the app's current one.
+    /// Part of the endpoint rather than the request body.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workflow_id: Option<String>,
+}
+
+#[derive(Serialize)]
+struct WorkflowRunPayload<'a> {
+    inputs: &'a Value,
+    response_mode: ResponseMode,
+    user: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    files: Option<&'a Vec<FileInput>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    trace_id: Option<&'a str>,
+}
+
+impl WorkflowRunRequest {
+    pub fn new(inputs: Value, user: &str) -> Self {
+        Self {
+            inputs,
+            response_mode: ResponseMode::Block,
+            user: user.to_string(),
+            files: None,
+            trace_id: None,
+            workflow_id: None,
+        }
+    }
+
+    pub fn response_mode(mut self, response_mode: ResponseMode) -> Self {
+        self.response_mode = response_mode;
+        self
+    }
+
+    pub fn files(mut self, files: Vec<FileInput>) -> Self {
+        self.files = Some(files);
+        self
+    }
+
+    pub fn trace_id(mut self, trace_id: &str) -> Self {
+        self.trace_id = Some(trace_id.to_string());
+        self
+    }
+
+    pub fn workflow_id(mut self, workflow_id: &str) -> Self {
+        self.workflow_id = Some(workflow_id.to_string());
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut problems = Vec::new();
+        if !(self.inputs.is_object() || self.inputs.is_null()) {
+            problems.push(ConfigProblem::InputsNotObject);
+        }
+        if self.user.trim().is_empty() {
+            problems.push(ConfigProblem::EmptyField { field: "user" });
+        }
+        if self
+            .workflow_id
+            .as_deref()
+            .is_some_and(|workflow_id| workflow_id.trim().is_empty())
+        {
+            problems.push(ConfigProblem::EmptyField {
+                field: "workflow_id",
+            });
+        }
+        ConfigError::check(problems)
+    }
+
+    fn endpoint(&self) -> String {
+        match &self.workflow_id {
+            Some(workflow_id) => format!("/workflows/{}/run", workflow_id),
+            None => "/workflows/run".to_string(),
+        }
+    }
+}
+
+impl WorkflowClient {
+    pub fn new(api_key: &str, base_url: Option<&str>) -> Self {
+        Self {
+            dify_client: Arc::new(DifyClient::new(api_key, base_url)),
+        }
+    }
+
+    pub fn from_config(config: &DifyConfig) -> Result<Self> {
+        Ok(Self {
+            dify_client: Arc::new(DifyClient::from_config(config)?),
+        })
+    }
+
+    /// The shared transport, for building clients of other app types on it.
+    pub fn dify_client(&self) -> &Arc<DifyClient> {
+        &self.dify_client
+    }
+
+    pub async fn run(
+        &self,
+        inputs: Value,
+        response_mode: ResponseMode,
+        user: &str,
+    ) -> Result<Response> {
+        let request = WorkflowRunRequest::new(inputs, user).response_mode(response_mode);
+        self.run_request(&request).await
+    }
+
+    pub async fn run_raw(
+        &self,
+        inputs: Value,
+        response_mode: ResponseMode,
+        user: Option<&str>,
+    ) -> Result<Response> {
+        let request =
+            WorkflowRunRequest::new(inputs, user.unwrap_or("abc-123")).response_mode(response_mode);
+        self.send_run_request(&request).await
+    }
+
+    pub async fn run_request(&self, request: &WorkflowRunRequest) -> Result<Response> {
+        self.send_run_request(request).await?.check_status().await
+    }
+
+    async fn send_run_request(&self, request: &WorkflowRunRequest) -> Result<Response> {
+        request.validate()?;
+        let data = serde_json::to_value(WorkflowRunPayload {
+            inputs: &request.inputs,
+            response_mode: request.response_mode,
+            user: &request.user,
+            files: request.files.as_ref(),
+            trace_id: request.trace_id.as_deref(),
+        })?;
+
+        self.dify_client
+            .send_request(
+                reqwest::Method::POST,
+                &request.endpoint(),
+                Some(data),
+                None,
+                request.response_mode == ResponseMode::Stream,
+            )
+            .await
+    }
+
+    pub async fn run_stream(&self, inputs: Value, user: &str) -> Result<WorkflowStream> {
+        let request = WorkflowRunRequest::new(inputs, user).response_mode(ResponseMode::Stream);
+        self.run_request_stream(&request).await
+    }
+
+    /// Runs `request` in streaming mode, whatever its `response_mode`. Drops
+    /// are handled like in
+    /// [`ChatClient::create_chat_message_stream`](crate::ChatClient::create_chat_message_stream).
+    pub async fn run_request_stream(&self, request: &WorkflowRunRequest) -> Result<WorkflowStream> {
+        let request = request.clone().response_mode(ResponseMode::Stream);
+        let response = self.run_request(&request).await?;
+        let stream = self
+            .dify_client
+            .event_stream(response)
+            .collect_answer(WorkflowStreamEvent::text);
+
+        let reconnects = self.dify_client.retry_config().stream_reconnects;
+        if reconnects == 0 {
+            return Ok(stream);
+        }
+        let workflow = self.clone();
+        Ok(stream.reconnect(
+            reconnects,
+            move || -> BoxFuture<'static, Result<Response>> {
+                let (workflow, request) = (workflow.clone(), request.clone());
+                Box::pin(async move { workflow.run_request(&request).await })
+            },
+        ))
+    }
+
+    /// Stops a streaming run. `task_id` is available from every
+    /// [`WorkflowStreamEvent`].
+    pub async fn stop(&self, task_id: &str, user: &str) -> Result<Response> {
+        let data = json!({
+            "user": user
+        });
+        self.dify_client
+            .send_request(
+                reqwest::Method::POST,
+                &format!("/workflows/tasks/{}/stop", task_id),
+                Some(data),
+                None,
+                false,
+            )
+            .await?
+            .check_status()
+            .await
+    }
+
+    /// Ends `stream` once `cancel` completes and stops the run on the server,
+    /// see [`ChatClient::stop_on_cancel`](crate::ChatClient::stop_on_cancel).
+    pub fn stop_on_cancel(
+        &self,
+        stream: WorkflowStream,
+        user: &str,
+        cancel: impl Future<Output = ()> + MaybeSend + 'static,
+    ) -> WorkflowStream {
+        let workflow = self.clone();
+        let user = user.to_string();
+        stream
+            .with_cancel(cancel)
+            .stop_on_cancel(WorkflowStreamEvent::task_id, move |task_id| {
+                Box::pin(async move {
+                    if let Err(err) = workflow.stop(&task_id, &user).await {
+                        tracing::warn!("failed to stop task {}: {:#}", task_id, err);
+                    }
+                })
+            })
+    }
+
+    pub async fn get_run(&self, workflow_run_id: &str) -> Result<WorkflowRun> {
+        let response = self
+            .dify_client
+            .send_request(
+                reqwest::Method::GET,
+                &format!("/workflows/run/{}", workflow_run_id),
+                None,
+                None,
+                false,
+            )
+            .await?
+            .check_status()
+            .await?;
+        Ok(response.json().await?)
+    }
+
+    pub async fn get_logs(
+        &self,
+        keyword: Option<&str>,
+        status: Option<WorkflowStatus>,
+        page: u32,
+        limit: u32,
+    ) -> Result<Page<WorkflowLog>> {
+        let mut params = json!({
+            "page": page,
+            "limit": limit
+        });
+        if let Some(keyword) = keyword {
+            params["keyword"] = Value::String(keyword.to_string());
+        }
+        if let Some(status) = status {
+            params["status"] = Value::String(status.to_string());
+        }
+
+        let response = self
+            .dify_client
+            .send_request(
+                reqwest::Method::GET,
+                "/workflows/logs",
+                None,
+                Some(params),
+                false,
+            )
+            .await?
+            .check_status()
+            .await?;
+        Ok(response.json().await?)
+    }
+
+    pub fn paginate_logs<'a>(
+        &'a self,
+        keyword: Option<&'a str>,
+        status: Option<WorkflowStatus>,
+        limit: u32,
+    ) -> Paginator<'a, WorkflowLog> {
+        Paginator::by_page(move |page| self.get_logs(keyword, status.clone(), page, limit))
+    }
+}
+
+impl From<DifyClient> for WorkflowClient {
+    fn from(value: DifyClient) -> Self {
+        Arc::new(value).into()
+    }
+}
+
+impl From<Arc<DifyClient>> for WorkflowClient {
+    fn from(value: Arc<DifyClient>) -> Self {
+        WorkflowClient { dify_client: value }
+    }
+}
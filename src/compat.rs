@@ -0,0 +1,45 @@
+//! Shims over the differences between native targets and `wasm32`, where
+//! futures and streams holding JavaScript values are not `Send` and there is
+//! no tokio timer.
+
+use std::time::Duration;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use futures_util::{future::BoxFuture, stream::BoxStream};
+#[cfg(target_arch = "wasm32")]
+pub use futures_util::{future::LocalBoxFuture as BoxFuture, stream::LocalBoxStream as BoxStream};
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+pub(crate) use web_time::Instant;
+
+/// The current wall-clock time. `std::time::SystemTime::now` panics on
+/// `wasm32-unknown-unknown`, so the browser clock is read there instead.
+pub(crate) fn system_time_now() -> std::time::SystemTime {
+    #[cfg(not(target_arch = "wasm32"))]
+    return std::time::SystemTime::now();
+    #[cfg(target_arch = "wasm32")]
+    return std::time::UNIX_EPOCH
+        + web_time::SystemTime::now()
+            .duration_since(web_time::UNIX_EPOCH)
+            .unwrap_or_default();
+}
+
+/// `Send` on native targets, implemented by every type on `wasm32`.
+#[cfg(not(target_arch = "wasm32"))]
+pub trait MaybeSend: Send {}
+#[cfg(not(target_arch = "wasm32"))]
+impl<T: Send + ?Sized> MaybeSend for T {}
+
+#[cfg(target_arch = "wasm32")]
+pub trait MaybeSend {}
+#[cfg(target_arch = "wasm32")]
+impl<T: ?Sized> MaybeSend for T {}
+
+pub(crate) fn sleep(duration: Duration) -> BoxFuture<'static, ()> {
+    #[cfg(not(target_arch = "wasm32"))]
+    return Box::pin(tokio::time::sleep(duration));
+    #[cfg(target_arch = "wasm32")]
+    return Box::pin(gloo_timers::future::sleep(duration));
+}
@@ -0,0 +1,315 @@
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, time::Duration};
+
+use crate::error::{ConfigError, ConfigProblem};
+
+pub const DEFAULT_BASE_URL: &str = "https://api.dify.ai/v1";
+
+/// Whether reqwest was built with a TLS backend. On wasm32 the browser or
+/// worker runtime handles TLS.
+const HTTPS_SUPPORTED: bool = cfg!(any(
+    target_arch = "wasm32",
+    feature = "rustls",
+    feature = "native-tls"
+));
+
+/// Shared configuration for every client type, deserializable from any serde
+/// format (TOML, JSON, YAML, ...). Each Dify app has its own API key, so
+/// services talking to several apps usually keep one section per app.
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DifyConfig {
+    pub api_key: String,
+    pub base_url: Option<String>,
+    /// Total duration of blocking calls and uploads.
+    pub timeout_secs: Option<u64>,
+    pub connect_timeout_secs: Option<u64>,
+    /// Longest gap between two chunks of a streaming response.
+    pub read_timeout_secs: Option<u64>,
+    /// Longest wait for the response of a streaming call, before the first
+    /// chunk.
+    pub first_byte_timeout_secs: Option<u64>,
+    /// Total duration of streaming calls, unlimited by default so long
+    /// generations are not cut off.
+    pub stream_timeout_secs: Option<u64>,
+    pub retry: RetryConfig,
+    pub limits: LimitsConfig,
+    pub version: VersionConfig,
+    pub multipart: MultipartConfig,
+    pub proxy: ProxyConfig,
+}
+
+/// Printed in place of secrets by the `Debug` impls, so configs can be
+/// logged.
+const REDACTED: &str = "<redacted>";
+
+/// `url` without the password of its userinfo, for printing.
+fn redact_url(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(mut parsed) if parsed.password().is_some() => {
+            let _ = parsed.set_password(None);
+            parsed.to_string()
+        }
+        Ok(_) => url.to_string(),
+        // can't tell the userinfo apart, so drop all of it
+        Err(_) => match url.rsplit_once('@') {
+            Some((_, host)) => format!("{}@{}", REDACTED, host),
+            None => url.to_string(),
+        },
+    }
+}
+
+impl std::fmt::Debug for DifyConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DifyConfig")
+            .field("api_key", &REDACTED)
+            .field("base_url", &self.base_url)
+            .field("timeout_secs", &self.timeout_secs)
+            .field("connect_timeout_secs", &self.connect_timeout_secs)
+            .field("read_timeout_secs", &self.read_timeout_secs)
+            .field("first_byte_timeout_secs", &self.first_byte_timeout_secs)
+            .field("stream_timeout_secs", &self.stream_timeout_secs)
+            .field("retry", &self.retry)
+            .field("limits", &self.limits)
+            .field("version", &self.version)
+            .field("multipart", &self.multipart)
+            .field("proxy", &self.proxy)
+            .finish()
+    }
+}
+
+impl DifyConfig {
+    pub fn new(api_key: &str) -> Self {
+        Self {
+            api_key: api_key.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        self.base_url.as_deref().unwrap_or(DEFAULT_BASE_URL)
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut problems = Vec::new();
+        if self.api_key.trim().is_empty() {
+            problems.push(ConfigProblem::EmptyApiKey);
+        }
+        match reqwest::Url::parse(self.base_url()) {
+            Ok(url) if url.scheme() == "https" && !HTTPS_SUPPORTED => {
+                problems.push(ConfigProblem::InvalidBaseUrl {
+                    url: self.base_url().to_string(),
+                    reason: "https needs the rustls or native-tls feature".to_string(),
+                })
+            }
+            Ok(url) if matches!(url.scheme(), "http" | "https") => {}
+            Ok(url) => problems.push(ConfigProblem::InvalidBaseUrl {
+                url: self.base_url().to_string(),
+                reason: format!("unsupported scheme {}", url.scheme()),
+            }),
+            Err(err) => problems.push(ConfigProblem::InvalidBaseUrl {
+                url: self.base_url().to_string(),
+                reason: err.to_string(),
+            }),
+        }
+        if self.timeout_secs == Some(0) {
+            problems.push(ConfigProblem::ZeroTimeout {
+                field: "timeout_secs",
+            });
+        }
+        if self.connect_timeout_secs == Some(0) {
+            problems.push(ConfigProblem::ZeroTimeout {
+                field: "connect_timeout_secs",
+            });
+        }
+        if self.read_timeout_secs == Some(0) {
+            problems.push(ConfigProblem::ZeroTimeout {
+                field: "read_timeout_secs",
+            });
+        }
+        if self.first_byte_timeout_secs == Some(0) {
+            problems.push(ConfigProblem::ZeroTimeout {
+                field: "first_byte_timeout_secs",
+            });
+        }
+        if self.stream_timeout_secs == Some(0) {
+            problems.push(ConfigProblem::ZeroTimeout {
+                field: "stream_timeout_secs",
+            });
+        }
+        if let Some(url) = &self.proxy.url {
+            match reqwest::Url::parse(url) {
+                Ok(parsed)
+                    if matches!(parsed.scheme(), "socks5" | "socks5h")
+                        && !cfg!(feature = "socks") =>
+                {
+                    problems.push(ConfigProblem::InvalidProxyUrl {
+                        url: redact_url(url),
+                        reason: format!("{} needs the socks feature", parsed.scheme()),
+                    })
+                }
+                Ok(parsed)
+                    if matches!(parsed.scheme(), "http" | "https" | "socks5" | "socks5h") => {}
+                Ok(parsed) => problems.push(ConfigProblem::InvalidProxyUrl {
+                    url: redact_url(url),
+                    reason: format!("unsupported scheme {}", parsed.scheme()),
+                }),
+                Err(err) => problems.push(ConfigProblem::InvalidProxyUrl {
+                    url: redact_url(url),
+                    reason: err.to_string(),
+                }),
+            }
+        }
+        if self.retry.initial_backoff_ms > self.retry.max_backoff_ms {
+            problems.push(ConfigProblem::BackoffRange {
+                initial_ms: self.retry.initial_backoff_ms,
+                max_ms: self.retry.max_backoff_ms,
+            });
+        }
+        if self.limits.max_concurrent == Some(0) {
+            problems.push(ConfigProblem::ZeroConcurrency);
+        }
+        if self.limits.max_requests_per_minute == Some(0) {
+            problems.push(ConfigProblem::ZeroRateLimit);
+        }
+        ConfigError::check(problems)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryConfig {
+    /// Sends a request again after a timeout, a connection error or a 408,
+    /// 429, 502, 503 or 504 response, up to this many times. Multipart
+    /// uploads are never retried.
+    ///
+    /// Retries are not limited to idempotent methods: the POSTs that send
+    /// chat messages, run workflows or create documents are sent again too.
+    /// Delivery is at-least-once, as the server may have handled the first
+    /// attempt before it failed, so a retry can store the message, run the
+    /// workflow or create the document twice.
+    pub max_retries: u32,
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+    /// Wait for the `Retry-After` of a retried response, e.g. a 429, instead
+    /// of the backoff, unless it exceeds `max_retry_after_secs`, in which case the
+    /// response is returned without retrying.
+    pub respect_retry_after: bool,
+    pub max_retry_after_secs: u64,
+    /// Sends a chat, completion or workflow stream again when its connection
+    /// drops before the first event arrived, up to this many times. Later
+    /// drops end the stream with a [`StreamInterrupted`](crate::StreamInterrupted)
+    /// error.
+    ///
+    /// Delivery is at-least-once: the server may have accepted the request
+    /// before the connection dropped, so a reconnect can store the message
+    /// or run the workflow, with its tool and HTTP nodes, twice.
+    pub stream_reconnects: u32,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 0,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 10_000,
+            respect_retry_after: false,
+            max_retry_after_secs: 60,
+            stream_reconnects: 0,
+        }
+    }
+}
+
+impl RetryConfig {
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let backoff = self
+            .initial_backoff_ms
+            .saturating_mul(2u64.saturating_pow(attempt));
+        Duration::from_millis(backoff.min(self.max_backoff_ms))
+    }
+
+    /// The wait before retrying after `attempt`, or `None` when the server
+    /// asks to wait longer than `max_retry_after_secs`.
+    pub fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Option<Duration> {
+        match retry_after {
+            Some(retry_after) if self.respect_retry_after => (retry_after
+                <= Duration::from_secs(self.max_retry_after_secs))
+            .then_some(retry_after),
+            _ => Some(self.backoff(attempt)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LimitsConfig {
+    /// Requests in flight at once, across every client built from one
+    /// `DifyClient`. A request counts until its body is read or its response
+    /// dropped; streamed bodies count until their stream is dropped. On wasm32
+    /// only the wait for the response headers counts.
+    pub max_concurrent: Option<usize>,
+    /// Client-side quota shared by every client built from one `DifyClient`,
+    /// enforced with a token bucket that allows bursts of the same size.
+    pub max_requests_per_minute: Option<u32>,
+    /// Lets one write helper of the knowledge client (imports, ingestion,
+    /// split documents) at a time work on a dataset; the others wait up to
+    /// this long before failing with [`DatasetBusy`](crate::DatasetBusy).
+    /// The lock is advisory and not reentrant: plain dataset endpoints do not
+    /// take it.
+    pub dataset_write_wait_secs: Option<u64>,
+}
+
+/// Pins the Dify server version this client expects, see
+/// [`DifyClient::check_server_version`](crate::DifyClient::check_server_version).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VersionConfig {
+    /// A version or version prefix, e.g. `"1.4"` accepts `1.4.0` and `1.4.3`.
+    pub expected: Option<String>,
+    /// Fail on a mismatch instead of logging a warning.
+    pub strict: bool,
+}
+
+/// Multipart layout of uploads, for gateways in front of Dify that expect
+/// other part names or additional form fields.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MultipartConfig {
+    /// Renames parts, e.g. `file = "upload"` or `data = "payload"`.
+    pub field_names: BTreeMap<String, String>,
+    /// Text fields added to every upload.
+    pub extra_fields: BTreeMap<String, String>,
+}
+
+impl MultipartConfig {
+    /// The name sent for the part Dify calls `name`.
+    pub fn field_name<'a>(&'a self, name: &'a str) -> &'a str {
+        self.field_names.get(name).map_or(name, String::as_str)
+    }
+}
+
+/// Outbound proxy for every request, e.g. `http://proxy.corp:3128` or, with
+/// the `socks` feature, `socks5://proxy.corp:1080`. Without a `url` the
+/// `HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY` environment variables apply.
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProxyConfig {
+    pub url: Option<String>,
+    /// Basic auth sent to the proxy, for proxies whose url can't carry the
+    /// credentials.
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Hosts, domains (`.corp`) or IP ranges reached without the proxy.
+    pub no_proxy: Vec<String>,
+}
+
+impl std::fmt::Debug for ProxyConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProxyConfig")
+            .field("url", &self.url.as_deref().map(redact_url))
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| REDACTED))
+            .field("no_proxy", &self.no_proxy)
+            .finish()
+    }
+}
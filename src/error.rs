@@ -0,0 +1,289 @@
+use anyhow::Result;
+use reqwest::{header, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::{
+    fmt,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use crate::{compat, timestamp};
+
+/// Errors raised by the transport and by non-success API responses.
+///
+/// Client methods return `anyhow::Error`; use [`DifyError::find`] to get the
+/// `DifyError` out of one for retry or alerting decisions.
+#[derive(Debug)]
+pub enum DifyError {
+    Api {
+        status: StatusCode,
+        retry_after: Option<Duration>,
+        body: String,
+    },
+    Transport(reqwest::Error),
+}
+
+impl DifyError {
+    pub fn find(err: &anyhow::Error) -> Option<&DifyError> {
+        err.chain()
+            .find_map(|cause| cause.downcast_ref::<DifyError>())
+    }
+
+    pub fn status_code(&self) -> Option<StatusCode> {
+        match self {
+            Self::Api { status, .. } => Some(*status),
+            Self::Transport(err) => err.status(),
+        }
+    }
+
+    pub fn is_rate_limited(&self) -> bool {
+        self.status_code() == Some(StatusCode::TOO_MANY_REQUESTS)
+    }
+
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::Api { retry_after, .. } => *retry_after,
+            Self::Transport(_) => None,
+        }
+    }
+
+    /// Whether sending the same request again may succeed: timeouts, connection
+    /// failures, rate limiting and gateway errors.
+    pub fn is_retriable(&self) -> bool {
+        match self {
+            Self::Api { status, .. } => Self::is_retriable_status(*status),
+            Self::Transport(err) => err.is_timeout() || err.is_connect(),
+        }
+    }
+
+    pub(crate) fn is_retriable_status(status: StatusCode) -> bool {
+        matches!(status.as_u16(), 408 | 429 | 502 | 503 | 504)
+    }
+
+    /// The error body of an API error, when the server sent Dify's usual
+    /// `{"code", "message", "status"}` JSON.
+    pub fn api_error(&self) -> Option<DifyApiError> {
+        match self {
+            Self::Api { body, .. } => serde_json::from_str::<DifyApiError>(body)
+                .ok()
+                .filter(|error| !error.code.is_empty() || !error.message.is_empty()),
+            Self::Transport(_) => None,
+        }
+    }
+}
+
+/// The JSON error body returned by Dify for non-success responses, e.g.
+/// `{"code": "invalid_param", "message": "...", "status": 400}`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DifyApiError {
+    pub code: String,
+    pub message: String,
+    pub status: u16,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl fmt::Display for DifyApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.code, self.message)
+    }
+}
+
+impl fmt::Display for DifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Api { status, body, .. } => match self.api_error() {
+                Some(error) => write!(f, "dify api error {}: {}", status, error),
+                None => write!(f, "dify api error {}: {}", status, body),
+            },
+            Self::Transport(err) => write!(f, "request failed: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for DifyError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Api { .. } => None,
+            Self::Transport(err) => Some(err),
+        }
+    }
+}
+
+impl From<reqwest::Error> for DifyError {
+    fn from(value: reqwest::Error) -> Self {
+        Self::Transport(value)
+    }
+}
+
+/// Every problem found when validating a [`DifyConfig`](crate::DifyConfig)
+/// or a request, rather than just the first one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    pub problems: Vec<ConfigProblem>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigProblem {
+    EmptyApiKey,
+    InvalidBaseUrl { url: String, reason: String },
+    InvalidProxyUrl { url: String, reason: String },
+    ZeroTimeout { field: &'static str },
+    ZeroConcurrency,
+    ZeroRateLimit,
+    BackoffRange { initial_ms: u64, max_ms: u64 },
+    EmptyField { field: &'static str },
+    InputsNotObject,
+}
+
+impl ConfigError {
+    /// Turns the collected problems into a result, `Ok` when there are none.
+    pub(crate) fn check(problems: Vec<ConfigProblem>) -> std::result::Result<(), Self> {
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(Self { problems })
+        }
+    }
+}
+
+impl fmt::Display for ConfigProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyApiKey => f.write_str("api_key must not be empty"),
+            Self::InvalidBaseUrl { url, reason } => {
+                write!(f, "base_url {:?} is invalid: {}", url, reason)
+            }
+            Self::InvalidProxyUrl { url, reason } => {
+                write!(f, "proxy.url {:?} is invalid: {}", url, reason)
+            }
+            Self::ZeroTimeout { field } => write!(f, "{} must be greater than zero", field),
+            Self::ZeroConcurrency => f.write_str("limits.max_concurrent must be greater than zero"),
+            Self::ZeroRateLimit => {
+                f.write_str("limits.max_requests_per_minute must be greater than zero")
+            }
+            Self::BackoffRange { initial_ms, max_ms } => write!(
+                f,
+                "retry.initial_backoff_ms ({}) must not exceed retry.max_backoff_ms ({})",
+                initial_ms, max_ms
+            ),
+            Self::EmptyField { field } => write!(f, "{} must not be empty", field),
+            Self::InputsNotObject => f.write_str("inputs must be a JSON object"),
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid configuration: ")?;
+        for (index, problem) in self.problems.iter().enumerate() {
+            if index > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{}", problem)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// The Dify server does not run the version pinned in
+/// [`VersionConfig`](crate::config::VersionConfig).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionMismatch {
+    pub expected: String,
+    pub actual: String,
+}
+
+impl fmt::Display for VersionMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "dify server version {} does not match the expected version {}",
+            self.actual, self.expected
+        )
+    }
+}
+
+impl std::error::Error for VersionMismatch {}
+
+/// Another write to the dataset held its lock for longer than
+/// [`LimitsConfig::dataset_write_wait_secs`](crate::LimitsConfig::dataset_write_wait_secs).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DatasetBusy {
+    pub dataset_id: String,
+    pub waited: Duration,
+}
+
+impl fmt::Display for DatasetBusy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "dataset {} is busy, waited {:?} for other writes to finish",
+            self.dataset_id, self.waited
+        )
+    }
+}
+
+impl std::error::Error for DatasetBusy {}
+
+/// A streaming response broke off and could not be resumed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamInterrupted {
+    /// The answer text received before the interruption.
+    pub partial_answer: String,
+    pub reason: String,
+}
+
+impl fmt::Display for StreamInterrupted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "stream interrupted after {} bytes of answer: {}",
+            self.partial_answer.len(),
+            self.reason
+        )
+    }
+}
+
+impl std::error::Error for StreamInterrupted {}
+
+pub(crate) trait ResponseExt: Sized {
+    /// Turns a non-success response into a [`DifyError::Api`].
+    async fn check_status(self) -> Result<Self>;
+}
+
+/// The `Retry-After` header, in seconds or as an HTTP date. Dates in the past
+/// give a zero duration.
+pub(crate) fn retry_after(headers: &header::HeaderMap) -> Option<Duration> {
+    let value = headers.get(header::RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = UNIX_EPOCH + Duration::from_secs(timestamp::parse_http_date(value)?.try_into().ok()?);
+    Some(
+        at.duration_since(compat::system_time_now())
+            .unwrap_or_default(),
+    )
+}
+
+impl ResponseExt for Response {
+    async fn check_status(self) -> Result<Self> {
+        let status = self.status();
+        if status.is_success() {
+            return Ok(self);
+        }
+
+        let retry_after = retry_after(self.headers());
+        let body = self.text().await.unwrap_or_default();
+
+        Err(DifyError::Api {
+            status,
+            retry_after,
+            body,
+        }
+        .into())
+    }
+}
@@ -0,0 +1,100 @@
+//! Structured events from the reliability machinery of the client, so
+//! retries show up in logs and metrics instead of happening silently.
+
+use reqwest::{Method, StatusCode};
+use std::{fmt, sync::Arc, time::Duration};
+
+/// Why a request is retried.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RetryReason {
+    /// The server answered with a retriable status (429 or 5xx).
+    Status(StatusCode),
+    /// Connecting failed or the request timed out.
+    Transport(String),
+}
+
+impl fmt::Display for RetryReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RetryReason::Status(status) => write!(f, "status {}", status),
+            RetryReason::Transport(err) => write!(f, "error {}", err),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ClientEvent {
+    /// Attempt `attempt` (0-based) failed and the request is sent again after
+    /// `backoff`.
+    Retry {
+        method: Method,
+        url: String,
+        attempt: u32,
+        reason: RetryReason,
+        backoff: Duration,
+    },
+    /// The last of `attempts` attempts failed as well.
+    RetriesExhausted {
+        method: Method,
+        url: String,
+        attempts: u32,
+        reason: RetryReason,
+    },
+}
+
+/// Receives the [`ClientEvent`]s of a client.
+///
+/// Install listeners with
+/// [`DifyClient::with_event_listener`](crate::DifyClient::with_event_listener).
+/// They are called inline, so they should hand expensive work off.
+pub trait EventListener: Send + Sync {
+    fn on_event(&self, event: &ClientEvent);
+}
+
+impl<F> EventListener for F
+where
+    F: Fn(&ClientEvent) + Send + Sync,
+{
+    fn on_event(&self, event: &ClientEvent) {
+        self(event)
+    }
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct EventListeners(Vec<Arc<dyn EventListener>>);
+
+impl EventListeners {
+    pub(crate) fn push(&mut self, listener: Arc<dyn EventListener>) {
+        self.0.push(listener);
+    }
+
+    /// Logs `event` and passes it to every listener.
+    pub(crate) fn emit(&self, event: ClientEvent) {
+        match &event {
+            ClientEvent::Retry {
+                method,
+                url,
+                attempt,
+                reason,
+                backoff,
+            } => tracing::warn!(
+                %method,
+                %url,
+                attempt,
+                %reason,
+                backoff_ms = backoff.as_millis() as u64,
+                "retrying dify request"
+            ),
+            ClientEvent::RetriesExhausted {
+                method,
+                url,
+                attempts,
+                reason,
+            } => tracing::warn!(%method, %url, attempts, %reason, "dify request retries exhausted"),
+        }
+        for listener in &self.0 {
+            listener.on_event(&event);
+        }
+    }
+}
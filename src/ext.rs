@@ -0,0 +1,41 @@
+//! High-level helpers built on the typed API: conversation and duplex chat
+//! sessions, batch runs, input loading, cached app parameters and file
+//! metadata, stream transcript replay, OpenAI message conversion, a generic
+//! retriever interface, retrieval-augmented chat, spreadsheet segment
+//! imports, answer language checks, local PDF extraction, bounded archive
+//! extraction, re-embedding datasets and the process-wide client.
+
+#[cfg(any(feature = "chat", feature = "completion", feature = "workflow"))]
+pub mod batch;
+#[cfg(feature = "chat")]
+pub mod conversation;
+pub mod global;
+#[cfg(feature = "ingest")]
+pub mod ingest;
+pub mod inputs;
+#[cfg(feature = "lang-detect")]
+pub mod language;
+#[cfg(feature = "loadtest")]
+pub mod loadtest;
+pub mod openai;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod parameters;
+#[cfg(feature = "pdf")]
+pub mod pdf;
+#[cfg(all(feature = "chat", feature = "knowledge"))]
+pub mod rag;
+#[cfg(feature = "knowledge")]
+pub mod reembed;
+pub mod replay;
+#[cfg(feature = "knowledge")]
+pub mod retriever;
+#[cfg(feature = "knowledge")]
+pub mod segments;
+#[cfg(feature = "chat")]
+pub mod session;
+#[cfg(feature = "unzip")]
+pub mod unzip;
+#[cfg(feature = "knowledge")]
+pub mod upload_files;
+
+pub use global::{global, init, try_global};
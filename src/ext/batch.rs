@@ -0,0 +1,297 @@
+#[cfg(any(feature = "chat", feature = "fs"))]
+use anyhow::Context;
+use anyhow::Result;
+use futures_util::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+#[cfg(feature = "chat")]
+use crate::ChatClient;
+#[cfg(feature = "completion")]
+use crate::CompletionClient;
+#[cfg(feature = "workflow")]
+use crate::WorkflowClient;
+use crate::{compat, DifyError, ResponseMode, RetryConfig};
+
+/// One line of a batch job file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BatchJob {
+    pub id: Option<String>,
+    pub query: Option<String>,
+    pub inputs: Value,
+    pub user: Option<String>,
+    pub conversation_id: Option<String>,
+}
+
+/// One line of a batch result file, in the same order as the job file.
+/// `index` is the position of the job; for [`BatchRunner::run_file`] that is
+/// its zero-based line number, counting blank lines.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchResult {
+    pub index: usize,
+    pub id: Option<String>,
+    pub attempts: u32,
+    pub response: Option<Value>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BatchSummary {
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+}
+
+/// Reported to the [`BatchRunner::on_progress`] callback after every job.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BatchProgress {
+    pub completed: usize,
+    pub failed: usize,
+    /// The number of jobs, when known up front.
+    pub total: Option<usize>,
+}
+
+pub enum BatchTarget<'a> {
+    #[cfg(feature = "chat")]
+    Chat(&'a ChatClient),
+    #[cfg(feature = "completion")]
+    Completion(&'a CompletionClient),
+    #[cfg(feature = "workflow")]
+    Workflow(&'a WorkflowClient),
+}
+
+/// Runs a set of blocking requests against one app with bounded concurrency.
+pub struct BatchRunner<'a> {
+    target: BatchTarget<'a>,
+    concurrency: usize,
+    retry: RetryConfig,
+    user: String,
+    progress: Option<Box<dyn Fn(BatchProgress) + Send + Sync + 'a>>,
+}
+
+impl<'a> BatchRunner<'a> {
+    pub fn new(target: BatchTarget<'a>) -> Self {
+        Self {
+            target,
+            concurrency: 4,
+            retry: RetryConfig {
+                max_retries: 2,
+                ..Default::default()
+            },
+            user: "batch-runner".to_string(),
+            progress: None,
+        }
+    }
+
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    pub fn retry(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn user(mut self, user: &str) -> Self {
+        self.user = user.to_string();
+        self
+    }
+
+    pub fn on_progress(mut self, callback: impl Fn(BatchProgress) + Send + Sync + 'a) -> Self {
+        self.progress = Some(Box::new(callback));
+        self
+    }
+
+    pub async fn run_jobs(&self, jobs: Vec<BatchJob>) -> Vec<BatchResult> {
+        let total = jobs.len();
+        let mut results = self
+            .run_indexed(jobs.into_iter().enumerate(), Some(total))
+            .await;
+        results.sort_by_key(|result| result.index);
+        results
+    }
+
+    /// Runs one job per input map, e.g. one completion per recipient of a
+    /// mail merge. Inputs are pulled from the iterator lazily, so it may be
+    /// arbitrarily long.
+    pub async fn run_inputs<I>(&self, inputs: I) -> Vec<BatchResult>
+    where
+        I: IntoIterator<Item = Map<String, Value>>,
+    {
+        let inputs = inputs.into_iter();
+        let total = match inputs.size_hint() {
+            (lower, Some(upper)) if lower == upper => Some(lower),
+            _ => None,
+        };
+        let jobs = inputs.map(|inputs| BatchJob {
+            inputs: Value::Object(inputs),
+            ..Default::default()
+        });
+
+        let mut results = self.run_indexed(jobs.enumerate(), total).await;
+        results.sort_by_key(|result| result.index);
+        results
+    }
+
+    /// Reads a JSONL job file and writes a JSONL result file. Lines that fail to
+    /// parse are reported as failed results rather than aborting the run.
+    #[cfg(feature = "fs")]
+    pub async fn run_file(
+        &self,
+        input: impl AsRef<Path>,
+        output: impl AsRef<Path>,
+    ) -> Result<BatchSummary> {
+        let input = input.as_ref();
+        let content = tokio::fs::read_to_string(input)
+            .await
+            .with_context(|| format!("failed to read batch file {}", input.display()))?;
+
+        let mut jobs = Vec::new();
+        let mut invalid = Vec::new();
+        for (index, line) in content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+        {
+            match serde_json::from_str::<BatchJob>(line) {
+                Ok(job) => jobs.push((index, job)),
+                Err(err) => invalid.push(BatchResult {
+                    index,
+                    id: None,
+                    attempts: 0,
+                    response: None,
+                    error: Some(format!("invalid job: {}", err)),
+                }),
+            }
+        }
+
+        let total = jobs.len();
+        let mut results = self.run_indexed(jobs.into_iter(), Some(total)).await;
+        results.extend(invalid);
+        results.sort_by_key(|result| result.index);
+
+        let mut lines = String::new();
+        for result in &results {
+            lines.push_str(&serde_json::to_string(result)?);
+            lines.push('\n');
+        }
+        let output = output.as_ref();
+        tokio::fs::write(output, lines)
+            .await
+            .with_context(|| format!("failed to write batch results {}", output.display()))?;
+
+        let failed = results
+            .iter()
+            .filter(|result| result.error.is_some())
+            .count();
+        Ok(BatchSummary {
+            total: results.len(),
+            succeeded: results.len() - failed,
+            failed,
+        })
+    }
+
+    async fn run_indexed(
+        &self,
+        jobs: impl Iterator<Item = (usize, BatchJob)>,
+        total: Option<usize>,
+    ) -> Vec<BatchResult> {
+        let mut progress = BatchProgress {
+            total,
+            ..Default::default()
+        };
+        stream::iter(jobs)
+            .map(|(index, job)| self.run_with_retry(index, job))
+            .buffer_unordered(self.concurrency)
+            .inspect(|result| {
+                progress.completed += 1;
+                if result.error.is_some() {
+                    progress.failed += 1;
+                }
+                if let Some(callback) = &self.progress {
+                    callback(progress);
+                }
+            })
+            .collect()
+            .await
+    }
+
+    async fn run_with_retry(&self, index: usize, job: BatchJob) -> BatchResult {
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            match self.run_job(&job).await {
+                Ok(response) => {
+                    return BatchResult {
+                        index,
+                        id: job.id,
+                        attempts,
+                        response: Some(response),
+                        error: None,
+                    }
+                }
+                Err(err) => {
+                    let dify_err = DifyError::find(&err);
+                    let delay = self
+                        .retry
+                        .delay(attempts - 1, dify_err.and_then(DifyError::retry_after))
+                        .filter(|_| {
+                            attempts <= self.retry.max_retries
+                                && dify_err.is_some_and(DifyError::is_retriable)
+                        });
+                    let Some(delay) = delay else {
+                        return BatchResult {
+                            index,
+                            id: job.id,
+                            attempts,
+                            response: None,
+                            error: Some(format!("{:#}", err)),
+                        };
+                    };
+                    tracing::debug!("batch job {} failed, retrying: {:#}", index, err);
+                    compat::sleep(delay).await;
+                }
+            }
+        }
+    }
+
+    async fn run_job(&self, job: &BatchJob) -> Result<Value> {
+        let user = job.user.as_deref().unwrap_or(&self.user);
+        let inputs = if job.inputs.is_null() {
+            Value::Object(Default::default())
+        } else {
+            job.inputs.clone()
+        };
+
+        let response = match self.target {
+            #[cfg(feature = "chat")]
+            BatchTarget::Chat(client) => {
+                let query = job.query.as_deref().context("chat jobs require a query")?;
+                client
+                    .create_chat_message(
+                        inputs,
+                        query,
+                        user,
+                        ResponseMode::Block,
+                        job.conversation_id.as_deref(),
+                        None,
+                    )
+                    .await?
+            }
+            #[cfg(feature = "completion")]
+            BatchTarget::Completion(client) => {
+                client
+                    .create_completion_message(inputs, ResponseMode::Block, user, None)
+                    .await?
+            }
+            #[cfg(feature = "workflow")]
+            BatchTarget::Workflow(client) => client.run(inputs, ResponseMode::Block, user).await?,
+        };
+
+        Ok(response.json().await?)
+    }
+}
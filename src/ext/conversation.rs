@@ -0,0 +1,197 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use crate::{
+    api::models::{ChatMessageResponse, FileInput, FileType, Introduction},
+    compat, ChatClient, ResponseMode,
+};
+
+/// A chat session bound to one user, carrying the conversation id between turns.
+///
+/// Files attached with [`attach_file`](Self::attach_file) or
+/// [`attach_url`](Self::attach_url) are sent with every message until
+/// [`clear_attachments`](Self::clear_attachments) is called.
+pub struct Conversation {
+    client: Arc<ChatClient>,
+    user: String,
+    conversation_id: Option<String>,
+    attachments: Vec<FileInput>,
+}
+
+impl Conversation {
+    pub fn new(client: Arc<ChatClient>, user: &str, conversation_id: Option<&str>) -> Self {
+        Self {
+            client,
+            user: user.to_string(),
+            conversation_id: conversation_id.map(String::from),
+            attachments: Vec::new(),
+        }
+    }
+
+    pub fn user(&self) -> &str {
+        &self.user
+    }
+
+    pub fn conversation_id(&self) -> Option<&str> {
+        self.conversation_id.as_deref()
+    }
+
+    /// Attaches a file uploaded with
+    /// [`DifyClient::file_upload`](crate::DifyClient::file_upload).
+    pub fn attach_file(&mut self, file_type: impl Into<FileType>, upload_file_id: &str) {
+        self.attachments
+            .push(FileInput::local_file(file_type, upload_file_id));
+    }
+
+    pub fn attach_url(&mut self, file_type: impl Into<FileType>, url: &str) {
+        self.attachments.push(FileInput::remote_url(file_type, url));
+    }
+
+    pub fn attachments(&self) -> &[FileInput] {
+        &self.attachments
+    }
+
+    pub fn clear_attachments(&mut self) {
+        self.attachments.clear();
+    }
+
+    /// Starts a new conversation, dropping the current id and attachments,
+    /// and returns the app's opening statement and questions to greet the
+    /// user with.
+    pub async fn start(&mut self) -> Result<Option<Introduction>> {
+        self.conversation_id = None;
+        self.attachments.clear();
+        self.client.client().get_introduction(&self.user).await
+    }
+
+    pub async fn send(&mut self, query: &str, inputs: Value) -> Result<ChatMessageResponse> {
+        let files = (!self.attachments.is_empty()).then(|| self.attachments.clone());
+        let response = self
+            .client
+            .create_chat_message(
+                inputs,
+                query,
+                &self.user,
+                ResponseMode::Block,
+                self.conversation_id.as_deref(),
+                files,
+            )
+            .await?;
+
+        let mut message: ChatMessageResponse = response.json().await?;
+        message.answer = self.client.client().output().answer(message.answer);
+        if !message.conversation_id.is_empty() {
+            self.conversation_id = Some(message.conversation_id.to_string());
+        }
+
+        Ok(message)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredConversation {
+    pub conversation_id: String,
+    pub last_active: SystemTime,
+}
+
+/// Persistence for the user -> conversation mapping kept by [`ConversationManager`].
+pub trait ConversationStore: Send + Sync {
+    fn get(&self, user: &str) -> Option<StoredConversation>;
+    fn put(&self, user: &str, conversation: StoredConversation);
+    fn remove(&self, user: &str);
+}
+
+#[derive(Default)]
+pub struct InMemoryConversationStore {
+    conversations: Mutex<HashMap<String, StoredConversation>>,
+}
+
+impl ConversationStore for InMemoryConversationStore {
+    fn get(&self, user: &str) -> Option<StoredConversation> {
+        self.conversations.lock().unwrap().get(user).cloned()
+    }
+
+    fn put(&self, user: &str, conversation: StoredConversation) {
+        self.conversations
+            .lock()
+            .unwrap()
+            .insert(user.to_string(), conversation);
+    }
+
+    fn remove(&self, user: &str) {
+        self.conversations.lock().unwrap().remove(user);
+    }
+}
+
+/// Maps external user ids to their active conversation, starting a fresh one
+/// once the previous conversation has been idle for longer than `ttl`.
+pub struct ConversationManager<S = InMemoryConversationStore> {
+    client: Arc<ChatClient>,
+    store: S,
+    ttl: Duration,
+}
+
+impl ConversationManager {
+    pub fn new(client: Arc<ChatClient>, ttl: Duration) -> Self {
+        Self::with_store(client, InMemoryConversationStore::default(), ttl)
+    }
+}
+
+impl<S: ConversationStore> ConversationManager<S> {
+    pub fn with_store(client: Arc<ChatClient>, store: S, ttl: Duration) -> Self {
+        Self { client, store, ttl }
+    }
+
+    pub fn conversation(&self, user: &str) -> Conversation {
+        let conversation_id = self.active_conversation_id(user);
+        Conversation::new(self.client.clone(), user, conversation_id.as_deref())
+    }
+
+    pub fn commit(&self, conversation: &Conversation) {
+        if let Some(conversation_id) = conversation.conversation_id() {
+            self.store.put(
+                conversation.user(),
+                StoredConversation {
+                    conversation_id: conversation_id.to_string(),
+                    last_active: compat::system_time_now(),
+                },
+            );
+        }
+    }
+
+    pub async fn send(
+        &self,
+        user: &str,
+        query: &str,
+        inputs: Value,
+    ) -> Result<ChatMessageResponse> {
+        let mut conversation = self.conversation(user);
+        let message = conversation.send(query, inputs).await?;
+        self.commit(&conversation);
+        Ok(message)
+    }
+
+    pub fn reset(&self, user: &str) {
+        self.store.remove(user);
+    }
+
+    fn active_conversation_id(&self, user: &str) -> Option<String> {
+        let stored = self.store.get(user)?;
+        let idle = compat::system_time_now()
+            .duration_since(stored.last_active)
+            .unwrap_or_default();
+
+        if idle > self.ttl {
+            self.store.remove(user);
+            return None;
+        }
+
+        Some(stored.conversation_id)
+    }
+}
@@ -0,0 +1,28 @@
+use anyhow::Result;
+use std::sync::OnceLock;
+
+use crate::{DifyClient, DifyConfig};
+
+static GLOBAL: OnceLock<DifyClient> = OnceLock::new();
+
+/// Initializes the process-wide client returned by [`global`].
+///
+/// Fails if the client was already initialized.
+pub fn init(config: &DifyConfig) -> Result<&'static DifyClient> {
+    let client = DifyClient::from_config(config)?;
+    GLOBAL
+        .set(client)
+        .map_err(|_| anyhow::anyhow!("global dify client is already initialized"))?;
+    Ok(global())
+}
+
+/// Returns the process-wide client.
+///
+/// Panics if [`init`] has not been called; use [`try_global`] to check first.
+pub fn global() -> &'static DifyClient {
+    try_global().expect("global dify client is not initialized, call dify_client_rust::init first")
+}
+
+pub fn try_global() -> Option<&'static DifyClient> {
+    GLOBAL.get()
+}
@@ -0,0 +1,268 @@
+//! Web page ingestion: fetches a page, extracts its readable text and adds it
+//! to a dataset.
+
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+
+use crate::{
+    knowledge::models::{
+        DocumentMetadata, DocumentResponse, IndexingTechnique, MetadataField, MetadataValue,
+        ProcessRule,
+    },
+    KnowledgeBaseClient,
+};
+
+/// Elements whose content is never part of the readable text.
+const SKIPPED_TAGS: &[&str] = &[
+    "script", "style", "noscript", "template", "svg", "nav", "header", "footer", "aside", "form",
+];
+
+/// Elements that start a new line of text.
+const BLOCK_TAGS: &[&str] = &[
+    "p",
+    "div",
+    "br",
+    "li",
+    "tr",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "section",
+    "article",
+    "pre",
+    "blockquote",
+    "table",
+    "ul",
+    "ol",
+    "dt",
+    "dd",
+    "hr",
+];
+
+#[derive(Debug, Clone)]
+pub struct IngestOptions {
+    /// The document name, by default the page title or else the URL.
+    pub name: Option<String>,
+    pub process_rule: ProcessRule,
+    pub indexing_technique: IndexingTechnique,
+    /// A metadata field of the dataset that receives the page URL.
+    pub url_field: Option<MetadataField>,
+}
+
+impl Default for IngestOptions {
+    fn default() -> Self {
+        Self {
+            name: None,
+            process_rule: ProcessRule::Automatic,
+            indexing_technique: IndexingTechnique::HighQuality,
+            url_field: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct IngestedPage {
+    pub title: Option<String>,
+    pub response: DocumentResponse,
+}
+
+/// The readable part of an HTML page.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExtractedPage {
+    pub title: Option<String>,
+    pub text: String,
+}
+
+impl KnowledgeBaseClient {
+    /// Fetches `url`, extracts its readable text with [`extract_text`] and
+    /// creates a document from it.
+    pub async fn ingest_url(&self, url: &str, options: &IngestOptions) -> Result<IngestedPage> {
+        let html = self
+            .client()
+            .http_client()
+            .get(url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .with_context(|| format!("failed to fetch {}", url))?
+            .text()
+            .await
+            .with_context(|| format!("failed to read {}", url))?;
+
+        let page = extract_text(&html);
+        if page.text.is_empty() {
+            bail!("no readable text found at {}", url);
+        }
+
+        let name = options
+            .name
+            .as_deref()
+            .or(page.title.as_deref())
+            .unwrap_or(url);
+        let _guard = self.lock_dataset().await?;
+        let response = self
+            .create_document_by_text(
+                name,
+                &page.text,
+                options.process_rule.clone(),
+                options.indexing_technique.clone(),
+            )
+            .await?;
+
+        if let Some(field) = &options.url_field {
+            self.update_documents_metadata(&[DocumentMetadata {
+                document_id: response.document.id.to_string(),
+                metadata_list: vec![MetadataValue {
+                    id: field.id.to_string(),
+                    name: field.name.clone(),
+                    value: Value::String(url.to_string()),
+                    ..Default::default()
+                }],
+            }])
+            .await?;
+        }
+
+        Ok(IngestedPage {
+            title: page.title,
+            response,
+        })
+    }
+}
+
+/// Extracts the title and readable text of an HTML page. The content of the
+/// first `<article>` or `<main>` element is preferred over the whole body, and
+/// navigation, scripts and similar elements are dropped.
+pub fn extract_text(html: &str) -> ExtractedPage {
+    let title = element_content(html, "title")
+        .map(|title| collapse_whitespace(&decode_entities(title)))
+        .filter(|title| !title.is_empty());
+    let content = element_content(html, "article")
+        .or_else(|| element_content(html, "main"))
+        .or_else(|| element_content(html, "body"))
+        .unwrap_or(html);
+
+    let mut text = String::new();
+    let mut skipped: Option<(String, usize)> = None;
+    let mut rest = content;
+    while let Some(start) = rest.find('<') {
+        if skipped.is_none() {
+            text.push_str(&rest[..start]);
+        }
+        let tag_start = &rest[start..];
+        if let Some(comment) = tag_start.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let Some(end) = tag_start.find('>') else {
+            rest = "";
+            break;
+        };
+        let tag = &tag_start[1..end];
+        rest = &tag_start[end + 1..];
+
+        let closing = tag.starts_with('/');
+        let self_closing = tag.ends_with('/');
+        let name = tag
+            .trim_start_matches('/')
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+
+        if let Some((skipped_name, depth)) = &mut skipped {
+            if *skipped_name == name {
+                if closing {
+                    *depth -= 1;
+                } else if !self_closing {
+                    *depth += 1;
+                }
+            }
+            if *depth == 0 {
+                skipped = None;
+            }
+            continue;
+        }
+        if !closing && !self_closing && SKIPPED_TAGS.contains(&name.as_str()) {
+            skipped = Some((name, 1));
+        } else if BLOCK_TAGS.contains(&name.as_str()) {
+            text.push('\n');
+        }
+    }
+    if skipped.is_none() {
+        text.push_str(rest);
+    }
+
+    let text = decode_entities(&text)
+        .lines()
+        .map(collapse_whitespace)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+    ExtractedPage { title, text }
+}
+
+/// The content between the first `<name ...>` and the following `</name>`,
+/// matched case-insensitively.
+fn element_content<'a>(html: &'a str, name: &str) -> Option<&'a str> {
+    let lower = html.to_ascii_lowercase();
+    let open = format!("<{}", name);
+    let mut search_from = 0;
+    let start = loop {
+        let index = search_from + lower[search_from..].find(&open)?;
+        let after = lower[index + open.len()..].chars().next()?;
+        if after == '>' || after.is_ascii_whitespace() {
+            break index + lower[index..].find('>')? + 1;
+        }
+        search_from = index + open.len();
+    };
+    let end = start + lower[start..].find(&format!("</{}", name))?;
+    Some(&html[start..end])
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest[1..]
+            .find(';')
+            .filter(|&end| end <= 10)
+            .map(|end| &rest[1..end + 1]);
+        let character = entity.and_then(|entity| match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            _ => {
+                let code = entity.strip_prefix('#')?;
+                let code = match code.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                    None => code.parse().ok()?,
+                };
+                char::from_u32(code)
+            }
+        });
+        match (entity, character) {
+            (Some(entity), Some(character)) => {
+                decoded.push(character);
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
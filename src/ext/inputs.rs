@@ -0,0 +1,114 @@
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+use std::path::Path;
+
+/// Loads the `inputs` object for a completion or workflow call from a JSON,
+/// YAML (`yaml` feature) or TOML (`toml` feature) file, chosen by extension.
+pub fn load_inputs(path: impl AsRef<Path>) -> Result<Value> {
+    let path = path.as_ref();
+    let inputs = parse_file(path)?;
+    validate_inputs(&inputs).with_context(|| format!("invalid inputs in {}", path.display()))?;
+    Ok(inputs)
+}
+
+/// Loads a list of `inputs` objects, either from a top-level array or from an
+/// `inputs` array (the only shape TOML can express).
+pub fn load_inputs_list(path: impl AsRef<Path>) -> Result<Vec<Value>> {
+    let path = path.as_ref();
+    let list = match parse_file(path)? {
+        Value::Array(list) => list,
+        Value::Object(mut object) => match object.remove("inputs") {
+            Some(Value::Array(list)) => list,
+            _ => bail!(
+                "{}: expected a list of inputs or an `inputs` array",
+                path.display()
+            ),
+        },
+        other => bail!(
+            "{}: expected a list of inputs, found {}",
+            path.display(),
+            type_name(&other)
+        ),
+    };
+
+    for (index, inputs) in list.iter().enumerate() {
+        validate_inputs(inputs)
+            .with_context(|| format!("invalid inputs at index {} in {}", index, path.display()))?;
+    }
+
+    Ok(list)
+}
+
+pub fn inputs_from_json_str(content: &str) -> Result<Value> {
+    let inputs = serde_json::from_str(content).context("failed to parse JSON inputs")?;
+    validate_inputs(&inputs)?;
+    Ok(inputs)
+}
+
+#[cfg(feature = "yaml")]
+pub fn inputs_from_yaml_str(content: &str) -> Result<Value> {
+    let inputs = serde_yaml::from_str(content).context("failed to parse YAML inputs")?;
+    validate_inputs(&inputs)?;
+    Ok(inputs)
+}
+
+#[cfg(feature = "toml")]
+pub fn inputs_from_toml_str(content: &str) -> Result<Value> {
+    let inputs = toml::from_str(content).context("failed to parse TOML inputs")?;
+    validate_inputs(&inputs)?;
+    Ok(inputs)
+}
+
+/// Checks that `inputs` is a mapping of variable names to values, as Dify expects.
+pub fn validate_inputs(inputs: &Value) -> Result<()> {
+    let Some(object) = inputs.as_object() else {
+        bail!(
+            "inputs must be a mapping of variable names to values, found {}",
+            type_name(inputs)
+        );
+    };
+
+    for (name, value) in object {
+        if value.is_null() {
+            bail!("inputs.{}: value must not be null", name);
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_file(path: &Path) -> Result<Value> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read inputs file {}", path.display()))?;
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+
+    let parsed: Result<Value> = match extension.as_str() {
+        "json" => serde_json::from_str(&content).map_err(Into::into),
+        #[cfg(feature = "yaml")]
+        "yaml" | "yml" => serde_yaml::from_str(&content).map_err(Into::into),
+        #[cfg(not(feature = "yaml"))]
+        "yaml" | "yml" => bail!("loading YAML inputs requires the `yaml` feature"),
+        #[cfg(feature = "toml")]
+        "toml" => toml::from_str(&content).map_err(Into::into),
+        #[cfg(not(feature = "toml"))]
+        "toml" => bail!("loading TOML inputs requires the `toml` feature"),
+        _ => bail!("unsupported inputs file extension: {}", path.display()),
+    };
+
+    parsed.with_context(|| format!("failed to parse inputs file {}", path.display()))
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "a list",
+        Value::Object(_) => "a mapping",
+    }
+}
@@ -0,0 +1,153 @@
+//! Detects the language of answers and re-asks when it doesn't match the
+//! locale the user asked for, with the `whatlang` trigram detector.
+
+use anyhow::Result;
+use serde_json::Value;
+
+use crate::{models::ChatMessageResponse, Conversation};
+
+/// ISO 639-3 codes reported by the detector and their ISO 639-1 codes.
+const LANGUAGES: &[(&str, &str)] = &[
+    ("afr", "af"),
+    ("aka", "ak"),
+    ("amh", "am"),
+    ("ara", "ar"),
+    ("aze", "az"),
+    ("bel", "be"),
+    ("ben", "bn"),
+    ("bul", "bg"),
+    ("cat", "ca"),
+    ("ces", "cs"),
+    ("cmn", "zh"),
+    ("dan", "da"),
+    ("deu", "de"),
+    ("ell", "el"),
+    ("eng", "en"),
+    ("epo", "eo"),
+    ("est", "et"),
+    ("fin", "fi"),
+    ("fra", "fr"),
+    ("guj", "gu"),
+    ("heb", "he"),
+    ("hin", "hi"),
+    ("hrv", "hr"),
+    ("hun", "hu"),
+    ("hye", "hy"),
+    ("ind", "id"),
+    ("ita", "it"),
+    ("jav", "jv"),
+    ("jpn", "ja"),
+    ("kan", "kn"),
+    ("kat", "ka"),
+    ("khm", "km"),
+    ("kor", "ko"),
+    ("lat", "la"),
+    ("lav", "lv"),
+    ("lit", "lt"),
+    ("mal", "ml"),
+    ("mar", "mr"),
+    ("mkd", "mk"),
+    ("mya", "my"),
+    ("nep", "ne"),
+    ("nld", "nl"),
+    ("nob", "nb"),
+    ("ori", "or"),
+    ("pan", "pa"),
+    ("pes", "fa"),
+    ("pol", "pl"),
+    ("por", "pt"),
+    ("ron", "ro"),
+    ("rus", "ru"),
+    ("sin", "si"),
+    ("slk", "sk"),
+    ("slv", "sl"),
+    ("sna", "sn"),
+    ("spa", "es"),
+    ("srp", "sr"),
+    ("swe", "sv"),
+    ("tam", "ta"),
+    ("tel", "te"),
+    ("tgl", "tl"),
+    ("tha", "th"),
+    ("tuk", "tk"),
+    ("tur", "tr"),
+    ("ukr", "uk"),
+    ("urd", "ur"),
+    ("uzb", "uz"),
+    ("vie", "vi"),
+    ("yid", "yi"),
+    ("zul", "zu"),
+];
+
+/// Detections below this confidence are discarded. whatlang's own
+/// `is_reliable` (above 0.9) rejects many ordinary one-sentence answers.
+const MIN_CONFIDENCE: f64 = 0.5;
+
+/// The language of `text` as an ISO 639-1 code (ISO 639-3 for languages
+/// without one), or `None` when the text is too short or ambiguous to tell.
+pub fn detect_language(text: &str) -> Option<String> {
+    let info = whatlang::detect(text).filter(|info| info.confidence() >= MIN_CONFIDENCE)?;
+    let code = info.lang().code();
+    Some(
+        LANGUAGES
+            .iter()
+            .find(|(iso3, _)| *iso3 == code)
+            .map_or(code, |(_, iso1)| *iso1)
+            .to_string(),
+    )
+}
+
+/// Whether `text` is written in the language of `locale`, e.g. `fr` or
+/// `zh-CN`, or `None` when its language can't be detected.
+pub fn matches_locale(text: &str, locale: &str) -> Option<bool> {
+    let detected = detect_language(text)?;
+    Some(detected.eq_ignore_ascii_case(&locale_language(locale)))
+}
+
+/// The instruction appended to a query re-asked in `locale`.
+pub fn language_instruction(locale: &str) -> String {
+    let language = locale_language(locale);
+    let code = LANGUAGES
+        .iter()
+        .find(|(_, iso1)| *iso1 == language)
+        .map_or(language.as_str(), |(iso3, _)| *iso3);
+    match whatlang::Lang::from_code(code) {
+        Some(lang) => format!("Please answer in {}.", lang.eng_name()),
+        None => format!("Please answer in the language of the locale {}.", locale),
+    }
+}
+
+/// The lowercase language subtag of a locale: `zh` for `zh-CN` or `zh_CN`.
+fn locale_language(locale: &str) -> String {
+    locale
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+impl Conversation {
+    /// Like [`send`](Self::send), but when the answer is detected to be in
+    /// another language than `locale`, asks again once with
+    /// [`language_instruction`] appended to `query`. Answers whose language
+    /// can't be detected are returned as is.
+    pub async fn send_in_language(
+        &mut self,
+        query: &str,
+        inputs: Value,
+        locale: &str,
+    ) -> Result<ChatMessageResponse> {
+        let message = self.send(query, inputs.clone()).await?;
+        if matches_locale(&message.answer, locale) != Some(false) {
+            return Ok(message);
+        }
+
+        tracing::debug!(
+            "answer of message {} is not in {}, asking again",
+            message.message_id,
+            locale
+        );
+        let query = format!("{}\n\n{}", query, language_instruction(locale));
+        self.send(&query, inputs).await
+    }
+}
@@ -0,0 +1,271 @@
+//! Load generation against a (usually self-hosted) Dify instance. Enabled by
+//! the `loadtest` feature.
+
+use anyhow::{bail, Result};
+use futures_util::future::join_all;
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+use tokio::time::Instant;
+
+use crate::{error::ResponseExt, ChatClient, KnowledgeBaseClient, ResponseMode, WorkflowClient};
+
+enum Scenario<'a> {
+    Chat {
+        client: &'a ChatClient,
+        query: String,
+        inputs: Value,
+    },
+    Workflow {
+        client: &'a WorkflowClient,
+        inputs: Value,
+    },
+    Retrieve {
+        client: &'a KnowledgeBaseClient,
+        query: String,
+    },
+}
+
+impl Scenario<'_> {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Chat { .. } => "chat",
+            Self::Workflow { .. } => "workflow",
+            Self::Retrieve { .. } => "retrieve",
+        }
+    }
+
+    async fn run(&self, user: &str) -> Result<()> {
+        match self {
+            Self::Chat {
+                client,
+                query,
+                inputs,
+            } => {
+                client
+                    .create_chat_message(
+                        inputs.clone(),
+                        query,
+                        user,
+                        ResponseMode::Block,
+                        None,
+                        None,
+                    )
+                    .await?
+                    .check_status()
+                    .await?
+                    .bytes()
+                    .await?;
+            }
+            Self::Workflow { client, inputs } => {
+                client
+                    .run(inputs.clone(), ResponseMode::Block, user)
+                    .await?
+                    .check_status()
+                    .await?
+                    .bytes()
+                    .await?;
+            }
+            Self::Retrieve { client, query } => {
+                client.retrieve(query, None).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Sends a weighted mix of blocking requests from `concurrency` workers for a
+/// fixed duration and reports latency percentiles and error rates. Requests
+/// still running when the duration is up are cancelled.
+pub struct LoadTest<'a> {
+    scenarios: Vec<(Scenario<'a>, u32)>,
+    concurrency: usize,
+    duration: Duration,
+    user: String,
+}
+
+impl<'a> LoadTest<'a> {
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            scenarios: Vec::new(),
+            concurrency: 8,
+            duration,
+            user: "load-test".to_string(),
+        }
+    }
+
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    pub fn user(mut self, user: &str) -> Self {
+        self.user = user.to_string();
+        self
+    }
+
+    /// Adds chat messages to the mix, `weight` out of the total of all weights.
+    pub fn chat(mut self, client: &'a ChatClient, query: &str, inputs: Value, weight: u32) -> Self {
+        self.scenarios.push((
+            Scenario::Chat {
+                client,
+                query: query.to_string(),
+                inputs,
+            },
+            weight,
+        ));
+        self
+    }
+
+    pub fn workflow(mut self, client: &'a WorkflowClient, inputs: Value, weight: u32) -> Self {
+        self.scenarios
+            .push((Scenario::Workflow { client, inputs }, weight));
+        self
+    }
+
+    pub fn retrieve(mut self, client: &'a KnowledgeBaseClient, query: &str, weight: u32) -> Self {
+        self.scenarios.push((
+            Scenario::Retrieve {
+                client,
+                query: query.to_string(),
+            },
+            weight,
+        ));
+        self
+    }
+
+    pub async fn run(&self) -> Result<LoadReport> {
+        // requests are spread over the scenarios by deterministic weighted round robin
+        let schedule: Vec<&Scenario> = self
+            .scenarios
+            .iter()
+            .flat_map(|(scenario, weight)| std::iter::repeat_n(scenario, *weight as usize))
+            .collect();
+        if schedule.is_empty() {
+            bail!("load test has no scenarios with a non-zero weight");
+        }
+
+        let started = Instant::now();
+        let deadline = started + self.duration;
+        let next = AtomicUsize::new(0);
+        let cut_off = AtomicUsize::new(0);
+        let samples = Mutex::new(Vec::new());
+        let (schedule, next, cut_off_ref, samples_ref) = (&schedule, &next, &cut_off, &samples);
+
+        let workers = (0..self.concurrency).map(move |_| async move {
+            while Instant::now() < deadline {
+                let scenario = schedule[next.fetch_add(1, Ordering::Relaxed) % schedule.len()];
+                let sent = Instant::now();
+                let Ok(result) = tokio::time::timeout_at(deadline, scenario.run(&self.user)).await
+                else {
+                    cut_off_ref.fetch_add(1, Ordering::Relaxed);
+                    break;
+                };
+                if let Err(err) = &result {
+                    tracing::debug!("load test {} request failed: {:#}", scenario.kind(), err);
+                }
+                samples_ref
+                    .lock()
+                    .unwrap()
+                    .push((scenario.kind(), sent.elapsed(), result.is_ok()));
+            }
+        });
+        join_all(workers).await;
+
+        Ok(LoadReport::new(
+            samples.into_inner().unwrap(),
+            cut_off.into_inner(),
+            started.elapsed(),
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LoadReport {
+    pub elapsed: Duration,
+    pub overall: LatencyStats,
+    pub by_kind: BTreeMap<&'static str, LatencyStats>,
+    /// Requests still running at the end of the test, cancelled and left out
+    /// of the latency stats.
+    pub cut_off: usize,
+}
+
+impl LoadReport {
+    fn new(
+        samples: Vec<(&'static str, Duration, bool)>,
+        cut_off: usize,
+        elapsed: Duration,
+    ) -> Self {
+        let mut by_kind: BTreeMap<&'static str, Vec<(Duration, bool)>> = BTreeMap::new();
+        for (kind, latency, ok) in &samples {
+            by_kind.entry(*kind).or_default().push((*latency, *ok));
+        }
+
+        let overall = samples
+            .into_iter()
+            .map(|(_, latency, ok)| (latency, ok))
+            .collect();
+        Self {
+            elapsed,
+            overall: LatencyStats::from_samples(overall),
+            by_kind: by_kind
+                .into_iter()
+                .map(|(kind, samples)| (kind, LatencyStats::from_samples(samples)))
+                .collect(),
+            cut_off,
+        }
+    }
+
+    pub fn requests_per_sec(&self) -> f64 {
+        self.overall.count as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LatencyStats {
+    pub count: usize,
+    pub errors: usize,
+    pub mean: Duration,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+impl LatencyStats {
+    pub fn from_samples(samples: Vec<(Duration, bool)>) -> Self {
+        let errors = samples.iter().filter(|(_, ok)| !ok).count();
+        let mut latencies: Vec<Duration> =
+            samples.into_iter().map(|(latency, _)| latency).collect();
+        latencies.sort();
+
+        let count = latencies.len();
+        if count == 0 {
+            return Self::default();
+        }
+        let percentile =
+            |p: f64| latencies[((count as f64 * p).ceil() as usize).clamp(1, count) - 1];
+        Self {
+            count,
+            errors,
+            mean: latencies.iter().sum::<Duration>() / count as u32,
+            p50: percentile(0.50),
+            p90: percentile(0.90),
+            p99: percentile(0.99),
+            max: latencies[count - 1],
+        }
+    }
+
+    pub fn error_rate(&self) -> f64 {
+        if self.count == 0 {
+            return 0.0;
+        }
+        self.errors as f64 / self.count as f64
+    }
+}
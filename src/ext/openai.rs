@@ -0,0 +1,88 @@
+//! Conversion between Dify conversation history and the OpenAI-style
+//! `[{"role": ..., "content": ...}]` message array.
+
+use serde::{de, Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+use crate::api::models::{string_enum, HistoryMessage};
+
+string_enum!(
+    /// The `role` of an OpenAI-style message.
+    Role {
+        System => "system",
+        User => "user",
+        Assistant => "assistant",
+        Tool => "tool",
+    }
+);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpenAiMessage {
+    pub role: Role,
+    /// Content given as an array of parts is joined from its text parts.
+    #[serde(deserialize_with = "text_content")]
+    pub content: String,
+}
+
+impl OpenAiMessage {
+    pub fn new(role: Role, content: &str) -> Self {
+        Self {
+            role,
+            content: content.to_string(),
+        }
+    }
+}
+
+/// Turns each Dify message into a `user` message with its query followed by an
+/// `assistant` message with its answer, skipping empty answers.
+pub fn to_openai(history: &[HistoryMessage]) -> Vec<OpenAiMessage> {
+    let mut messages = Vec::with_capacity(history.len() * 2);
+    for message in history {
+        messages.push(OpenAiMessage::new(Role::User, &message.query));
+        if !message.answer.is_empty() {
+            messages.push(OpenAiMessage::new(Role::Assistant, &message.answer));
+        }
+    }
+    messages
+}
+
+/// Pairs every `user` message with the `assistant` messages that follow it.
+/// Dify has no place for other roles, so `system` and `tool` messages are
+/// skipped, as are assistant messages before the first user message.
+pub fn from_openai(messages: &[OpenAiMessage]) -> Vec<HistoryMessage> {
+    let mut history: Vec<HistoryMessage> = Vec::new();
+    for message in messages {
+        match message.role {
+            Role::User => history.push(HistoryMessage {
+                query: message.content.clone(),
+                ..Default::default()
+            }),
+            Role::Assistant => {
+                if let Some(last) = history.last_mut() {
+                    if !last.answer.is_empty() {
+                        last.answer.push('\n');
+                    }
+                    last.answer.push_str(&message.content);
+                }
+            }
+            _ => {}
+        }
+    }
+    history
+}
+
+fn text_content<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    match Value::deserialize(deserializer)? {
+        Value::Null => Ok(String::new()),
+        Value::String(content) => Ok(content),
+        Value::Array(parts) => Ok(parts
+            .iter()
+            .filter_map(|part| part.get("text").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join("\n")),
+        other => Err(de::Error::custom(format!(
+            "expected string or array content, got {}",
+            other
+        ))),
+    }
+}
@@ -0,0 +1,63 @@
+use std::{sync::Arc, time::Duration};
+use tokio::{sync::watch, task::JoinHandle};
+
+use crate::{models::AppParameters, DifyClient};
+
+/// Refresh intervals below this are raised to it, so a misconfigured interval
+/// cannot hammer the `/parameters` endpoint.
+pub const MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The app's `/parameters`, refreshed in the background.
+///
+/// [`latest`](Self::latest) never blocks on the network: it returns the copy
+/// fetched by the last successful refresh. Failed refreshes keep the previous
+/// copy. The background task stops when the cache is dropped.
+pub struct ParametersCache {
+    receiver: watch::Receiver<Option<Arc<AppParameters>>>,
+    task: JoinHandle<()>,
+}
+
+impl ParametersCache {
+    /// Starts refreshing the parameters every `interval`, beginning
+    /// immediately. Must be called from within a tokio runtime.
+    pub fn spawn(client: DifyClient, user: &str, interval: Duration) -> Self {
+        let (sender, receiver) = watch::channel(None);
+        let user = user.to_string();
+        let interval = interval.max(MIN_REFRESH_INTERVAL);
+
+        let task = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                if let Ok(parameters) = client.get_parameters(&user).await {
+                    let changed = sender.borrow().as_deref() != Some(&parameters);
+                    if changed && sender.send(Some(Arc::new(parameters))).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+
+        Self { receiver, task }
+    }
+
+    /// The most recently fetched parameters, or `None` before the first
+    /// successful refresh.
+    pub fn latest(&self) -> Option<Arc<AppParameters>> {
+        self.receiver.borrow().clone()
+    }
+
+    /// Waits until the parameters change on the server (or are fetched for
+    /// the first time) and returns the new copy.
+    pub async fn changed(&mut self) -> Option<Arc<AppParameters>> {
+        self.receiver.changed().await.ok()?;
+        self.latest()
+    }
+}
+
+impl Drop for ParametersCache {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
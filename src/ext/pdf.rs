@@ -0,0 +1,107 @@
+//! Local PDF text extraction, for creating documents from text extracted on
+//! the client instead of relying on Dify's server-side parsing.
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::path::Path;
+
+use crate::{
+    knowledge::models::{
+        DocumentMetadata, DocumentResponse, IndexingTechnique, MetadataField, MetadataValue,
+        ProcessRule,
+    },
+    KnowledgeBaseClient,
+};
+
+/// The text of one PDF page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PdfPage {
+    /// 1-based page number.
+    pub number: u32,
+    pub text: String,
+}
+
+pub fn extract_pdf_pages(path: impl AsRef<Path>) -> Result<Vec<PdfPage>> {
+    let path = path.as_ref();
+    let document = lopdf::Document::load(path)
+        .with_context(|| format!("failed to load PDF {}", path.display()))?;
+    pages_of(&document).with_context(|| format!("failed to extract text from {}", path.display()))
+}
+
+pub fn extract_pdf_pages_from_bytes(bytes: &[u8]) -> Result<Vec<PdfPage>> {
+    let document = lopdf::Document::load_mem(bytes).context("failed to load PDF")?;
+    pages_of(&document)
+}
+
+fn pages_of(document: &lopdf::Document) -> Result<Vec<PdfPage>> {
+    document
+        .get_pages()
+        .into_keys()
+        .map(|number| {
+            Ok(PdfPage {
+                number,
+                text: document.extract_text(&[number])?.trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+impl KnowledgeBaseClient {
+    /// Creates one document per non-empty page of a PDF, named
+    /// `"{file name} (page 3)"`. The page number is stored in `page_field`
+    /// when given.
+    pub async fn create_documents_from_pdf(
+        &self,
+        path: impl AsRef<Path>,
+        process_rule: ProcessRule,
+        indexing_technique: IndexingTechnique,
+        page_field: Option<&MetadataField>,
+    ) -> Result<Vec<(PdfPage, DocumentResponse)>> {
+        let path = path.as_ref();
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "document.pdf".to_string());
+
+        let pages = {
+            let path = path.to_path_buf();
+            tokio::task::spawn_blocking(move || extract_pdf_pages(path)).await??
+        };
+        let _guard = self.lock_dataset().await?;
+        let mut documents = Vec::new();
+        for page in pages {
+            if page.text.is_empty() {
+                continue;
+            }
+            let response = self
+                .create_document_by_text(
+                    &format!("{} (page {})", file_name, page.number),
+                    &page.text,
+                    process_rule.clone(),
+                    indexing_technique.clone(),
+                )
+                .await?;
+            documents.push((page, response));
+        }
+
+        if let Some(field) = page_field {
+            let updates: Vec<_> = documents
+                .iter()
+                .map(|(page, response)| DocumentMetadata {
+                    document_id: response.document.id.to_string(),
+                    metadata_list: vec![MetadataValue {
+                        id: field.id.to_string(),
+                        name: field.name.clone(),
+                        value: Value::from(page.number),
+                        ..Default::default()
+                    }],
+                })
+                .collect();
+            if !updates.is_empty() {
+                self.update_documents_metadata(&updates).await?;
+            }
+        }
+
+        Ok(documents)
+    }
+}
@@ -0,0 +1,138 @@
+//! One-call retrieval-augmented chat for apps that don't bind datasets in
+//! Dify: retrieves chunks from datasets, passes them as an input variable and
+//! sends the message.
+
+use anyhow::Result;
+use futures_util::future;
+use reqwest::Response;
+use serde_json::{Map, Value};
+
+use crate::{
+    ext::retriever::Retriever, knowledge::models::RetrievedDocument, ChatClient,
+    KnowledgeBaseClient, ResponseMode,
+};
+
+#[derive(Debug, Clone)]
+pub struct RetrieveAndChatOptions {
+    /// The input variable of the app that receives the formatted chunks.
+    pub context_variable: String,
+    /// Chunks passed on, best matches across all datasets first.
+    pub top_k: usize,
+    /// Drops chunks scoring below this.
+    pub score_threshold: Option<f64>,
+    /// Other inputs of the app.
+    pub inputs: Map<String, Value>,
+    pub response_mode: ResponseMode,
+    pub conversation_id: Option<String>,
+}
+
+impl Default for RetrieveAndChatOptions {
+    fn default() -> Self {
+        Self {
+            context_variable: "context".to_string(),
+            top_k: 3,
+            score_threshold: None,
+            inputs: Map::new(),
+            response_mode: ResponseMode::Block,
+            conversation_id: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct RetrieveAndChatResponse {
+    /// The chunks sent as context, best match first.
+    pub documents: Vec<RetrievedDocument>,
+    pub response: Response,
+}
+
+/// Formats chunks as numbered sources, e.g. `[1] Refund policy` followed by
+/// the chunk, separated by blank lines.
+pub fn format_context(documents: &[RetrievedDocument]) -> String {
+    documents
+        .iter()
+        .enumerate()
+        .map(|(index, document)| {
+            let mut chunk = format!("[{}]", index + 1);
+            if let Some(name) = document
+                .metadata
+                .get("document_name")
+                .and_then(Value::as_str)
+            {
+                chunk.push(' ');
+                chunk.push_str(name);
+            }
+            chunk.push('\n');
+            chunk.push_str(&document.content);
+            if let Some(answer) = document.metadata.get("answer").and_then(Value::as_str) {
+                chunk.push('\n');
+                chunk.push_str(answer);
+            }
+            chunk
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+impl ChatClient {
+    /// Retrieves `query` from `dataset_ids` with the dataset API key of
+    /// `knowledge`, formats the `top_k` best chunks with [`format_context`]
+    /// into `context_variable` and sends `query` to the chat app.
+    pub async fn retrieve_and_chat(
+        &self,
+        knowledge: &KnowledgeBaseClient,
+        query: &str,
+        dataset_ids: &[&str],
+        user: &str,
+        options: &RetrieveAndChatOptions,
+    ) -> Result<RetrieveAndChatResponse> {
+        let datasets: Vec<_> = dataset_ids
+            .iter()
+            .map(|&dataset_id| {
+                KnowledgeBaseClient::with_client(knowledge.dify_client().clone(), Some(dataset_id))
+            })
+            .collect();
+        let results = future::try_join_all(
+            datasets
+                .iter()
+                .map(|dataset| dataset.retrieve_documents(query)),
+        )
+        .await?;
+
+        let mut documents: Vec<_> = results
+            .into_iter()
+            .flatten()
+            .filter(|document| match (options.score_threshold, document.score) {
+                (Some(threshold), Some(score)) => score >= threshold,
+                _ => true,
+            })
+            .collect();
+        documents.sort_by(|a, b| {
+            b.score
+                .unwrap_or_default()
+                .total_cmp(&a.score.unwrap_or_default())
+        });
+        documents.truncate(options.top_k);
+
+        let mut inputs = options.inputs.clone();
+        inputs.insert(
+            options.context_variable.clone(),
+            Value::String(format_context(&documents)),
+        );
+        let response = self
+            .create_chat_message(
+                Value::Object(inputs),
+                query,
+                user,
+                options.response_mode,
+                options.conversation_id.as_deref(),
+                None,
+            )
+            .await?;
+
+        Ok(RetrieveAndChatResponse {
+            documents,
+            response,
+        })
+    }
+}
@@ -0,0 +1,202 @@
+//! Re-ingests a dataset with another embedding model. Dify can't re-embed a
+//! dataset in place, so the documents and segments are copied into a new
+//! dataset created with the target model.
+
+use anyhow::{Context, Result};
+use futures_util::TryStreamExt;
+use std::time::Duration;
+
+use crate::{
+    knowledge::models::{
+        CreateDatasetRequest, Dataset, Document, IndexingTechnique, NewSegment, ProcessRule,
+        ProcessRuleRules, RetrievalModel, Segment, Segmentation, UpdateSegmentRequest,
+    },
+    KnowledgeBaseClient,
+};
+
+/// Documents and segments are listed in pages of this many items.
+const PAGE_SIZE: u32 = 100;
+
+/// Segments are added in requests of at most this many segments.
+const COPY_BATCH_SIZE: usize = 100;
+
+/// Large enough that the seed segment of a copied document isn't split again.
+const SEED_MAX_TOKENS: u32 = 4000;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+const INDEXING_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Embedding model of a dataset, e.g. `openai` / `text-embedding-3-large`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbeddingModel {
+    pub provider: String,
+    pub model: String,
+}
+
+impl EmbeddingModel {
+    pub fn new(provider: &str, model: &str) -> Self {
+        Self {
+            provider: provider.to_string(),
+            model: model.to_string(),
+        }
+    }
+}
+
+/// Reported after each copied document.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReembedProgress {
+    pub total_documents: u64,
+    pub copied_documents: u64,
+    pub copied_segments: u64,
+    /// Name of the document that was just copied.
+    pub document: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReembedSummary {
+    pub dataset: Dataset,
+    /// Pairs of source and copied document ids.
+    pub documents: Vec<(String, String)>,
+    pub segments: u64,
+}
+
+/// Builds the request that creates the copy of `source`: same description,
+/// permission and retrieval settings, high quality indexing with `model`.
+pub fn reembed_dataset_request(
+    source: &Dataset,
+    name: &str,
+    model: &EmbeddingModel,
+) -> CreateDatasetRequest {
+    CreateDatasetRequest {
+        name: name.to_string(),
+        description: source.description.clone(),
+        indexing_technique: Some(IndexingTechnique::HighQuality),
+        permission: Some(source.permission.clone()),
+        embedding_model_provider: Some(model.provider.clone()),
+        embedding_model: Some(model.model.clone()),
+        retrieval_model: source
+            .retrieval_model_dict
+            .clone()
+            .and_then(|retrieval_model| {
+                serde_json::from_value::<RetrievalModel>(retrieval_model).ok()
+            }),
+        ..Default::default()
+    }
+}
+
+impl KnowledgeBaseClient {
+    /// Copies this dataset into a new dataset named `name` that embeds with
+    /// `model`, calling `progress` after each document.
+    ///
+    /// Each document is created from its first segment, then the remaining
+    /// segments are added once it is indexed, so segment boundaries, answers
+    /// and keywords are kept. Documents without segments are skipped. The
+    /// source dataset is left untouched; on error the partially copied
+    /// dataset is kept and has to be deleted by the caller.
+    pub async fn reembed(
+        &self,
+        name: &str,
+        model: &EmbeddingModel,
+        progress: impl Fn(ReembedProgress) + Send + Sync,
+    ) -> Result<ReembedSummary> {
+        let source_id = self.dataset_id().context("dataset_id is not set")?;
+        let source = self.get_dataset(source_id).await?;
+        let dataset = self
+            .create_dataset_with_config(&reembed_dataset_request(&source, name, model))
+            .await?;
+        let target =
+            KnowledgeBaseClient::with_client(self.dify_client().clone(), Some(&dataset.id));
+
+        let mut state = ReembedProgress {
+            total_documents: source.document_count,
+            ..Default::default()
+        };
+        let mut documents = Vec::new();
+        let mut pages = self.paginate_documents(None, PAGE_SIZE);
+        while let Some(document) = pages.try_next().await? {
+            let segments: Vec<Segment> = self
+                .paginate_segments(&document.id, None, None, PAGE_SIZE)
+                .try_collect()
+                .await?;
+            if let Some(copy) = target
+                .copy_document(&document, &segments)
+                .await
+                .with_context(|| format!("failed to copy document {}", document.name))?
+            {
+                documents.push((document.id.to_string(), copy));
+                state.copied_segments += segments.len() as u64;
+            }
+            state.copied_documents += 1;
+            state.document = document.name;
+            progress(state.clone());
+        }
+
+        Ok(ReembedSummary {
+            dataset,
+            documents,
+            segments: state.copied_segments,
+        })
+    }
+
+    /// Creates `document` in this dataset from `segments` and returns the new
+    /// document id, or `None` if there is nothing to copy.
+    async fn copy_document(
+        &self,
+        document: &Document,
+        segments: &[Segment],
+    ) -> Result<Option<String>> {
+        let Some((seed, rest)) = segments.split_first() else {
+            return Ok(None);
+        };
+        let process_rule = ProcessRule::Custom {
+            rules: ProcessRuleRules {
+                pre_processing_rules: Vec::new(),
+                segmentation: Segmentation {
+                    max_tokens: SEED_MAX_TOKENS,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        };
+        let created = self
+            .create_document_by_text(
+                &document.name,
+                &seed.content,
+                process_rule,
+                IndexingTechnique::HighQuality,
+            )
+            .await?;
+        let document_id = created.document.id.to_string();
+        self.wait_until_indexed(&created.batch, POLL_INTERVAL, INDEXING_TIMEOUT)
+            .await?;
+
+        if seed.answer.is_some() || !seed.keywords.is_empty() {
+            let seeded: Vec<Segment> = self
+                .paginate_segments(&document_id, None, None, PAGE_SIZE)
+                .try_collect()
+                .await?;
+            if let Some(segment) = seeded.first() {
+                let request = UpdateSegmentRequest {
+                    answer: seed.answer.clone(),
+                    keywords: Some(seed.keywords.clone()).filter(|keywords| !keywords.is_empty()),
+                    ..UpdateSegmentRequest::new(&seed.content)
+                };
+                self.update_segment(&document_id, &segment.id, &request)
+                    .await?;
+            }
+        }
+
+        let rest: Vec<NewSegment> = rest
+            .iter()
+            .map(|segment| NewSegment {
+                content: segment.content.clone(),
+                answer: segment.answer.clone(),
+                keywords: segment.keywords.clone(),
+            })
+            .collect();
+        for batch in rest.chunks(COPY_BATCH_SIZE) {
+            self.add_segments(&document_id, batch).await?;
+        }
+        Ok(Some(document_id))
+    }
+}
@@ -0,0 +1,91 @@
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use futures_util::stream;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+
+use crate::{
+    compat::{self, Instant},
+    EventStream,
+};
+
+/// One recorded stream event, `offset_ms` after the stream started.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptEntry {
+    pub offset_ms: u64,
+    pub event: Value,
+}
+
+/// Records stream events with their timing, for later [`replay`].
+///
+/// The transcript is stored as JSON lines, one [`TranscriptEntry`] per line.
+pub struct TranscriptRecorder {
+    started: Instant,
+    entries: Vec<TranscriptEntry>,
+}
+
+impl Default for TranscriptRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TranscriptRecorder {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn record<T: Serialize>(&mut self, event: &T) -> Result<()> {
+        self.entries.push(TranscriptEntry {
+            offset_ms: self.started.elapsed().as_millis() as u64,
+            event: serde_json::to_value(event)?,
+        });
+        Ok(())
+    }
+
+    pub fn entries(&self) -> &[TranscriptEntry] {
+        &self.entries
+    }
+
+    pub fn to_jsonl(&self) -> Result<String> {
+        let mut lines = String::new();
+        for entry in &self.entries {
+            lines.push_str(&serde_json::to_string(entry)?);
+            lines.push('\n');
+        }
+        Ok(lines)
+    }
+}
+
+pub fn parse_transcript(content: &str) -> Result<Vec<TranscriptEntry>> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line)
+                .with_context(|| format!("invalid transcript entry on line {}", index + 1))
+        })
+        .collect()
+}
+
+/// Replays a transcript as an event stream, e.g. a
+/// [`ChatStream`](crate::ChatStream), with the original timing divided by
+/// `speed`. A `speed` of `f64::INFINITY` replays without delays.
+pub fn replay<T: DeserializeOwned>(entries: Vec<TranscriptEntry>, speed: f64) -> EventStream<T> {
+    let started = Instant::now();
+    let chunks = stream::unfold(entries.into_iter(), move |mut entries| async move {
+        let entry = entries.next()?;
+        if speed.is_finite() && speed > 0.0 {
+            let offset = Duration::from_millis(entry.offset_ms).div_f64(speed);
+            compat::sleep(offset.saturating_sub(started.elapsed())).await;
+        }
+        let chunk = format!("data: {}\n\n", entry.event);
+        Some((Ok(Bytes::from(chunk)), entries))
+    });
+    EventStream::from_bytes_stream(chunks)
+}
@@ -0,0 +1,52 @@
+//! A backend-neutral retrieval interface, implemented by
+//! [`KnowledgeBaseClient`] so Dify datasets can be swapped with other vector
+//! stores.
+
+use anyhow::Result;
+use serde_json::Value;
+use std::sync::Arc;
+
+use crate::{compat::BoxFuture, knowledge::models::RetrievedDocument, KnowledgeBaseClient};
+
+/// Finds the documents relevant to a query, best match first.
+pub trait Retriever: Send + Sync {
+    fn retrieve_documents<'a>(
+        &'a self,
+        query: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<RetrievedDocument>>>;
+}
+
+/// Uses the dataset's default retrieval settings. Every document carries the
+/// `dataset_id` in its metadata.
+impl Retriever for KnowledgeBaseClient {
+    fn retrieve_documents<'a>(
+        &'a self,
+        query: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<RetrievedDocument>>> {
+        Box::pin(async move {
+            let dataset_id = self.dataset_id().map(str::to_string);
+            let records = self.retrieve(query, None).await?;
+            Ok(records
+                .into_iter()
+                .map(|record| {
+                    let mut document = RetrievedDocument::from(record);
+                    if let Some(dataset_id) = &dataset_id {
+                        document
+                            .metadata
+                            .insert("dataset_id".into(), Value::String(dataset_id.clone()));
+                    }
+                    document
+                })
+                .collect())
+        })
+    }
+}
+
+impl<R: Retriever + ?Sized> Retriever for Arc<R> {
+    fn retrieve_documents<'a>(
+        &'a self,
+        query: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<RetrievedDocument>>> {
+        (**self).retrieve_documents(query)
+    }
+}
@@ -0,0 +1,243 @@
+//! Imports Q&A segments from spreadsheets, e.g. an FAQ export, with one row
+//! per segment and `content` (or `question`), `answer` and `keywords`
+//! columns, and exports the segments of a document to JSONL.
+
+#[cfg(feature = "fs")]
+use anyhow::Context;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+use crate::knowledge::models::{NewSegment, Segment};
+#[cfg(feature = "fs")]
+use crate::KnowledgeBaseClient;
+
+/// Segments are added in requests of at most this many segments.
+#[cfg(feature = "fs")]
+const IMPORT_BATCH_SIZE: usize = 100;
+
+/// Segments are listed in pages of this many segments when exporting.
+#[cfg(feature = "fs")]
+const EXPORT_PAGE_SIZE: u32 = 100;
+
+/// One line of a segment export.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct ExportedSegment {
+    pub id: String,
+    pub content: String,
+    pub answer: Option<String>,
+    pub keywords: Vec<String>,
+    /// Document id, position, counts and status of the segment.
+    pub metadata: Map<String, Value>,
+}
+
+impl From<&Segment> for ExportedSegment {
+    fn from(segment: &Segment) -> Self {
+        let metadata = json!({
+            "document_id": segment.document_id,
+            "position": segment.position,
+            "word_count": segment.word_count,
+            "tokens": segment.tokens,
+            "hit_count": segment.hit_count,
+            "enabled": segment.enabled,
+            "status": segment.status,
+        });
+        Self {
+            id: segment.id.to_string(),
+            content: segment.content.clone(),
+            answer: segment.answer.clone(),
+            keywords: segment.keywords.clone(),
+            metadata: match metadata {
+                Value::Object(metadata) => metadata,
+                _ => Map::new(),
+            },
+        }
+    }
+}
+
+/// Loads segments from a CSV or, with the `xlsx` feature, an XLSX file
+/// (first sheet), chosen by extension.
+#[cfg(feature = "fs")]
+pub fn load_segments(path: impl AsRef<Path>) -> Result<Vec<NewSegment>> {
+    let path = path.as_ref();
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+
+    let segments = match extension.as_str() {
+        "csv" => {
+            let content = std::fs::read_to_string(path)
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            segments_from_csv(&content)
+        }
+        #[cfg(feature = "xlsx")]
+        "xlsx" => segments_from_xlsx(path),
+        #[cfg(not(feature = "xlsx"))]
+        "xlsx" => bail!("loading XLSX segments requires the `xlsx` feature"),
+        _ => bail!("unsupported segments file extension: {}", path.display()),
+    };
+
+    segments.with_context(|| format!("failed to load segments from {}", path.display()))
+}
+
+pub fn segments_from_csv(content: &str) -> Result<Vec<NewSegment>> {
+    segments_from_rows(parse_csv(content)?)
+}
+
+#[cfg(feature = "xlsx")]
+pub fn segments_from_xlsx(path: impl AsRef<Path>) -> Result<Vec<NewSegment>> {
+    use calamine::Reader;
+
+    let mut workbook: calamine::Xlsx<_> = calamine::open_workbook(path.as_ref())?;
+    let Some(sheet) = workbook.sheet_names().first().cloned() else {
+        bail!("the workbook has no sheets");
+    };
+    let range = workbook.worksheet_range(&sheet)?;
+    segments_from_rows(
+        range
+            .rows()
+            .map(|row| row.iter().map(|cell| cell.to_string()).collect()),
+    )
+}
+
+/// Turns rows, the first of which names the columns, into segments. Rows
+/// without content are skipped. Keywords are separated by `,` or `;`.
+fn segments_from_rows(rows: impl IntoIterator<Item = Vec<String>>) -> Result<Vec<NewSegment>> {
+    let mut rows = rows.into_iter();
+    let Some(header) = rows.next() else {
+        return Ok(Vec::new());
+    };
+    let column = |names: &[&str]| {
+        header
+            .iter()
+            .position(|column| names.contains(&column.trim().to_ascii_lowercase().as_str()))
+    };
+    let Some(content_column) = column(&["content", "question"]) else {
+        bail!("missing a `content` or `question` column");
+    };
+    let answer_column = column(&["answer"]);
+    let keywords_column = column(&["keywords"]);
+
+    let cell = |row: &[String], index: Option<usize>| {
+        index
+            .and_then(|index| row.get(index))
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    Ok(rows
+        .filter_map(|row| {
+            let content = cell(&row, Some(content_column))?;
+            let keywords = cell(&row, keywords_column)
+                .map(|keywords| {
+                    keywords
+                        .split([',', ';'])
+                        .map(str::trim)
+                        .filter(|keyword| !keyword.is_empty())
+                        .map(String::from)
+                        .collect()
+                })
+                .unwrap_or_default();
+            Some(NewSegment {
+                content,
+                answer: cell(&row, answer_column),
+                keywords,
+            })
+        })
+        .collect())
+}
+
+/// Parses RFC 4180 CSV: fields may be quoted with `"`, quoted fields may
+/// contain separators, line breaks and `""` escaped quotes.
+fn parse_csv(content: &str) -> Result<Vec<Vec<String>>> {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted => {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    field.push('"');
+                } else {
+                    quoted = false;
+                }
+            }
+            '"' if field.is_empty() => quoted = true,
+            ',' if !quoted => row.push(std::mem::take(&mut field)),
+            '\r' if !quoted && chars.peek() == Some(&'\n') => {}
+            '\n' if !quoted => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            c => field.push(c),
+        }
+    }
+    if quoted {
+        bail!("unterminated quoted field");
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+#[cfg(feature = "fs")]
+impl KnowledgeBaseClient {
+    /// Adds the segments of a file read with [`load_segments`] to a document,
+    /// in batches. The file is read on the blocking thread pool.
+    pub async fn import_segments(
+        &self,
+        document_id: &str,
+        path: impl AsRef<Path>,
+    ) -> Result<Vec<Segment>> {
+        let path = path.as_ref().to_path_buf();
+        let segments = tokio::task::spawn_blocking(move || load_segments(path)).await??;
+        let _guard = self.lock_dataset().await?;
+        let mut added = Vec::with_capacity(segments.len());
+        for batch in segments.chunks(IMPORT_BATCH_SIZE) {
+            added.extend(self.add_segments(document_id, batch).await?);
+        }
+        Ok(added)
+    }
+
+    /// Writes every segment of a document to `path` as JSONL, one
+    /// [`ExportedSegment`] per line, and returns the number of segments.
+    pub async fn export_segments(
+        &self,
+        document_id: &str,
+        path: impl AsRef<Path>,
+    ) -> Result<usize> {
+        use futures_util::TryStreamExt;
+        use tokio::io::{AsyncWriteExt, BufWriter};
+
+        let path = path.as_ref();
+        let file = tokio::fs::File::create(path)
+            .await
+            .with_context(|| format!("failed to create {}", path.display()))?;
+        let mut writer = BufWriter::new(file);
+
+        let mut segments = self.paginate_segments(document_id, None, None, EXPORT_PAGE_SIZE);
+        let mut exported = 0;
+        while let Some(segment) = segments.try_next().await? {
+            let mut line = serde_json::to_vec(&ExportedSegment::from(&segment))?;
+            line.push(b'\n');
+            writer.write_all(&line).await?;
+            exported += 1;
+        }
+        writer
+            .flush()
+            .await
+            .with_context(|| format!("failed to write {}", path.display()))?;
+
+        Ok(exported)
+    }
+}
@@ -0,0 +1,162 @@
+//! A duplex chat session: queries are sent through its [`Sink`] side and the
+//! answer events come out of its [`Stream`] side.
+
+use anyhow::{bail, Result};
+use futures_util::{Sink, Stream, StreamExt};
+use serde_json::{Map, Value};
+use std::{
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll, Waker},
+};
+
+use crate::{compat::BoxFuture, ChatClient, ChatStream, ChatStreamEvent};
+
+/// Sends each query as a streaming chat message and yields its events,
+/// carrying the conversation id between turns like
+/// [`Conversation`](crate::Conversation).
+///
+/// Only one answer is in flight at a time: the sink is not ready for the next
+/// query until the events of the previous one have been read. The request is
+/// sent when the stream side is polled. Closing the sink ends the stream once
+/// the last answer is done.
+pub struct ChatSession {
+    client: Arc<ChatClient>,
+    user: String,
+    inputs: Value,
+    conversation_id: Option<String>,
+    state: SessionState,
+    closed: bool,
+    ready_waker: Option<Waker>,
+    events_waker: Option<Waker>,
+}
+
+enum SessionState {
+    Idle,
+    Connecting(BoxFuture<'static, Result<ChatStream>>),
+    Streaming(Box<ChatStream>),
+}
+
+impl ChatSession {
+    pub fn new(client: Arc<ChatClient>, user: &str, conversation_id: Option<&str>) -> Self {
+        Self {
+            client,
+            user: user.to_string(),
+            inputs: Value::Object(Map::new()),
+            conversation_id: conversation_id.map(String::from),
+            state: SessionState::Idle,
+            closed: false,
+            ready_waker: None,
+            events_waker: None,
+        }
+    }
+
+    /// Sets the app inputs sent with every query.
+    pub fn with_inputs(mut self, inputs: Value) -> Self {
+        self.inputs = inputs;
+        self
+    }
+
+    pub fn conversation_id(&self) -> Option<&str> {
+        self.conversation_id.as_deref()
+    }
+
+    fn finish_answer(&mut self) {
+        self.state = SessionState::Idle;
+        if let Some(waker) = self.ready_waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl Sink<String> for ChatSession {
+    type Error = anyhow::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = self.get_mut();
+        if matches!(this.state, SessionState::Idle) {
+            return Poll::Ready(Ok(()));
+        }
+        this.ready_waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+
+    fn start_send(self: Pin<&mut Self>, query: String) -> Result<()> {
+        let this = self.get_mut();
+        if this.closed {
+            bail!("chat session is closed");
+        }
+        if !matches!(this.state, SessionState::Idle) {
+            bail!("the previous answer of the chat session is still streaming");
+        }
+
+        let client = this.client.clone();
+        let user = this.user.clone();
+        let inputs = this.inputs.clone();
+        let conversation_id = this.conversation_id.clone();
+        this.state = SessionState::Connecting(Box::pin(async move {
+            client
+                .create_chat_message_stream(inputs, &query, &user, conversation_id.as_deref(), None)
+                .await
+        }));
+        if let Some(waker) = this.events_waker.take() {
+            waker.wake();
+        }
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = self.get_mut();
+        this.closed = true;
+        if let Some(waker) = this.events_waker.take() {
+            waker.wake();
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl Stream for ChatSession {
+    type Item = Result<ChatStreamEvent>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            match &mut this.state {
+                SessionState::Idle => {
+                    if this.closed {
+                        return Poll::Ready(None);
+                    }
+                    this.events_waker = Some(cx.waker().clone());
+                    return Poll::Pending;
+                }
+                SessionState::Connecting(request) => match request.as_mut().poll(cx) {
+                    Poll::Ready(Ok(stream)) => {
+                        this.state = SessionState::Streaming(Box::new(stream))
+                    }
+                    Poll::Ready(Err(err)) => {
+                        this.finish_answer();
+                        return Poll::Ready(Some(Err(err)));
+                    }
+                    Poll::Pending => return Poll::Pending,
+                },
+                SessionState::Streaming(stream) => match stream.poll_next_unpin(cx) {
+                    Poll::Ready(Some(Ok(event))) => {
+                        if let ChatStreamEvent::MessageEnd(end) = &event {
+                            if !end.conversation_id.is_empty() {
+                                this.conversation_id = Some(end.conversation_id.to_string());
+                            }
+                        }
+                        return Poll::Ready(Some(Ok(event)));
+                    }
+                    Poll::Ready(Some(Err(err))) => return Poll::Ready(Some(Err(err))),
+                    Poll::Ready(None) => this.finish_answer(),
+                    Poll::Pending => return Poll::Pending,
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,108 @@
+//! Extraction of zip archives, e.g. exported data, with limits on the entry
+//! count and the decompressed sizes so a malicious archive cannot exhaust
+//! memory or disk.
+
+use anyhow::{bail, Context, Result};
+use std::{
+    fs,
+    io::{Cursor, Read},
+    path::{Path, PathBuf},
+};
+
+/// Limits checked against the actual decompressed data, not the sizes the
+/// archive claims.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractLimits {
+    pub max_entries: usize,
+    pub max_entry_size: u64,
+    pub max_total_size: u64,
+}
+
+impl Default for ExtractLimits {
+    fn default() -> Self {
+        Self {
+            max_entries: 1_000,
+            max_entry_size: 100 * 1024 * 1024,
+            max_total_size: 1024 * 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractedEntry {
+    /// The entry path inside the archive, always relative.
+    pub path: PathBuf,
+    pub contents: Vec<u8>,
+}
+
+/// Whether `bytes` start like a zip archive.
+pub fn is_zip(bytes: &[u8]) -> bool {
+    bytes.starts_with(b"PK\x03\x04") || bytes.starts_with(b"PK\x05\x06")
+}
+
+/// Extracts the files of a zip archive into memory, skipping directories.
+pub fn extract_zip(bytes: &[u8], limits: &ExtractLimits) -> Result<Vec<ExtractedEntry>> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).context("invalid zip archive")?;
+    if archive.len() > limits.max_entries {
+        bail!(
+            "zip archive has {} entries, more than the limit of {}",
+            archive.len(),
+            limits.max_entries
+        );
+    }
+
+    let mut entries = Vec::new();
+    let mut total = 0u64;
+    for index in 0..archive.len() {
+        let entry = archive.by_index(index)?;
+        if entry.is_dir() {
+            continue;
+        }
+        let Some(path) = entry.enclosed_name() else {
+            bail!("zip entry {:?} escapes the archive", entry.name());
+        };
+
+        let mut contents = Vec::new();
+        let read = entry
+            .take(limits.max_entry_size + 1)
+            .read_to_end(&mut contents)? as u64;
+        if read > limits.max_entry_size {
+            bail!(
+                "zip entry {} exceeds the limit of {} bytes",
+                path.display(),
+                limits.max_entry_size
+            );
+        }
+        total += read;
+        if total > limits.max_total_size {
+            bail!(
+                "zip archive exceeds the limit of {} bytes in total",
+                limits.max_total_size
+            );
+        }
+        entries.push(ExtractedEntry { path, contents });
+    }
+    Ok(entries)
+}
+
+/// Extracts a zip archive below `dir` and returns the written paths. Nothing
+/// is written unless the whole archive is within `limits`.
+pub fn extract_zip_to(
+    bytes: &[u8],
+    dir: impl AsRef<Path>,
+    limits: &ExtractLimits,
+) -> Result<Vec<PathBuf>> {
+    let dir = dir.as_ref();
+    extract_zip(bytes, limits)?
+        .into_iter()
+        .map(|entry| {
+            let path = dir.join(&entry.path);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&path, entry.contents)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+            Ok(path)
+        })
+        .collect()
+}
@@ -0,0 +1,59 @@
+use anyhow::Result;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use crate::{compat::Instant, knowledge::models::UploadFile, KnowledgeBaseClient};
+
+/// Caches the source file metadata of documents for `ttl`, so rendering
+/// document lists that reference the same files does not repeat the lookup.
+pub struct UploadFileCache {
+    client: Arc<KnowledgeBaseClient>,
+    ttl: Duration,
+    entries: Mutex<HashMap<String, (Instant, Arc<UploadFile>)>>,
+}
+
+impl UploadFileCache {
+    pub fn new(client: Arc<KnowledgeBaseClient>, ttl: Duration) -> Self {
+        Self {
+            client,
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the cached metadata of the document's source file, fetching it
+    /// when missing or older than the ttl.
+    pub async fn get(&self, document_id: &str) -> Result<Arc<UploadFile>> {
+        if let Some(file) = self.cached(document_id) {
+            return Ok(file);
+        }
+
+        let file = Arc::new(self.client.get_document_upload_file(document_id).await?);
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(document_id.to_string(), (Instant::now(), file.clone()));
+        Ok(file)
+    }
+
+    pub fn invalidate(&self, document_id: &str) {
+        self.entries.lock().unwrap().remove(document_id);
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    fn cached(&self, document_id: &str) -> Option<Arc<UploadFile>> {
+        let mut entries = self.entries.lock().unwrap();
+        let (fetched_at, file) = entries.get(document_id)?;
+        if fetched_at.elapsed() <= self.ttl {
+            return Some(file.clone());
+        }
+        entries.remove(document_id);
+        None
+    }
+}
@@ -0,0 +1,109 @@
+//! Deterministic sample values for the response models, built from golden
+//! payloads captured from the Dify API. Enabled by the `test-fixtures` feature.
+
+use bytes::Bytes;
+
+use crate::api::{
+    knowledge::models::{Dataset, DocumentResponse, RetrieveResponse},
+    models::{
+        AppInfo, AppMeta, AppParameters, AppSite, AudioToTextResponse, ChatMessageResponse, Page,
+        SuggestedQuestionsResponse, WorkflowRun,
+    },
+    stream::{ChatStream, ChatStreamEvent, MessageEndEvent, MessageEvent},
+};
+
+pub mod golden {
+    pub const CHAT_MESSAGE_RESPONSE: &str = include_str!("../fixtures/chat_message_response.json");
+    pub const SUGGESTED_QUESTIONS_RESPONSE: &str =
+        include_str!("../fixtures/suggested_questions_response.json");
+    pub const AUDIO_TO_TEXT_RESPONSE: &str =
+        include_str!("../fixtures/audio_to_text_response.json");
+    pub const APP_INFO: &str = include_str!("../fixtures/app_info.json");
+    pub const APP_META: &str = include_str!("../fixtures/app_meta.json");
+    pub const APP_SITE: &str = include_str!("../fixtures/app_site.json");
+    pub const APP_PARAMETERS: &str = include_str!("../fixtures/app_parameters.json");
+    pub const CHAT_STREAM: &str = include_str!("../fixtures/chat_stream.txt");
+    pub const DATASET_PAGE: &str = include_str!("../fixtures/dataset_page.json");
+    pub const DOCUMENT_RESPONSE: &str = include_str!("../fixtures/document_response.json");
+    pub const RETRIEVE_RESPONSE: &str = include_str!("../fixtures/retrieve_response.json");
+    pub const WORKFLOW_RUN: &str = include_str!("../fixtures/workflow_run.json");
+}
+
+/// A realistic, deterministic value of a model, for use in unit tests.
+///
+/// Adjust individual fields with struct update syntax:
+/// `AppInfo { name: "other".into(), ..AppInfo::fixture() }`.
+pub trait Fixture: Sized {
+    fn fixture() -> Self;
+}
+
+macro_rules! golden_fixture {
+    ($model:ty, $golden:expr) => {
+        impl Fixture for $model {
+            fn fixture() -> Self {
+                serde_json::from_str($golden)
+                    .expect(concat!("invalid golden fixture for ", stringify!($model)))
+            }
+        }
+    };
+}
+
+golden_fixture!(ChatMessageResponse, golden::CHAT_MESSAGE_RESPONSE);
+golden_fixture!(
+    SuggestedQuestionsResponse,
+    golden::SUGGESTED_QUESTIONS_RESPONSE
+);
+golden_fixture!(AudioToTextResponse, golden::AUDIO_TO_TEXT_RESPONSE);
+golden_fixture!(AppInfo, golden::APP_INFO);
+golden_fixture!(AppMeta, golden::APP_META);
+golden_fixture!(AppSite, golden::APP_SITE);
+golden_fixture!(AppParameters, golden::APP_PARAMETERS);
+golden_fixture!(Page<Dataset>, golden::DATASET_PAGE);
+golden_fixture!(DocumentResponse, golden::DOCUMENT_RESPONSE);
+golden_fixture!(RetrieveResponse, golden::RETRIEVE_RESPONSE);
+golden_fixture!(WorkflowRun, golden::WORKFLOW_RUN);
+
+impl Fixture for Dataset {
+    fn fixture() -> Self {
+        Page::<Dataset>::fixture().data.remove(0)
+    }
+}
+
+impl Fixture for MessageEvent {
+    fn fixture() -> Self {
+        chat_stream_events()
+            .into_iter()
+            .find_map(|event| match event {
+                ChatStreamEvent::Message(event) => Some(event),
+                _ => None,
+            })
+            .expect("golden chat stream has a message event")
+    }
+}
+
+impl Fixture for MessageEndEvent {
+    fn fixture() -> Self {
+        chat_stream_events()
+            .into_iter()
+            .find_map(|event| match event {
+                ChatStreamEvent::MessageEnd(event) => Some(event),
+                _ => None,
+            })
+            .expect("golden chat stream has a message_end event")
+    }
+}
+
+/// The events of the golden chat stream, in order.
+pub fn chat_stream_events() -> Vec<ChatStreamEvent> {
+    golden::CHAT_STREAM
+        .lines()
+        .filter_map(|line| line.strip_prefix("data:"))
+        .map(|data| serde_json::from_str(data.trim()).expect("invalid golden chat stream event"))
+        .collect()
+}
+
+/// A [`ChatStream`] replaying the golden chat stream.
+pub fn chat_stream() -> ChatStream {
+    let chunk: reqwest::Result<Bytes> = Ok(Bytes::from_static(golden::CHAT_STREAM.as_bytes()));
+    ChatStream::from_bytes_stream(futures_util::stream::iter([chunk]))
+}
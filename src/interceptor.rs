@@ -0,0 +1,86 @@
+//! Request and response hooks run by the transport for every endpoint,
+//! including streaming and multipart requests.
+
+use reqwest::{Request, Response};
+use std::{sync::Arc, time::Duration};
+
+use crate::events::ClientEvent;
+
+/// Hooks around every HTTP request sent by the client.
+///
+/// Install interceptors with
+/// [`DifyClient::with_interceptor`](crate::DifyClient::with_interceptor); they
+/// run in installation order. `on_request` runs once per call, before the
+/// first attempt, so header changes carry over to retries. `on_response` and
+/// `on_error` see the final outcome; for streaming calls `elapsed` is the time
+/// until the response headers arrived.
+pub trait Interceptor: Send + Sync {
+    fn on_request(&self, _request: &mut Request) {}
+
+    fn on_response(&self, _request: &RequestInfo, _response: &Response, _elapsed: Duration) {}
+
+    fn on_error(&self, _request: &RequestInfo, _error: &anyhow::Error, _elapsed: Duration) {}
+
+    /// Receives the same events as an [`EventListener`](crate::EventListener).
+    fn on_event(&self, _event: &ClientEvent) {}
+}
+
+impl<I: Interceptor + ?Sized> Interceptor for Arc<I> {
+    fn on_request(&self, request: &mut Request) {
+        (**self).on_request(request)
+    }
+
+    fn on_response(&self, request: &RequestInfo, response: &Response, elapsed: Duration) {
+        (**self).on_response(request, response, elapsed)
+    }
+
+    fn on_error(&self, request: &RequestInfo, error: &anyhow::Error, elapsed: Duration) {
+        (**self).on_error(request, error, elapsed)
+    }
+
+    fn on_event(&self, event: &ClientEvent) {
+        (**self).on_event(event)
+    }
+}
+
+/// The method and url of an intercepted request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestInfo {
+    pub method: reqwest::Method,
+    pub url: String,
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct Interceptors(Vec<Arc<dyn Interceptor>>);
+
+impl Interceptors {
+    pub(crate) fn push(&mut self, interceptor: Arc<dyn Interceptor>) {
+        self.0.push(interceptor);
+    }
+
+    pub(crate) fn request(&self, request: &mut Request) {
+        for interceptor in &self.0 {
+            interceptor.on_request(request);
+        }
+    }
+
+    pub(crate) fn result(
+        &self,
+        request: &RequestInfo,
+        result: &anyhow::Result<Response>,
+        elapsed: Duration,
+    ) {
+        for interceptor in &self.0 {
+            match result {
+                Ok(response) => interceptor.on_response(request, response, elapsed),
+                Err(err) => interceptor.on_error(request, err, elapsed),
+            }
+        }
+    }
+
+    pub(crate) fn event(&self, event: &ClientEvent) {
+        for interceptor in &self.0 {
+            interceptor.on_event(event);
+        }
+    }
+}
@@ -0,0 +1,60 @@
+//! Client-side input moderation, applied before queries leave the process.
+
+use std::{borrow::Cow, fmt, sync::Arc};
+
+/// The outcome of moderating one query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Moderation {
+    /// Send the query unchanged.
+    Allow,
+    /// Do not send the query; the call fails with [`InputRejected`].
+    Deny { reason: String },
+    /// Send this text instead of the query, e.g. with personal data masked.
+    Transform(String),
+}
+
+/// A filtering policy run on every query before it is sent.
+///
+/// Install one with [`DifyClient::with_moderator`](crate::DifyClient::with_moderator);
+/// every client built from that `DifyClient` applies it to chat queries and to
+/// the `query` input of completion messages.
+pub trait InputModerator: Send + Sync {
+    fn moderate(&self, query: &str) -> Moderation;
+}
+
+impl<F> InputModerator for F
+where
+    F: Fn(&str) -> Moderation + Send + Sync,
+{
+    fn moderate(&self, query: &str) -> Moderation {
+        self(query)
+    }
+}
+
+/// Returned when an [`InputModerator`] denies a query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputRejected {
+    pub reason: String,
+}
+
+impl fmt::Display for InputRejected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "query rejected by input moderation: {}", self.reason)
+    }
+}
+
+impl std::error::Error for InputRejected {}
+
+pub(crate) fn apply<'a>(
+    moderator: Option<&Arc<dyn InputModerator>>,
+    query: &'a str,
+) -> Result<Cow<'a, str>, InputRejected> {
+    let Some(moderator) = moderator else {
+        return Ok(Cow::Borrowed(query));
+    };
+    match moderator.moderate(query) {
+        Moderation::Allow => Ok(Cow::Borrowed(query)),
+        Moderation::Deny { reason } => Err(InputRejected { reason }),
+        Moderation::Transform(query) => Ok(Cow::Owned(query)),
+    }
+}
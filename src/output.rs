@@ -0,0 +1,92 @@
+//! Post-processing of generated answers, e.g. profanity masking, link
+//! rewriting or markdown sanitization.
+
+use std::sync::Arc;
+
+use crate::api::stream::{ChatStreamEvent, CompletionStreamEvent};
+
+/// A transformation applied to every answer received through the client.
+///
+/// Install processors with
+/// [`DifyClient::with_output_processor`](crate::DifyClient::with_output_processor);
+/// they run in installation order on complete answers and on
+/// `message_replace` stream events. Streamed `message` and `agent_message`
+/// deltas are passed through [`process_delta`](Self::process_delta), which
+/// leaves them unchanged unless overridden, because a delta may end in the
+/// middle of a word.
+pub trait OutputProcessor: Send + Sync {
+    fn process_answer(&self, answer: &str) -> String;
+
+    fn process_delta(&self, delta: &str) -> String {
+        delta.to_string()
+    }
+}
+
+impl<F> OutputProcessor for F
+where
+    F: Fn(&str) -> String + Send + Sync,
+{
+    fn process_answer(&self, answer: &str) -> String {
+        self(answer)
+    }
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct OutputProcessors(Vec<Arc<dyn OutputProcessor>>);
+
+impl OutputProcessors {
+    pub(crate) fn push(&mut self, processor: Arc<dyn OutputProcessor>) {
+        self.0.push(processor);
+    }
+
+    #[cfg_attr(not(any(feature = "chat", feature = "completion")), allow(dead_code))]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub(crate) fn answer(&self, answer: String) -> String {
+        self.0.iter().fold(answer, |answer, processor| {
+            processor.process_answer(&answer)
+        })
+    }
+
+    pub(crate) fn delta(&self, delta: String) -> String {
+        self.0
+            .iter()
+            .fold(delta, |delta, processor| processor.process_delta(&delta))
+    }
+
+    #[cfg_attr(not(feature = "chat"), allow(dead_code))]
+    pub(crate) fn chat_event(&self, event: ChatStreamEvent) -> ChatStreamEvent {
+        match event {
+            ChatStreamEvent::Message(mut event) => {
+                event.answer = self.delta(event.answer);
+                ChatStreamEvent::Message(event)
+            }
+            ChatStreamEvent::AgentMessage(mut event) => {
+                event.answer = self.delta(event.answer);
+                ChatStreamEvent::AgentMessage(event)
+            }
+            ChatStreamEvent::MessageReplace(mut event) => {
+                event.answer = self.answer(event.answer);
+                ChatStreamEvent::MessageReplace(event)
+            }
+            event => event,
+        }
+    }
+
+    #[cfg_attr(not(feature = "completion"), allow(dead_code))]
+    pub(crate) fn completion_event(&self, event: CompletionStreamEvent) -> CompletionStreamEvent {
+        match event {
+            CompletionStreamEvent::Message(mut event) => {
+                event.answer = self.delta(event.answer);
+                CompletionStreamEvent::Message(event)
+            }
+            CompletionStreamEvent::MessageReplace(mut event) => {
+                event.answer = self.answer(event.answer);
+                CompletionStreamEvent::MessageReplace(event)
+            }
+            event => event,
+        }
+    }
+}
@@ -0,0 +1,734 @@
+//! Raw request access: the authenticated transport every typed endpoint is
+//! built on, usable directly for endpoints this crate does not wrap yet.
+
+use anyhow::{bail, Result};
+use bytes::Bytes;
+use futures_util::{
+    future::{self, Either},
+    Stream, StreamExt,
+};
+use reqwest::{header, Client, Response};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::{borrow::Cow, sync::Arc, time::Duration};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::{
+    api::stream::EventStream,
+    compat::{self, Instant},
+    config::{self, DifyConfig, MultipartConfig, RetryConfig, VersionConfig},
+    error::{self, DifyError},
+    events::{ClientEvent, EventListener, EventListeners, RetryReason},
+    interceptor::{Interceptor, Interceptors, RequestInfo},
+    moderation::{self, InputModerator},
+    output::{OutputProcessor, OutputProcessors},
+};
+
+#[cfg(feature = "knowledge")]
+mod dataset_lock;
+mod files;
+mod rate_limit;
+mod stats;
+mod timeout;
+mod trace;
+mod transport;
+
+#[cfg(feature = "knowledge")]
+pub use dataset_lock::DatasetWriteGuard;
+pub use files::FileSource;
+pub use timeout::with_timeout;
+pub use trace::{current_trace_id, with_trace_id};
+pub use transport::HttpTransport;
+
+pub use rate_limit::{with_rate_limit_info, RateLimitInfo};
+pub use stats::{ClientStats, EndpointStats};
+
+#[cfg(feature = "knowledge")]
+use dataset_lock::DatasetLocks;
+use rate_limit::RateLimiter;
+use stats::StatsRecorder;
+
+#[derive(Clone)]
+pub struct DifyClient {
+    api_key: String,
+    base_url: String,
+    client: Client,
+    transport: Arc<dyn HttpTransport>,
+    retry: RetryConfig,
+    timeouts: Timeouts,
+    concurrency: Option<Arc<Semaphore>>,
+    rate_limit: Option<Arc<RateLimiter>>,
+    #[cfg(feature = "knowledge")]
+    dataset_locks: Option<Arc<DatasetLocks>>,
+    stats: Arc<StatsRecorder>,
+    moderator: Option<Arc<dyn InputModerator>>,
+    output: OutputProcessors,
+    version: VersionConfig,
+    multipart: MultipartConfig,
+    events: EventListeners,
+    interceptors: Interceptors,
+}
+
+/// Builds a [`DifyClient`] from the same settings as [`DifyConfig`], validated
+/// by [`build`](Self::build).
+#[derive(Clone)]
+pub struct DifyClientBuilder {
+    config: DifyConfig,
+    http_client: Option<Client>,
+    transport: Option<Arc<dyn HttpTransport>>,
+}
+
+impl std::fmt::Debug for DifyClientBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DifyClientBuilder")
+            .field("config", &self.config)
+            .field("http_client", &self.http_client)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DifyClientBuilder {
+    pub fn base_url(mut self, base_url: &str) -> Self {
+        self.config.base_url = Some(base_url.to_string());
+        self
+    }
+
+    pub fn timeout_secs(mut self, timeout_secs: u64) -> Self {
+        self.config.timeout_secs = Some(timeout_secs);
+        self
+    }
+
+    pub fn connect_timeout_secs(mut self, connect_timeout_secs: u64) -> Self {
+        self.config.connect_timeout_secs = Some(connect_timeout_secs);
+        self
+    }
+
+    pub fn read_timeout_secs(mut self, read_timeout_secs: u64) -> Self {
+        self.config.read_timeout_secs = Some(read_timeout_secs);
+        self
+    }
+
+    pub fn stream_timeout_secs(mut self, stream_timeout_secs: u64) -> Self {
+        self.config.stream_timeout_secs = Some(stream_timeout_secs);
+        self
+    }
+
+    pub fn first_byte_timeout_secs(mut self, first_byte_timeout_secs: u64) -> Self {
+        self.config.first_byte_timeout_secs = Some(first_byte_timeout_secs);
+        self
+    }
+
+    pub fn retry(mut self, retry: RetryConfig) -> Self {
+        self.config.retry = retry;
+        self
+    }
+
+    /// See [`LimitsConfig::max_concurrent`](crate::LimitsConfig::max_concurrent).
+    pub fn max_concurrent(mut self, max_concurrent: usize) -> Self {
+        self.config.limits.max_concurrent = Some(max_concurrent);
+        self
+    }
+
+    pub fn max_requests_per_minute(mut self, max_requests_per_minute: u32) -> Self {
+        self.config.limits.max_requests_per_minute = Some(max_requests_per_minute);
+        self
+    }
+
+    /// Serializes the write helpers of the knowledge client per dataset, see
+    /// [`LimitsConfig::dataset_write_wait_secs`](crate::LimitsConfig::dataset_write_wait_secs).
+    pub fn dataset_write_wait_secs(mut self, wait_secs: u64) -> Self {
+        self.config.limits.dataset_write_wait_secs = Some(wait_secs);
+        self
+    }
+
+    pub fn expected_server_version(mut self, expected: &str, strict: bool) -> Self {
+        self.config.version = VersionConfig {
+            expected: Some(expected.to_string()),
+            strict,
+        };
+        self
+    }
+
+    /// Sends the multipart part `name` (`file` or `data`) as `renamed`.
+    pub fn multipart_field_name(mut self, name: &str, renamed: &str) -> Self {
+        self.config
+            .multipart
+            .field_names
+            .insert(name.to_string(), renamed.to_string());
+        self
+    }
+
+    /// Adds a text field to every multipart upload.
+    pub fn multipart_field(mut self, name: &str, value: &str) -> Self {
+        self.config
+            .multipart
+            .extra_fields
+            .insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// Routes requests through the proxy at `url`, see [`ProxyConfig`](crate::ProxyConfig).
+    pub fn proxy(mut self, url: &str) -> Self {
+        self.config.proxy.url = Some(url.to_string());
+        self
+    }
+
+    pub fn proxy_auth(mut self, username: &str, password: &str) -> Self {
+        self.config.proxy.username = Some(username.to_string());
+        self.config.proxy.password = Some(password.to_string());
+        self
+    }
+
+    /// Reaches `hosts` without the proxy.
+    pub fn no_proxy<I, S>(mut self, hosts: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config
+            .proxy
+            .no_proxy
+            .extend(hosts.into_iter().map(Into::into));
+        self
+    }
+
+    /// Sends requests through `client` instead of building one. The connect
+    /// timeout and proxy of the builder are not applied to it.
+    pub fn http_client(mut self, client: Client) -> Self {
+        self.http_client = Some(client);
+        self
+    }
+
+    /// Sends requests through `transport`, e.g. a
+    /// `reqwest_middleware::ClientWithMiddleware`. Timeouts are still set on
+    /// each request; retries of the builder run on top of the transport.
+    pub fn transport(mut self, transport: impl HttpTransport + 'static) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    pub fn build(self) -> Result<DifyClient> {
+        let client = DifyClient::from_config_with(&self.config, self.http_client)?;
+        Ok(match self.transport {
+            Some(transport) => DifyClient {
+                transport,
+                ..client
+            },
+            None => client,
+        })
+    }
+}
+
+impl DifyClient {
+    pub fn new(api_key: &str, base_url: Option<&str>) -> Self {
+        Self::with_http_client(api_key, base_url, Client::new())
+    }
+
+    /// Like [`new`](Self::new), but shares an existing `reqwest::Client` and
+    /// with it its connection pool and settings.
+    pub fn with_http_client(api_key: &str, base_url: Option<&str>, client: Client) -> Self {
+        Self {
+            api_key: api_key.to_string(),
+            base_url: base_url.unwrap_or(config::DEFAULT_BASE_URL).to_string(),
+            transport: Arc::new(client.clone()),
+            client,
+            retry: RetryConfig::default(),
+            timeouts: Timeouts::default(),
+            concurrency: None,
+            rate_limit: None,
+            #[cfg(feature = "knowledge")]
+            dataset_locks: None,
+            stats: Arc::default(),
+            moderator: None,
+            output: OutputProcessors::default(),
+            version: VersionConfig::default(),
+            multipart: MultipartConfig::default(),
+            events: EventListeners::default(),
+            interceptors: Interceptors::default(),
+        }
+    }
+
+    pub fn builder(api_key: &str) -> DifyClientBuilder {
+        DifyClientBuilder {
+            config: DifyConfig::new(api_key),
+            http_client: None,
+            transport: None,
+        }
+    }
+
+    /// Fails with a [`ConfigError`](crate::error::ConfigError) listing every
+    /// problem of an invalid `config`.
+    pub fn from_config(config: &DifyConfig) -> Result<Self> {
+        Self::from_config_with(config, None)
+    }
+
+    fn from_config_with(config: &DifyConfig, http_client: Option<Client>) -> Result<Self> {
+        config.validate()?;
+
+        let client = match http_client {
+            Some(client) => client,
+            None => {
+                #[allow(unused_mut)]
+                let mut builder = Client::builder();
+                // browsers and workers manage connections themselves
+                #[cfg(not(target_arch = "wasm32"))]
+                {
+                    if let Some(timeout) = config.connect_timeout_secs {
+                        builder = builder.connect_timeout(Duration::from_secs(timeout));
+                    }
+                    if let Some(proxy) = proxy(&config.proxy)? {
+                        builder = builder.proxy(proxy);
+                    }
+                }
+                builder.build()?
+            }
+        };
+
+        Ok(Self {
+            api_key: config.api_key.clone(),
+            base_url: config.base_url().to_string(),
+            transport: Arc::new(client.clone()),
+            client,
+            retry: config.retry.clone(),
+            timeouts: Timeouts {
+                total: config.timeout_secs.map(Duration::from_secs),
+                read: config.read_timeout_secs.map(Duration::from_secs),
+                first_byte: config.first_byte_timeout_secs.map(Duration::from_secs),
+                stream: config.stream_timeout_secs.map(Duration::from_secs),
+            },
+            concurrency: config
+                .limits
+                .max_concurrent
+                .map(|permits| Arc::new(Semaphore::new(permits))),
+            rate_limit: config
+                .limits
+                .max_requests_per_minute
+                .map(|per_minute| Arc::new(RateLimiter::per_minute(per_minute))),
+            #[cfg(feature = "knowledge")]
+            dataset_locks: config
+                .limits
+                .dataset_write_wait_secs
+                .map(|wait| Arc::new(DatasetLocks::new(Duration::from_secs(wait)))),
+            stats: Arc::default(),
+            moderator: None,
+            output: OutputProcessors::default(),
+            version: config.version.clone(),
+            multipart: config.multipart.clone(),
+            events: EventListeners::default(),
+            interceptors: Interceptors::default(),
+        })
+    }
+
+    /// Runs `moderator` on every query sent through this client and the
+    /// clients built from it.
+    pub fn with_moderator(mut self, moderator: impl InputModerator + 'static) -> Self {
+        self.moderator = Some(Arc::new(moderator));
+        self
+    }
+
+    /// Appends `processor` to the chain run on answers received through this
+    /// client and the clients built from it.
+    pub fn with_output_processor(mut self, processor: impl OutputProcessor + 'static) -> Self {
+        self.output.push(Arc::new(processor));
+        self
+    }
+
+    /// Adds a listener for the retry events of this client and the clients
+    /// built from it.
+    pub fn with_event_listener(mut self, listener: impl EventListener + 'static) -> Self {
+        self.events.push(Arc::new(listener));
+        self
+    }
+
+    /// Appends `interceptor` to the hooks run around every request of this
+    /// client and the clients built from it.
+    pub fn with_interceptor(mut self, interceptor: impl Interceptor + 'static) -> Self {
+        self.interceptors.push(Arc::new(interceptor));
+        self
+    }
+
+    /// Changes the part names and extra fields of multipart uploads.
+    pub fn with_multipart(mut self, multipart: MultipartConfig) -> Self {
+        self.multipart = multipart;
+        self
+    }
+
+    /// The underlying HTTP client, for requests to other hosts.
+    #[cfg(feature = "ingest")]
+    pub(crate) fn http_client(&self) -> &Client {
+        &self.client
+    }
+
+    pub(crate) fn version_config(&self) -> &VersionConfig {
+        &self.version
+    }
+
+    #[cfg_attr(
+        not(any(feature = "chat", feature = "completion", feature = "workflow")),
+        allow(dead_code)
+    )]
+    pub(crate) fn retry_config(&self) -> &RetryConfig {
+        &self.retry
+    }
+
+    #[cfg_attr(not(any(feature = "chat", feature = "completion")), allow(dead_code))]
+    pub(crate) fn output(&self) -> &OutputProcessors {
+        &self.output
+    }
+
+    /// Latency and error-rate averages per endpoint of this client and the
+    /// clients built from it.
+    pub fn stats(&self) -> ClientStats {
+        self.stats.snapshot()
+    }
+
+    /// Waits for the write lock of `dataset_id` when write locking is enabled.
+    #[cfg(feature = "knowledge")]
+    pub(crate) async fn lock_dataset(&self, dataset_id: &str) -> Result<DatasetWriteGuard> {
+        match &self.dataset_locks {
+            Some(locks) => Ok(locks.lock(dataset_id).await?),
+            None => Ok(DatasetWriteGuard::unlocked()),
+        }
+    }
+
+    /// Decodes a streaming response, failing when no data arrives within the
+    /// read timeout.
+    #[cfg_attr(
+        not(any(feature = "chat", feature = "completion", feature = "workflow")),
+        allow(dead_code)
+    )]
+    pub(crate) fn event_stream<T: DeserializeOwned>(&self, response: Response) -> EventStream<T> {
+        let stream = EventStream::new(response);
+        match self.timeouts().read {
+            Some(timeout) => stream.with_idle_timeout(timeout),
+            None => stream,
+        }
+    }
+
+    /// The client's timeouts, overridden by [`with_timeout`] in scope.
+    fn timeouts(&self) -> Timeouts {
+        match timeout::current_timeout() {
+            Some(timeout) => Timeouts {
+                total: Some(timeout),
+                read: Some(timeout),
+                first_byte: Some(timeout),
+                stream: self.timeouts.stream,
+            },
+            None => self.timeouts.clone(),
+        }
+    }
+
+    #[cfg_attr(not(any(feature = "chat", feature = "completion")), allow(dead_code))]
+    pub(crate) fn moderate<'a>(&self, query: &'a str) -> Result<Cow<'a, str>> {
+        Ok(moderation::apply(self.moderator.as_ref(), query)?)
+    }
+
+    /// Sends a JSON request to `endpoint` (relative to the base url) and returns
+    /// the response as is.
+    pub async fn request(
+        &self,
+        method: reqwest::Method,
+        endpoint: &str,
+        json: Option<Value>,
+        params: Option<Value>,
+    ) -> Result<Response> {
+        self.send_request(method, endpoint, json, params, false)
+            .await
+    }
+
+    /// Sends a multipart request with text `fields` and one `file` part and
+    /// returns the response as is.
+    pub async fn request_multipart(
+        &self,
+        method: reqwest::Method,
+        endpoint: &str,
+        fields: Vec<(&'static str, String)>,
+        file: FileSource,
+    ) -> Result<Response> {
+        self.send_request_with_files(method, endpoint, fields, file)
+            .await
+    }
+
+    async fn execute(&self, mut request: reqwest::Request) -> Result<Response> {
+        let permit = match &self.concurrency {
+            Some(semaphore) => Some(semaphore.clone().acquire_owned().await?),
+            None => None,
+        };
+
+        self.interceptors.request(&mut request);
+        let info = RequestInfo {
+            method: request.method().clone(),
+            url: request.url().to_string(),
+        };
+        let started = Instant::now();
+        let result = self.execute_with_retries(request, &info).await;
+        let result = result.map(|mut response| {
+            if let Some(permit) = permit {
+                ConcurrencyPermit(Arc::new(permit)).attach(&mut response);
+            }
+            response
+        });
+        if let Ok(response) = &result {
+            rate_limit::record(response);
+        }
+        let elapsed = started.elapsed();
+        self.interceptors.result(&info, &result, elapsed);
+        let path = info.url.strip_prefix(&self.base_url).unwrap_or(&info.url);
+        let path = path.split('?').next().unwrap_or_default();
+        self.stats
+            .record(stats::endpoint_key(&info.method, path), &result, elapsed);
+
+        result.map_err(transport_error)
+    }
+
+    async fn execute_with_retries(
+        &self,
+        request: reqwest::Request,
+        info: &RequestInfo,
+    ) -> Result<Response> {
+        let mut attempt = 0;
+        loop {
+            let retry_request = if attempt < self.retry.max_retries {
+                request.try_clone()
+            } else {
+                None
+            };
+            if let Some(rate_limit) = &self.rate_limit {
+                rate_limit.acquire().await;
+            }
+            let Some(next) = retry_request else {
+                let result = self.transport.execute(request).await;
+                if attempt > 0 {
+                    if let Some(reason) = retry_reason(&result) {
+                        self.emit(ClientEvent::RetriesExhausted {
+                            method: info.method.clone(),
+                            url: info.url.clone(),
+                            attempts: attempt + 1,
+                            reason,
+                        });
+                    }
+                }
+                return result;
+            };
+
+            let result = self.transport.execute(next).await;
+            let Some(reason) = retry_reason(&result) else {
+                return result;
+            };
+
+            let retry_after = result
+                .as_ref()
+                .ok()
+                .and_then(|response| error::retry_after(response.headers()));
+            let Some(backoff) = self.retry.delay(attempt, retry_after) else {
+                return result;
+            };
+            self.emit(ClientEvent::Retry {
+                method: info.method.clone(),
+                url: info.url.clone(),
+                attempt,
+                reason,
+                backoff,
+            });
+            compat::sleep(backoff).await;
+            attempt += 1;
+        }
+    }
+
+    fn emit(&self, event: ClientEvent) {
+        self.interceptors.event(&event);
+        self.events.emit(event);
+    }
+
+    pub(crate) async fn send_request(
+        &self,
+        method: reqwest::Method,
+        endpoint: &str,
+        json: Option<Value>,
+        params: Option<Value>,
+        stream: bool,
+    ) -> Result<Response> {
+        let mut headers = header::HeaderMap::new();
+        headers.insert(
+            "Content-Type",
+            header::HeaderValue::from_static("application/json"),
+        );
+        if let Some(trace_id) = current_trace_id() {
+            headers.insert(
+                trace::TRACE_ID_HEADER,
+                header::HeaderValue::from_str(&trace_id)?,
+            );
+        }
+
+        let url = format!("{}{}", self.base_url, endpoint);
+
+        tracing::debug!("request url: {}, method: {}", url, method);
+        tracing::debug!("request payload: {:?}", json);
+
+        let mut request = self
+            .client
+            .request(method, &url)
+            .headers(headers)
+            .bearer_auth(self.api_key.clone());
+
+        if let Some(json) = json {
+            request = request.json(&json);
+        }
+
+        tracing::debug!("{:?}", request);
+
+        if let Some(params) = params {
+            request = request.query(&params);
+        }
+
+        let timeouts = self.timeouts();
+        let timeout = if stream {
+            timeouts.stream
+        } else {
+            timeouts.total
+        };
+        if let Some(timeout) = timeout {
+            request = request.timeout(timeout);
+        }
+
+        let request = request.build()?;
+
+        let Some(timeout) = timeouts.first_byte.filter(|_| stream) else {
+            return self.execute(request).await;
+        };
+        match future::select(Box::pin(self.execute(request)), compat::sleep(timeout)).await {
+            Either::Left((result, _)) => result,
+            Either::Right(_) => bail!("no response received within {:?}", timeout),
+        }
+    }
+
+    pub(crate) async fn send_request_with_files(
+        &self,
+        method: reqwest::Method,
+        endpoint: &str,
+        fields: Vec<(&'static str, String)>,
+        file: FileSource,
+    ) -> Result<Response> {
+        let mut headers = header::HeaderMap::new();
+        headers.insert(
+            "Authorization",
+            header::HeaderValue::from_str(&format!("Bearer {}", self.api_key))?,
+        );
+        if let Some(trace_id) = current_trace_id() {
+            headers.insert(
+                trace::TRACE_ID_HEADER,
+                header::HeaderValue::from_str(&trace_id)?,
+            );
+        }
+
+        let url = format!("{}{}", self.base_url, endpoint);
+
+        let mut form = reqwest::multipart::Form::new();
+        for (name, value) in &self.multipart.extra_fields {
+            form = form.text(name.clone(), value.clone());
+        }
+        for (name, value) in fields {
+            form = form.text(self.multipart.field_name(name).to_string(), value);
+        }
+        let form = form.part(
+            self.multipart.field_name("file").to_string(),
+            file.into_part().await?,
+        );
+
+        let mut request = self
+            .client
+            .request(method, &url)
+            .headers(headers)
+            .multipart(form);
+        if let Some(timeout) = self.timeouts().total {
+            request = request.timeout(timeout);
+        }
+        let request = request.build()?;
+
+        self.execute(request).await
+    }
+}
+
+/// Request timeouts; the connect timeout is set on the `reqwest::Client`.
+#[derive(Debug, Clone, Default)]
+struct Timeouts {
+    total: Option<Duration>,
+    read: Option<Duration>,
+    first_byte: Option<Duration>,
+    stream: Option<Duration>,
+}
+
+/// A [`LimitsConfig::max_concurrent`](crate::LimitsConfig::max_concurrent)
+/// permit, kept in the response's extensions until its body is read.
+#[derive(Clone)]
+struct ConcurrencyPermit(#[allow(dead_code)] Arc<OwnedSemaphorePermit>);
+
+impl ConcurrencyPermit {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn attach(self, response: &mut Response) {
+        response.extensions_mut().insert(self);
+    }
+
+    /// Responses on wasm32 have no extensions, so the permit ends with the
+    /// request there.
+    #[cfg(target_arch = "wasm32")]
+    fn attach(self, _response: &mut Response) {}
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn take(response: &mut Response) -> Option<Self> {
+        response.extensions_mut().remove()
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn take(_response: &mut Response) -> Option<Self> {
+        None
+    }
+}
+
+/// The body of `response` as a stream that holds the response's
+/// `max_concurrent` permit until it is dropped.
+pub(crate) fn bytes_stream(
+    mut response: Response,
+) -> impl Stream<Item = reqwest::Result<Bytes>> + compat::MaybeSend + 'static {
+    let permit = ConcurrencyPermit::take(&mut response);
+    response.bytes_stream().map(move |chunk| {
+        let _permit = &permit;
+        chunk
+    })
+}
+
+/// Why `result` should be retried, or `None` when it is final.
+fn retry_reason(result: &Result<Response>) -> Option<RetryReason> {
+    match result {
+        Ok(response) if DifyError::is_retriable_status(response.status()) => {
+            Some(RetryReason::Status(response.status()))
+        }
+        Ok(_) => None,
+        Err(err) => transport::reqwest_error(err)
+            .filter(|err| err.is_connect() || err.is_timeout())
+            .map(|err| RetryReason::Transport(err.to_string())),
+    }
+}
+
+/// Wraps plain reqwest errors in [`DifyError::Transport`].
+fn transport_error(err: anyhow::Error) -> anyhow::Error {
+    match err.downcast::<reqwest::Error>() {
+        Ok(err) => DifyError::Transport(err).into(),
+        Err(err) => err,
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn proxy(config: &config::ProxyConfig) -> Result<Option<reqwest::Proxy>> {
+    let Some(url) = &config.url else {
+        return Ok(None);
+    };
+    let mut proxy = reqwest::Proxy::all(url)?;
+    if let Some(username) = &config.username {
+        proxy = proxy.basic_auth(username, config.password.as_deref().unwrap_or_default());
+    }
+    if !config.no_proxy.is_empty() {
+        proxy = proxy.no_proxy(reqwest::NoProxy::from_string(&config.no_proxy.join(",")));
+    }
+    Ok(Some(proxy))
+}
@@ -0,0 +1,98 @@
+use futures_util::future::{self, Either};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::sync::{Mutex, OwnedMutexGuard};
+
+use crate::{compat, error::DatasetBusy};
+
+type LockMap = Arc<std::sync::Mutex<HashMap<String, Arc<Mutex<()>>>>>;
+
+/// One write lock per dataset, shared by every client built from one
+/// `DifyClient`. A lock is removed once nobody holds or waits for it.
+#[derive(Debug)]
+pub(crate) struct DatasetLocks {
+    wait: Duration,
+    locks: LockMap,
+}
+
+/// Keeps other write helpers of the same client off a dataset until dropped,
+/// see [`KnowledgeBaseClient::lock_dataset`](crate::KnowledgeBaseClient::lock_dataset).
+///
+/// The lock is advisory: plain endpoints such as `create_document_by_text`
+/// or `delete_document` never take it, and it is not reentrant, so a task
+/// holding a guard that calls a locking helper on the same dataset waits
+/// and fails with [`DatasetBusy`](crate::DatasetBusy).
+#[derive(Debug)]
+pub struct DatasetWriteGuard {
+    // dropped before `_user`, which then finds the lock unused
+    _guard: Option<OwnedMutexGuard<()>>,
+    _user: Option<LockUser>,
+}
+
+impl DatasetWriteGuard {
+    /// A guard that holds nothing, used when write locking is disabled.
+    pub(crate) fn unlocked() -> Self {
+        Self {
+            _guard: None,
+            _user: None,
+        }
+    }
+}
+
+/// Holds or waits for the lock of `dataset_id`, and removes it from the map
+/// when dropped as its last user.
+#[derive(Debug)]
+struct LockUser {
+    locks: LockMap,
+    dataset_id: String,
+}
+
+impl Drop for LockUser {
+    fn drop(&mut self) {
+        let mut locks = self.locks.lock().unwrap();
+        // clones are only made with the map locked, so none can appear here
+        if locks
+            .get(&self.dataset_id)
+            .is_some_and(|lock| Arc::strong_count(lock) == 1)
+        {
+            locks.remove(&self.dataset_id);
+        }
+    }
+}
+
+impl DatasetLocks {
+    pub(crate) fn new(wait: Duration) -> Self {
+        Self {
+            wait,
+            locks: Default::default(),
+        }
+    }
+
+    pub(crate) async fn lock(&self, dataset_id: &str) -> Result<DatasetWriteGuard, DatasetBusy> {
+        let user = LockUser {
+            locks: self.locks.clone(),
+            dataset_id: dataset_id.to_string(),
+        };
+        let lock = self
+            .locks
+            .lock()
+            .unwrap()
+            .entry(dataset_id.to_string())
+            .or_default()
+            .clone();
+        match future::select(Box::pin(lock.lock_owned()), compat::sleep(self.wait)).await {
+            Either::Left((guard, _)) => Ok(DatasetWriteGuard {
+                _guard: Some(guard),
+                _user: Some(user),
+            }),
+            Either::Right((_, waiting)) => {
+                // stop waiting before `user` checks whether the lock is unused
+                drop(waiting);
+                drop(user);
+                Err(DatasetBusy {
+                    dataset_id: dataset_id.to_string(),
+                    waited: self.wait,
+                })
+            }
+        }
+    }
+}
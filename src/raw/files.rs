@@ -0,0 +1,243 @@
+use anyhow::Result;
+use bytes::Bytes;
+use reqwest::multipart::Part;
+#[cfg(feature = "fs")]
+use std::path::PathBuf;
+use std::{fmt, path::Path, sync::Arc};
+#[cfg(not(target_arch = "wasm32"))]
+use {
+    futures_util::{stream, Stream, StreamExt},
+    reqwest::Body,
+    std::{io, pin::Pin},
+    tokio::io::AsyncRead,
+    tokio_util::io::ReaderStream,
+};
+
+/// In-memory files are reported to progress callbacks in chunks of this size.
+const PROGRESS_CHUNK_SIZE: usize = 64 * 1024;
+
+type ProgressFn = Arc<dyn Fn(u64, Option<u64>) + Send + Sync>;
+
+/// The file content sent in a multipart upload. Uploads are not retried,
+/// see [`RetryConfig::max_retries`](crate::RetryConfig::max_retries).
+pub struct FileSource {
+    content: FileContent,
+    filename: Option<String>,
+    mime_type: Option<String>,
+    progress: Option<ProgressFn>,
+}
+
+enum FileContent {
+    #[cfg(feature = "fs")]
+    Path(PathBuf),
+    Bytes(Bytes),
+    #[cfg(not(target_arch = "wasm32"))]
+    Reader(Pin<Box<dyn AsyncRead + Send + Sync>>),
+}
+
+impl FileSource {
+    #[cfg(feature = "fs")]
+    pub fn path(path: impl Into<PathBuf>) -> Self {
+        Self::from_content(FileContent::Path(path.into()), None)
+    }
+
+    pub fn bytes(filename: &str, bytes: impl Into<Bytes>) -> Self {
+        Self::from_content(FileContent::Bytes(bytes.into()), Some(filename))
+    }
+
+    /// Read to the end while the request is sent, e.g. an upload body
+    /// forwarded from another HTTP request.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn reader(filename: &str, reader: impl AsyncRead + Send + Sync + 'static) -> Self {
+        Self::from_content(FileContent::Reader(Box::pin(reader)), Some(filename))
+    }
+
+    fn from_content(content: FileContent, filename: Option<&str>) -> Self {
+        Self {
+            content,
+            filename: filename.map(str::to_string),
+            mime_type: None,
+            progress: None,
+        }
+    }
+
+    /// Sets the content type instead of guessing it from the file extension.
+    pub fn with_mime_type(mut self, mime: &str) -> Self {
+        self.mime_type = Some(mime.to_string());
+        self
+    }
+
+    /// Calls `progress(sent, total)` as the file is sent. `total` is `None`
+    /// for readers, whose length is unknown. Not called on `wasm32`, where
+    /// bodies cannot be streamed.
+    pub fn with_progress(
+        mut self,
+        progress: impl Fn(u64, Option<u64>) + Send + Sync + 'static,
+    ) -> Self {
+        self.progress = Some(Arc::new(progress));
+        self
+    }
+
+    pub fn filename(&self) -> Option<String> {
+        if let Some(filename) = &self.filename {
+            return Some(filename.clone());
+        }
+        match &self.content {
+            #[cfg(feature = "fs")]
+            FileContent::Path(path) => path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned()),
+            _ => None,
+        }
+    }
+
+    pub fn mime_type(&self) -> Option<&str> {
+        if let Some(mime_type) = &self.mime_type {
+            return Some(mime_type);
+        }
+
+        let filename = self.filename()?;
+        let extension = Path::new(&filename).extension()?.to_str()?;
+        mime_from_extension(extension)
+    }
+
+    /// Files are streamed rather than read into memory. A multipart body is
+    /// always a stream, which can't be sent twice, so uploads are never
+    /// retried, whatever the source.
+    pub(crate) async fn into_part(self) -> Result<Part> {
+        let filename = self.filename();
+        let mime_type = self.mime_type().map(str::to_string);
+        #[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
+        let progress = self.progress;
+        let mut part = match self.content {
+            #[cfg(feature = "fs")]
+            FileContent::Path(path) => {
+                use anyhow::Context;
+
+                let file = tokio::fs::File::open(&path)
+                    .await
+                    .with_context(|| format!("failed to open {}", path.display()))?;
+                let length = file.metadata().await?.len();
+                let body = with_progress(ReaderStream::new(file), Some(length), progress);
+                Part::stream_with_length(body, length)
+            }
+            #[cfg(target_arch = "wasm32")]
+            FileContent::Bytes(bytes) => Part::bytes(bytes.to_vec()),
+            #[cfg(not(target_arch = "wasm32"))]
+            FileContent::Bytes(bytes) => {
+                let length = bytes.len() as u64;
+                match progress {
+                    Some(progress) => {
+                        let chunks =
+                            (0..bytes.len())
+                                .step_by(PROGRESS_CHUNK_SIZE)
+                                .map(move |start| {
+                                    let end = (start + PROGRESS_CHUNK_SIZE).min(bytes.len());
+                                    Ok(bytes.slice(start..end))
+                                });
+                        let body =
+                            with_progress(stream::iter(chunks), Some(length), Some(progress));
+                        Part::stream_with_length(body, length)
+                    }
+                    None => Part::stream_with_length(bytes, length),
+                }
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            FileContent::Reader(reader) => {
+                Part::stream(with_progress(ReaderStream::new(reader), None, progress))
+            }
+        };
+        if let Some(filename) = filename {
+            part = part.file_name(filename);
+        }
+        if let Some(mime_type) = mime_type {
+            part = part.mime_str(&mime_type)?;
+        }
+        Ok(part)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn with_progress<S>(chunks: S, total: Option<u64>, progress: Option<ProgressFn>) -> Body
+where
+    S: Stream<Item = io::Result<Bytes>> + Send + Sync + 'static,
+{
+    let Some(progress) = progress else {
+        return Body::wrap_stream(chunks);
+    };
+    let mut sent = 0;
+    Body::wrap_stream(chunks.inspect(move |chunk| {
+        if let Ok(chunk) = chunk {
+            sent += chunk.len() as u64;
+            progress(sent, total);
+        }
+    }))
+}
+
+fn mime_from_extension(extension: &str) -> Option<&'static str> {
+    let mime_type = match extension.to_ascii_lowercase().as_str() {
+        "txt" => "text/plain",
+        "md" | "markdown" | "mdx" => "text/markdown",
+        "html" | "htm" => "text/html",
+        "csv" => "text/csv",
+        "xml" => "application/xml",
+        "json" => "application/json",
+        "pdf" => "application/pdf",
+        "doc" => "application/msword",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "xls" => "application/vnd.ms-excel",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "ppt" => "application/vnd.ms-powerpoint",
+        "pptx" => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        "epub" => "application/epub+zip",
+        "eml" => "message/rfc822",
+        "msg" => "application/vnd.ms-outlook",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "mp3" | "mpga" => "audio/mpeg",
+        "m4a" => "audio/mp4",
+        "wav" => "audio/wav",
+        "amr" => "audio/amr",
+        "webm" => "audio/webm",
+        "mp4" => "video/mp4",
+        "mov" => "video/quicktime",
+        "mpeg" => "video/mpeg",
+        _ => return None,
+    };
+    Some(mime_type)
+}
+
+#[cfg(feature = "fs")]
+impl From<&Path> for FileSource {
+    fn from(value: &Path) -> Self {
+        Self::path(value)
+    }
+}
+
+#[cfg(feature = "fs")]
+impl From<PathBuf> for FileSource {
+    fn from(value: PathBuf) -> Self {
+        Self::path(value)
+    }
+}
+
+impl fmt::Debug for FileSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("FileSource");
+        match &self.content {
+            #[cfg(feature = "fs")]
+            FileContent::Path(path) => debug.field("path", path),
+            FileContent::Bytes(bytes) => debug.field("len", &bytes.len()),
+            #[cfg(not(target_arch = "wasm32"))]
+            FileContent::Reader(_) => debug.field("reader", &"..."),
+        };
+        debug
+            .field("filename", &self.filename)
+            .field("mime_type", &self.mime_type)
+            .field("progress", &self.progress.is_some())
+            .finish()
+    }
+}
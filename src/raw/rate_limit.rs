@@ -0,0 +1,123 @@
+use reqwest::{header::HeaderMap, Response};
+use std::{cell::RefCell, future::Future, time::Duration};
+use tokio::sync::Mutex;
+
+use crate::{
+    compat::{self, Instant},
+    error,
+};
+
+/// The quota headers of a response, as set by Dify Cloud and by gateways
+/// using the common `X-RateLimit-*` or `RateLimit-*` headers.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RateLimitInfo {
+    pub limit: Option<u64>,
+    pub remaining: Option<u64>,
+    /// Seconds until the quota resets, or a Unix timestamp, depending on the
+    /// gateway.
+    pub reset: Option<u64>,
+    pub retry_after: Option<Duration>,
+}
+
+impl RateLimitInfo {
+    /// `None` when the response has none of the headers.
+    pub fn from_response(response: &Response) -> Option<Self> {
+        let info = Self::from_headers(response.headers());
+        (info != Self::default()).then_some(info)
+    }
+
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let number = |name: &str| {
+            [
+                format!("x-ratelimit-{}", name),
+                format!("ratelimit-{}", name),
+            ]
+            .iter()
+            .find_map(|name| headers.get(name.as_str()))
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse().ok())
+        };
+        Self {
+            limit: number("limit"),
+            remaining: number("remaining"),
+            reset: number("reset"),
+            retry_after: error::retry_after(headers),
+        }
+    }
+}
+
+tokio::task_local! {
+    static LATEST: RefCell<Option<RateLimitInfo>>;
+}
+
+/// Runs `future` and returns its output with the quota headers of the last
+/// response it received that had any. Typed methods decode their response, so
+/// this is how their callers read the quota; for methods returning a
+/// [`Response`], [`RateLimitInfo::from_response`] does the same. Concurrent
+/// calls each see their own responses.
+pub async fn with_rate_limit_info<F: Future>(future: F) -> (F::Output, Option<RateLimitInfo>) {
+    LATEST
+        .scope(RefCell::new(None), async {
+            let output = future.await;
+            (output, LATEST.with(RefCell::take))
+        })
+        .await
+}
+
+/// Keeps the quota headers of `response` for the enclosing
+/// [`with_rate_limit_info`], if any.
+pub(crate) fn record(response: &Response) {
+    if let Some(info) = RateLimitInfo::from_response(response) {
+        let _ = LATEST.try_with(|latest| *latest.borrow_mut() = Some(info));
+    }
+}
+
+/// A token bucket refilled continuously at `per_minute` requests per minute,
+/// allowing bursts of up to `per_minute` requests.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    capacity: f64,
+    per_second: f64,
+    state: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled: Instant,
+}
+
+impl RateLimiter {
+    pub(crate) fn per_minute(per_minute: u32) -> Self {
+        let capacity = f64::from(per_minute);
+        Self {
+            capacity,
+            per_second: capacity / 60.0,
+            state: Mutex::new(Bucket {
+                tokens: capacity,
+                refilled: Instant::now(),
+            }),
+        }
+    }
+
+    /// Waits until a request may be sent. Waiters are served in order, since
+    /// the lock is held while sleeping.
+    pub(crate) async fn acquire(&self) {
+        let mut bucket = self.state.lock().await;
+        loop {
+            let now = Instant::now();
+            let elapsed = now.duration_since(bucket.refilled).as_secs_f64();
+            bucket.tokens = (bucket.tokens + elapsed * self.per_second).min(self.capacity);
+            bucket.refilled = now;
+
+            if bucket.tokens >= 1.0 {
+                bucket.tokens -= 1.0;
+                return;
+            }
+
+            let wait = Duration::from_secs_f64((1.0 - bucket.tokens) / self.per_second);
+            tracing::debug!("rate limited, waiting {:?}", wait);
+            compat::sleep(wait).await;
+        }
+    }
+}
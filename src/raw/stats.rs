@@ -0,0 +1,81 @@
+use reqwest::{Response, StatusCode};
+use std::{collections::BTreeMap, sync::Mutex, time::Duration};
+
+/// Weight of the newest sample in the moving averages.
+const ALPHA: f64 = 0.2;
+
+/// Moving averages of one endpoint, keyed in [`ClientStats`] by method and
+/// path with ids replaced by `:id`, e.g. `GET /datasets/:id/documents`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EndpointStats {
+    pub requests: u64,
+    pub errors: u64,
+    /// Exponential moving average of the time until the response headers
+    /// arrived.
+    pub latency: Duration,
+    /// Exponential moving average of failures: transport errors, 429 and 5xx
+    /// responses.
+    pub error_rate: f64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClientStats {
+    pub endpoints: BTreeMap<String, EndpointStats>,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct StatsRecorder(Mutex<ClientStats>);
+
+impl StatsRecorder {
+    pub(crate) fn record(
+        &self,
+        endpoint: String,
+        result: &anyhow::Result<Response>,
+        elapsed: Duration,
+    ) {
+        let failed = match result {
+            Ok(response) => {
+                response.status().is_server_error()
+                    || response.status() == StatusCode::TOO_MANY_REQUESTS
+            }
+            Err(_) => true,
+        };
+
+        let mut stats = self.0.lock().unwrap();
+        let stats = stats.endpoints.entry(endpoint).or_default();
+        let failure = if failed { 1.0 } else { 0.0 };
+        if stats.requests == 0 {
+            stats.latency = elapsed;
+            stats.error_rate = failure;
+        } else {
+            stats.latency = stats.latency.mul_f64(1.0 - ALPHA) + elapsed.mul_f64(ALPHA);
+            stats.error_rate = stats.error_rate * (1.0 - ALPHA) + failure * ALPHA;
+        }
+        stats.requests += 1;
+        stats.errors += failed as u64;
+    }
+
+    pub(crate) fn snapshot(&self) -> ClientStats {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+/// The stats key of a request to `path`, with numeric and uuid-like
+/// segments replaced so requests for different resources share an entry.
+pub(crate) fn endpoint_key(method: &reqwest::Method, path: &str) -> String {
+    let path = path
+        .split('/')
+        .map(|segment| {
+            let is_id = !segment.is_empty()
+                && segment.chars().any(|c| c.is_ascii_digit())
+                && segment.chars().all(|c| c.is_ascii_hexdigit() || c == '-');
+            if is_id {
+                ":id"
+            } else {
+                segment
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+    format!("{} {}", method, path)
+}
@@ -0,0 +1,17 @@
+use std::{future::Future, time::Duration};
+
+tokio::task_local! {
+    static TIMEOUT: Duration;
+}
+
+/// Runs `future` with `timeout` in place of the client's timeouts for every
+/// request it sends: the total duration of blocking calls and uploads, and
+/// for streaming calls the wait for the response and the longest gap between
+/// two chunks.
+pub async fn with_timeout<F: Future>(timeout: Duration, future: F) -> F::Output {
+    TIMEOUT.scope(timeout, future).await
+}
+
+pub(crate) fn current_timeout() -> Option<Duration> {
+    TIMEOUT.try_with(|timeout| *timeout).ok()
+}
@@ -0,0 +1,20 @@
+use std::future::Future;
+
+pub(crate) const TRACE_ID_HEADER: &str = "X-Trace-Id";
+
+tokio::task_local! {
+    static TRACE_ID: String;
+}
+
+/// Runs `future` with `trace_id` attached to every request it sends.
+///
+/// Dify forwards the id to its configured tracing integrations (LangFuse,
+/// LangSmith, ...), so generations can be correlated with the caller's own
+/// traces.
+pub async fn with_trace_id<F: Future>(trace_id: impl Into<String>, future: F) -> F::Output {
+    TRACE_ID.scope(trace_id.into(), future).await
+}
+
+pub fn current_trace_id() -> Option<String> {
+    TRACE_ID.try_with(|trace_id| trace_id.clone()).ok()
+}
@@ -0,0 +1,40 @@
+use anyhow::Result;
+use reqwest::{Request, Response};
+
+use crate::compat::BoxFuture;
+
+/// Sends the requests built by a [`DifyClient`](crate::DifyClient).
+///
+/// Implemented for `reqwest::Client` and, with the `middleware` feature, for
+/// `reqwest_middleware::ClientWithMiddleware`, so existing middlewares such
+/// as `reqwest-retry` or `reqwest-tracing` can be plugged in with
+/// [`DifyClientBuilder::transport`](crate::DifyClientBuilder::transport).
+/// Errors that wrap a `reqwest::Error` are classified like plain reqwest
+/// errors for retries.
+pub trait HttpTransport: Send + Sync {
+    fn execute(&self, request: Request) -> BoxFuture<'_, Result<Response>>;
+}
+
+impl HttpTransport for reqwest::Client {
+    fn execute(&self, request: Request) -> BoxFuture<'_, Result<Response>> {
+        Box::pin(async move { Ok(reqwest::Client::execute(self, request).await?) })
+    }
+}
+
+#[cfg(feature = "middleware")]
+impl HttpTransport for reqwest_middleware::ClientWithMiddleware {
+    fn execute(&self, request: Request) -> BoxFuture<'_, Result<Response>> {
+        Box::pin(async move {
+            Ok(reqwest_middleware::ClientWithMiddleware::execute(self, request).await?)
+        })
+    }
+}
+
+/// The reqwest error behind a transport error, if any.
+pub(crate) fn reqwest_error(err: &anyhow::Error) -> Option<&reqwest::Error> {
+    #[cfg(feature = "middleware")]
+    if let Some(reqwest_middleware::Error::Reqwest(err)) = err.downcast_ref() {
+        return Some(err);
+    }
+    err.downcast_ref()
+}
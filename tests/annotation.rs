@@ -0,0 +1,90 @@
+#![cfg(feature = "annotations")]
+
+use dify_client_rust::{
+    AnnotationJobStatus, AnnotationReplyAction, AnnotationReplySettings, DifyClient,
+};
+use std::time::Duration;
+
+mod common;
+
+use common::{MockServer, Reply};
+
+fn client(server: &MockServer) -> DifyClient {
+    DifyClient::builder("app-key")
+        .base_url(server.base_url())
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_annotation_reply_job() {
+    let server = MockServer::start(vec![
+        Reply::json(r#"{"job_id": "j-1", "job_status": "waiting"}"#),
+        Reply::json(r#"{"job_id": "j-1", "job_status": "processing"}"#),
+        Reply::json(r#"{"job_id": "j-1", "job_status": "completed"}"#),
+    ]);
+    let client = client(&server);
+
+    let settings = AnnotationReplySettings {
+        embedding_provider_name: Some("openai".to_string()),
+        embedding_model_name: Some("text-embedding-3-small".to_string()),
+        score_threshold: 0.9,
+        ..Default::default()
+    };
+    let job = client
+        .annotation_reply(AnnotationReplyAction::Enable, &settings)
+        .await
+        .unwrap();
+    assert_eq!(job.job_status, AnnotationJobStatus::Waiting);
+
+    let job = client
+        .wait_for_annotation_job(
+            AnnotationReplyAction::Enable,
+            job.job_id.as_str(),
+            Duration::from_millis(1),
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap();
+    assert_eq!(job.job_status, AnnotationJobStatus::Completed);
+
+    let requests = server.finish();
+    assert_eq!(
+        requests[0].line,
+        "POST /v1/apps/annotation-reply/enable HTTP/1.1"
+    );
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&requests[0].body).unwrap(),
+        serde_json::json!({
+            "embedding_provider_name": "openai",
+            "embedding_model_name": "text-embedding-3-small",
+            "score_threshold": 0.9
+        })
+    );
+    assert_eq!(
+        requests[2].line,
+        "GET /v1/apps/annotation-reply/enable/status/j-1 HTTP/1.1"
+    );
+}
+
+#[tokio::test]
+async fn test_annotation_job_error() {
+    let server = MockServer::start(vec![Reply::json(
+        r#"{"job_id": "j-2", "job_status": "error", "error_msg": "no embedding model"}"#,
+    )]);
+
+    let err = client(&server)
+        .wait_for_annotation_job(
+            AnnotationReplyAction::Disable,
+            "j-2",
+            Duration::from_millis(1),
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "annotation job j-2 failed: no embedding model"
+    );
+    server.finish();
+}
@@ -0,0 +1,79 @@
+#![cfg(feature = "audio")]
+
+use dify_client_rust::{DifyClient, FileSource};
+
+mod common;
+
+use common::{MockServer, Reply};
+
+fn client(server: &MockServer) -> DifyClient {
+    DifyClient::builder("app-key")
+        .base_url(server.base_url())
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_audio_to_text() {
+    let server = MockServer::start(vec![Reply::json(include_str!(
+        "../fixtures/audio_to_text_response.json"
+    ))]);
+
+    let text = client(&server)
+        .audio_to_text(
+            "user-1",
+            FileSource::bytes("hello.mp3", &b"ID3 mock audio"[..]),
+        )
+        .await
+        .unwrap();
+    assert_eq!(text, "Hello Dify");
+
+    let request = &server.finish()[0];
+    assert_eq!(request.line, "POST /v1/audio-to-text HTTP/1.1");
+    assert!(request.body.contains("name=\"user\"\r\n\r\nuser-1\r\n"));
+    assert!(request
+        .body
+        .contains("name=\"file\"; filename=\"hello.mp3\"\r\nContent-Type: audio/mpeg"));
+    assert!(request.body.contains("ID3 mock audio"));
+}
+
+#[tokio::test]
+async fn test_text_to_audio() {
+    use dify_client_rust::{AudioFormat, TextToAudioInput};
+    use futures_util::StreamExt;
+
+    let server = MockServer::start(vec![
+        Reply::new("audio/wav", "RIFF mock wav"),
+        Reply::new("audio/mpeg", "ID3 mock mp3"),
+    ]);
+    let client = client(&server);
+
+    let audio = client
+        .text_to_audio(TextToAudioInput::MessageId("m-1"), "user-1")
+        .await
+        .unwrap();
+    assert_eq!(audio.format, AudioFormat::Wav);
+    assert_eq!(&audio.bytes[..], b"RIFF mock wav");
+
+    let mut stream = client
+        .text_to_audio_stream(TextToAudioInput::Text("Hello"), "user-1")
+        .await
+        .unwrap();
+    assert_eq!(stream.format(), &AudioFormat::Mp3);
+    let mut bytes = Vec::new();
+    while let Some(chunk) = stream.next().await {
+        bytes.extend_from_slice(&chunk.unwrap());
+    }
+    assert_eq!(bytes, b"ID3 mock mp3");
+
+    let requests = server.finish();
+    assert_eq!(requests[0].line, "POST /v1/text-to-audio HTTP/1.1");
+    assert_eq!(
+        requests[0].body,
+        r#"{"message_id":"m-1","streaming":false,"user":"user-1"}"#
+    );
+    assert_eq!(
+        requests[1].body,
+        r#"{"streaming":true,"text":"Hello","user":"user-1"}"#
+    );
+}
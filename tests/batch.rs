@@ -0,0 +1,118 @@
+#![cfg(feature = "completion")]
+
+use dify_client_rust::{BatchProgress, BatchRunner, BatchTarget, CompletionClient, RetryConfig};
+use serde_json::{Map, Value};
+use std::sync::Mutex;
+
+mod common;
+
+#[tokio::test]
+async fn test_run_inputs_reports_progress() {
+    let client = CompletionClient::new("app-test", Some("http://127.0.0.1:9"));
+    let reports = Mutex::new(Vec::new());
+    let runner = BatchRunner::new(BatchTarget::Completion(&client))
+        .concurrency(2)
+        .retry(RetryConfig::default())
+        .on_progress(|progress| reports.lock().unwrap().push(progress));
+
+    let inputs = ["Ada", "Grace", "Linus"].map(|name| {
+        let mut inputs = Map::new();
+        inputs.insert("name".to_string(), Value::from(name));
+        inputs
+    });
+    let results = runner.run_inputs(inputs).await;
+
+    assert_eq!(results.len(), 3);
+    assert!(results.iter().all(|result| result.error.is_some()));
+    assert_eq!(
+        results
+            .iter()
+            .map(|result| result.index)
+            .collect::<Vec<_>>(),
+        [0, 1, 2]
+    );
+
+    drop(runner);
+    let reports = reports.into_inner().unwrap();
+    assert_eq!(reports.len(), 3);
+    assert_eq!(
+        reports.last(),
+        Some(&BatchProgress {
+            completed: 3,
+            failed: 3,
+            total: Some(3),
+        })
+    );
+}
+
+#[cfg(feature = "fs")]
+#[tokio::test]
+async fn test_run_file() {
+    use common::{MockServer, Reply};
+    use dify_client_rust::{BatchResult, BatchSummary, DifyClient};
+
+    let answer = r#"{"message_id": "m-1", "answer": "Hi"}"#;
+    let server = MockServer::start(vec![
+        Reply::json(answer),
+        Reply::status(503, r#"{"code": "unavailable", "message": "busy"}"#),
+        Reply::json(answer),
+    ]);
+    let client = CompletionClient::from(
+        DifyClient::builder("app-test")
+            .base_url(server.base_url())
+            .build()
+            .unwrap(),
+    );
+    let runner = BatchRunner::new(BatchTarget::Completion(&client))
+        .concurrency(1)
+        .retry(RetryConfig {
+            max_retries: 1,
+            initial_backoff_ms: 1,
+            ..Default::default()
+        });
+
+    let input = std::env::temp_dir().join("dify-client-rust-batch-jobs.jsonl");
+    let output = std::env::temp_dir().join("dify-client-rust-batch-results.jsonl");
+    std::fs::write(
+        &input,
+        "{\"id\": \"ada\", \"inputs\": {\"name\": \"Ada\"}}\n\
+        \n\
+        {not json\n\
+        {\"id\": \"grace\", \"inputs\": {\"name\": \"Grace\"}}\n",
+    )
+    .unwrap();
+    let summary = runner.run_file(&input, &output).await.unwrap();
+
+    assert_eq!(
+        summary,
+        BatchSummary {
+            total: 3,
+            succeeded: 2,
+            failed: 1,
+        }
+    );
+    let results: Vec<BatchResult> = std::fs::read_to_string(&output)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(
+        results
+            .iter()
+            .map(|result| (result.index, result.id.as_deref(), result.attempts))
+            .collect::<Vec<_>>(),
+        [(0, Some("ada"), 1), (2, None, 0), (3, Some("grace"), 2)]
+    );
+    assert_eq!(results[0].response.as_ref().unwrap()["answer"], "Hi");
+    assert!(results[1]
+        .error
+        .as_deref()
+        .unwrap()
+        .starts_with("invalid job"));
+
+    let requests = server.finish();
+    assert_eq!(requests[0].line, "POST /v1/completion-messages HTTP/1.1");
+    let body: Value = serde_json::from_str(&requests[0].body).unwrap();
+    assert_eq!(body["inputs"]["name"], "Ada");
+    assert_eq!(body["user"], "batch-runner");
+}
@@ -1,8 +1,11 @@
+#![cfg(feature = "chat")]
+
 use dify_client_rust::{ChatClient, DifyClient, ResponseMode};
 use serde_json::json;
 use std::env;
 use std::sync::Once;
-use tokio;
+
+mod common;
 
 static TRACING: Once = Once::new();
 
@@ -72,3 +75,47 @@ async fn test_streaming_chat() {
         tracing::debug!("{:?}", String::from_utf8(item.to_vec()).unwrap());
     }
 }
+
+#[tokio::test]
+async fn test_chat_request() {
+    use common::{MockServer, Reply};
+    use dify_client_rust::{ChatMessageRequest, ConfigProblem};
+
+    let server = MockServer::start(vec![Reply::json(include_str!(
+        "../fixtures/chat_message_response.json"
+    ))]);
+    let chat = ChatClient::from(
+        DifyClient::builder("app-key")
+            .base_url(server.base_url())
+            .build()
+            .unwrap(),
+    );
+
+    let err = ChatMessageRequest::new(json!([]), "", "mock-user")
+        .validate()
+        .unwrap_err();
+    assert_eq!(
+        err.problems,
+        [
+            ConfigProblem::InputsNotObject,
+            ConfigProblem::EmptyField { field: "query" },
+        ]
+    );
+
+    let request = ChatMessageRequest::new(json!({}), "hi", "mock-user").conversation_id("c-1");
+    chat.chat_request(&request).await.unwrap();
+
+    let request = &server.finish()[0];
+    assert_eq!(request.line, "POST /v1/chat-messages HTTP/1.1");
+    let body: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+    assert_eq!(
+        body,
+        json!({
+            "inputs": {},
+            "query": "hi",
+            "user": "mock-user",
+            "response_mode": "blocking",
+            "conversation_id": "c-1"
+        })
+    );
+}
@@ -0,0 +1,135 @@
+//! A local HTTP server answering requests with canned replies, one
+//! connection per reply.
+
+#![allow(dead_code)]
+
+use std::{
+    io::{Read, Write},
+    net::{TcpListener, TcpStream},
+    thread::JoinHandle,
+};
+
+pub struct Reply {
+    status: u16,
+    content_type: &'static str,
+    headers: Vec<(&'static str, String)>,
+    body: String,
+}
+
+impl Reply {
+    pub fn json(body: impl Into<String>) -> Self {
+        Self::status(200, body)
+    }
+
+    /// A JSON reply with another status than 200.
+    pub fn status(status: u16, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+
+    pub fn event_stream(body: impl Into<String>) -> Self {
+        Self::new("text/event-stream", body)
+    }
+
+    pub fn new(content_type: &'static str, body: impl Into<String>) -> Self {
+        Self {
+            status: 200,
+            content_type,
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+
+    pub fn header(mut self, name: &'static str, value: impl Into<String>) -> Self {
+        self.headers.push((name, value.into()));
+        self
+    }
+}
+
+/// A request received by the [`MockServer`].
+#[derive(Debug)]
+pub struct Request {
+    /// E.g. `GET /v1/parameters?user=user-1 HTTP/1.1`.
+    pub line: String,
+    pub body: String,
+}
+
+pub struct MockServer {
+    base_url: String,
+    server: JoinHandle<(TcpListener, Vec<Request>)>,
+}
+
+impl MockServer {
+    pub fn start(replies: Vec<Reply>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}/v1", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let mut requests = Vec::new();
+            for reply in replies {
+                let (mut socket, _) = listener.accept().unwrap();
+                requests.push(read_request(&mut socket));
+                let headers: String = reply
+                    .headers
+                    .iter()
+                    .map(|(name, value)| format!("{}: {}\r\n", name, value))
+                    .collect();
+                let response = format!(
+                    "HTTP/1.1 {} Mock\r\ncontent-type: {}\r\ncontent-length: {}\r\n{}connection: close\r\n\r\n{}",
+                    reply.status,
+                    reply.content_type,
+                    reply.body.len(),
+                    headers,
+                    reply.body
+                );
+                socket.write_all(response.as_bytes()).unwrap();
+            }
+            (listener, requests)
+        });
+        Self { base_url, server }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Waits until every reply was served and returns the requests, failing
+    /// when another request is already waiting.
+    pub fn finish(self) -> Vec<Request> {
+        let (listener, requests) = self.server.join().unwrap();
+        listener.set_nonblocking(true).unwrap();
+        assert!(listener.accept().is_err(), "unexpected extra request");
+        requests
+    }
+}
+
+/// Reads one HTTP request with a `content-length` body.
+pub fn read_request(socket: &mut TcpStream) -> Request {
+    let mut request = Vec::new();
+    let mut buffer = [0; 1024];
+    let end = loop {
+        let read = socket.read(&mut buffer).unwrap();
+        request.extend_from_slice(&buffer[..read]);
+        let text = String::from_utf8_lossy(&request).to_ascii_lowercase();
+        if let Some(end) = text.find("\r\n\r\n") {
+            let length = text
+                .lines()
+                .find_map(|line| line.strip_prefix("content-length:"))
+                .map_or(0, |length| length.trim().parse().unwrap());
+            if request.len() >= end + 4 + length {
+                break end;
+            }
+        }
+        if read == 0 {
+            break request.len();
+        }
+    };
+    let text = String::from_utf8_lossy(&request);
+    Request {
+        line: text.lines().next().unwrap_or_default().to_string(),
+        body: text.get(end + 4..).unwrap_or_default().to_string(),
+    }
+}
@@ -0,0 +1,141 @@
+#![cfg(feature = "completion")]
+
+use dify_client_rust::{CompletionClient, CompletionStreamEvent, DifyClient};
+use futures_util::StreamExt;
+use serde_json::json;
+
+mod common;
+
+use common::{MockServer, Reply};
+
+#[tokio::test]
+async fn test_completion_stream_events() {
+    let server = MockServer::start(vec![Reply::event_stream(concat!(
+            "data: {\"event\": \"message\", \"task_id\": \"t-1\", \"message_id\": \"m-1\", \"answer\": \"Bonjour\"}\n\n",
+            "data: {\"event\": \"message_replace\", \"task_id\": \"t-1\", \"message_id\": \"m-1\", \"answer\": \"Salut\"}\n\n",
+            "data: {\"event\": \"message_end\", \"task_id\": \"t-1\", \"message_id\": \"m-1\", ",
+            "\"metadata\": {\"usage\": {\"total_tokens\": 9}}}\n\n",
+    ))]);
+    let completion = CompletionClient::from(
+        DifyClient::builder("app-key")
+            .base_url(server.base_url())
+            .build()
+            .unwrap()
+            .with_output_processor(|answer: &str| answer.to_uppercase()),
+    );
+
+    let mut stream = completion
+        .create_completion_message_stream(json!({"query": "Say hello"}), "user-1", None)
+        .await
+        .unwrap();
+    let mut events = Vec::new();
+    while let Some(event) = stream.next().await {
+        events.push(event.unwrap());
+    }
+
+    assert!(
+        matches!(&events[0], CompletionStreamEvent::Message(event) if event.answer == "Bonjour")
+    );
+    assert!(
+        matches!(&events[1], CompletionStreamEvent::MessageReplace(event) if event.answer == "SALUT")
+    );
+    match &events[2] {
+        CompletionStreamEvent::MessageEnd(event) => {
+            assert_eq!(event.message_id.as_str(), "m-1");
+            assert_eq!(event.metadata.usage.as_ref().unwrap().total_tokens, 9);
+        }
+        event => panic!("unexpected event {:?}", event),
+    }
+    assert_eq!(events[0].answer(), Some("Bonjour"));
+    assert!(events.iter().all(|event| event.task_id() == Some("t-1")));
+
+    let request = &server.finish()[0];
+    assert_eq!(request.line, "POST /v1/completion-messages HTTP/1.1");
+    assert!(request.body.contains("\"response_mode\":\"streaming\""));
+}
+
+#[tokio::test]
+async fn test_completion_stop_generation() {
+    let server = MockServer::start(vec![Reply::json("{\"result\": \"success\"}")]);
+    let completion = CompletionClient::from(
+        DifyClient::builder("app-key")
+            .base_url(server.base_url())
+            .build()
+            .unwrap(),
+    );
+
+    completion.stop_generation("t-1", "user-1").await.unwrap();
+
+    let request = &server.finish()[0];
+    assert_eq!(
+        request.line,
+        "POST /v1/completion-messages/t-1/stop HTTP/1.1"
+    );
+    assert_eq!(request.body, "{\"user\":\"user-1\"}");
+}
+
+#[tokio::test]
+async fn test_completion_request() {
+    use dify_client_rust::{CompletionMessageRequest, ResponseMode};
+
+    let server = MockServer::start(vec![Reply::json(
+        "{\"message_id\": \"m-1\", \"answer\": \"Bonjour\"}",
+    )]);
+    let completion = CompletionClient::from(
+        DifyClient::builder("app-key")
+            .base_url(server.base_url())
+            .build()
+            .unwrap(),
+    );
+
+    let request = CompletionMessageRequest::new(json!({"query": "Say hello"}), "user-1");
+    assert!(completion
+        .completion_request(&CompletionMessageRequest::new(json!({}), " "))
+        .await
+        .is_err());
+    let response = completion.completion_request(&request).await.unwrap();
+    assert_eq!(response.status(), 200);
+
+    let request = &server.finish()[0];
+    assert_eq!(request.line, "POST /v1/completion-messages HTTP/1.1");
+    let body: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+    assert_eq!(
+        body,
+        json!({
+            "inputs": {"query": "Say hello"},
+            "response_mode": ResponseMode::Block,
+            "user": "user-1"
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_raw_methods_skip_the_status_check() {
+    use dify_client_rust::{DifyError, ResponseMode};
+
+    let error = r#"{"code": "app_unavailable", "message": "App unavailable", "status": 400}"#;
+    let server = MockServer::start(vec![Reply::status(400, error), Reply::status(400, error)]);
+    let completion = CompletionClient::from(
+        DifyClient::builder("app-key")
+            .base_url(server.base_url())
+            .build()
+            .unwrap(),
+    );
+
+    let response = completion
+        .create_completion_message_raw(json!({}), "blocking", "user-1", None)
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+    assert_eq!(response.text().await.unwrap(), error);
+
+    let err = completion
+        .create_completion_message(json!({}), ResponseMode::Block, "user-1", None)
+        .await
+        .unwrap_err();
+    let api_error = DifyError::find(&err)
+        .and_then(DifyError::api_error)
+        .unwrap();
+    assert_eq!(api_error.code, "app_unavailable");
+    server.finish();
+}
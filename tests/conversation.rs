@@ -0,0 +1,94 @@
+#![cfg(feature = "chat")]
+
+use dify_client_rust::{
+    ChatClient, Conversation, ConversationManager, ConversationStore, InMemoryConversationStore,
+    StoredConversation,
+};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+#[test]
+fn test_conversation_manager_ttl() {
+    let client = Arc::new(ChatClient::new("api_key", None));
+    let manager = ConversationManager::new(client, Duration::from_secs(60));
+
+    assert_eq!(manager.conversation("mock-user").conversation_id(), None);
+
+    let conversation = Conversation::new(
+        Arc::new(ChatClient::new("api_key", None)),
+        "mock-user",
+        Some("c-1"),
+    );
+    manager.commit(&conversation);
+    assert_eq!(
+        manager.conversation("mock-user").conversation_id(),
+        Some("c-1")
+    );
+    assert_eq!(manager.conversation("other-user").conversation_id(), None);
+
+    manager.reset("mock-user");
+    assert_eq!(manager.conversation("mock-user").conversation_id(), None);
+}
+
+#[test]
+fn test_conversation_manager_expiry() {
+    let client = Arc::new(ChatClient::new("api_key", None));
+    let store = InMemoryConversationStore::default();
+    store.put(
+        "mock-user",
+        StoredConversation {
+            conversation_id: "c-1".to_string(),
+            last_active: SystemTime::now() - Duration::from_secs(120),
+        },
+    );
+    let manager = ConversationManager::with_store(client, store, Duration::from_secs(60));
+
+    assert_eq!(manager.conversation("mock-user").conversation_id(), None);
+}
+
+#[test]
+fn test_conversation_attachments() {
+    let mut conversation = Conversation::new(
+        Arc::new(ChatClient::new("api_key", None)),
+        "mock-user",
+        None,
+    );
+    conversation.attach_file("document", "f-1");
+    conversation.attach_url("image", "https://example.com/a.png");
+
+    assert_eq!(
+        serde_json::to_value(conversation.attachments()).unwrap(),
+        serde_json::json!([
+            {
+                "type": "document",
+                "transfer_method": "local_file",
+                "upload_file_id": "f-1"
+            },
+            {
+                "type": "image",
+                "transfer_method": "remote_url",
+                "url": "https://example.com/a.png"
+            },
+        ])
+    );
+
+    conversation.clear_attachments();
+    assert!(conversation.attachments().is_empty());
+}
+
+#[tokio::test]
+async fn test_chat_session_close() {
+    use dify_client_rust::ChatSession;
+    use futures_util::{SinkExt, StreamExt};
+
+    let mut session = ChatSession::new(
+        Arc::new(ChatClient::new("api_key", None)),
+        "mock-user",
+        Some("c-1"),
+    );
+    assert_eq!(session.conversation_id(), Some("c-1"));
+
+    session.close().await.unwrap();
+    assert!(session.next().await.is_none());
+    assert!(session.send("hi".to_string()).await.is_err());
+}
@@ -0,0 +1,132 @@
+use anyhow::Context;
+use dify_client_rust::DifyError;
+use reqwest::StatusCode;
+use std::time::Duration;
+
+mod common;
+
+#[test]
+fn test_error_classification() {
+    let err: anyhow::Error = DifyError::Api {
+        status: StatusCode::TOO_MANY_REQUESTS,
+        retry_after: Some(Duration::from_secs(3)),
+        body: String::new(),
+    }
+    .into();
+    let err = Err::<(), _>(err)
+        .context("sending chat message")
+        .unwrap_err();
+
+    let dify_err = DifyError::find(&err).unwrap();
+    assert!(dify_err.is_rate_limited());
+    assert!(dify_err.is_retriable());
+    assert_eq!(dify_err.retry_after(), Some(Duration::from_secs(3)));
+    assert_eq!(dify_err.status_code(), Some(StatusCode::TOO_MANY_REQUESTS));
+
+    let dify_err = DifyError::Api {
+        status: StatusCode::UNAUTHORIZED,
+        retry_after: None,
+        body: String::new(),
+    };
+    assert!(!dify_err.is_retriable());
+    assert!(!dify_err.is_rate_limited());
+
+    assert!(DifyError::find(&anyhow::anyhow!("dataset_id is not set")).is_none());
+}
+
+#[test]
+fn test_api_error_body() {
+    use dify_client_rust::DifyApiError;
+
+    let err = DifyError::Api {
+        status: StatusCode::BAD_REQUEST,
+        retry_after: None,
+        body: r#"{"code": "invalid_param", "message": "query is required", "status": 400}"#
+            .to_string(),
+    };
+    assert_eq!(
+        err.api_error(),
+        Some(DifyApiError {
+            code: "invalid_param".to_string(),
+            message: "query is required".to_string(),
+            status: 400,
+            ..Default::default()
+        })
+    );
+    assert_eq!(
+        err.to_string(),
+        "dify api error 400 Bad Request: invalid_param: query is required"
+    );
+
+    let err = DifyError::Api {
+        status: StatusCode::BAD_GATEWAY,
+        retry_after: None,
+        body: "<html>bad gateway</html>".to_string(),
+    };
+    assert_eq!(err.api_error(), None);
+}
+
+#[test]
+fn test_rate_limit_info() {
+    use dify_client_rust::RateLimitInfo;
+    use reqwest::header::{HeaderMap, HeaderValue};
+
+    let mut headers = HeaderMap::new();
+    headers.insert("x-ratelimit-limit", HeaderValue::from_static("600"));
+    headers.insert("ratelimit-remaining", HeaderValue::from_static("12"));
+    headers.insert("retry-after", HeaderValue::from_static("7"));
+
+    assert_eq!(
+        RateLimitInfo::from_headers(&headers),
+        RateLimitInfo {
+            limit: Some(600),
+            remaining: Some(12),
+            reset: None,
+            retry_after: Some(Duration::from_secs(7)),
+        }
+    );
+    assert_eq!(
+        RateLimitInfo::from_headers(&HeaderMap::new()),
+        RateLimitInfo::default()
+    );
+
+    // HTTP dates count from now, past ones as no wait
+    headers.insert(
+        "retry-after",
+        HeaderValue::from_static("Sun, 06 Nov 1994 08:49:37 GMT"),
+    );
+    assert_eq!(
+        RateLimitInfo::from_headers(&headers).retry_after,
+        Some(Duration::ZERO)
+    );
+    headers.insert(
+        "retry-after",
+        HeaderValue::from_static("Fri, 01 Jan 2100 00:00:00 GMT"),
+    );
+    assert!(RateLimitInfo::from_headers(&headers).retry_after > Some(Duration::from_secs(86_400)));
+}
+
+#[tokio::test]
+async fn test_rate_limit_info_per_call() {
+    use common::{MockServer, Reply};
+    use dify_client_rust::{with_rate_limit_info, DifyClient};
+
+    let server = MockServer::start(vec![
+        Reply::json(include_str!("../fixtures/app_info.json")).header("x-ratelimit-remaining", "9"),
+        Reply::json(include_str!("../fixtures/app_info.json")),
+    ]);
+    let client = DifyClient::builder("app-key")
+        .base_url(server.base_url())
+        .build()
+        .unwrap();
+
+    let (info, rate_limit) = with_rate_limit_info(client.get_info()).await;
+    info.unwrap();
+    assert_eq!(rate_limit.unwrap().remaining, Some(9));
+
+    let (info, rate_limit) = with_rate_limit_info(client.get_info()).await;
+    info.unwrap();
+    assert_eq!(rate_limit, None);
+
+    server.finish();
+}
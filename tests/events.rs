@@ -0,0 +1,214 @@
+use dify_client_rust::{ClientEvent, DifyClient, RetryConfig, RetryReason};
+use std::sync::{Arc, Mutex};
+
+#[tokio::test]
+async fn test_retry_events() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let recorded = events.clone();
+    let client = DifyClient::builder("app-key")
+        .base_url("http://127.0.0.1:1/v1")
+        .retry(RetryConfig {
+            max_retries: 2,
+            initial_backoff_ms: 1,
+            max_backoff_ms: 2,
+            ..Default::default()
+        })
+        .build()
+        .unwrap()
+        .with_event_listener(move |event: &ClientEvent| {
+            recorded.lock().unwrap().push(event.clone())
+        });
+
+    assert!(client
+        .request(reqwest::Method::GET, "/info", None, None)
+        .await
+        .is_err());
+
+    let events = events.lock().unwrap();
+    assert_eq!(events.len(), 3);
+    match &events[1] {
+        ClientEvent::Retry {
+            attempt,
+            reason,
+            backoff,
+            url,
+            ..
+        } => {
+            assert_eq!(*attempt, 1);
+            assert!(matches!(reason, RetryReason::Transport(_)));
+            assert_eq!(backoff.as_millis(), 2);
+            assert_eq!(url, "http://127.0.0.1:1/v1/info");
+        }
+        event => panic!("unexpected event {:?}", event),
+    }
+    assert!(matches!(
+        events[2],
+        ClientEvent::RetriesExhausted { attempts: 3, .. }
+    ));
+}
+
+#[tokio::test]
+async fn test_interceptor_hooks() {
+    use dify_client_rust::{Interceptor, RequestInfo};
+    use std::time::Duration;
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<String>>);
+
+    impl Interceptor for Recorder {
+        fn on_request(&self, request: &mut reqwest::Request) {
+            request
+                .headers_mut()
+                .insert("x-gateway-tenant", "acme".parse().unwrap());
+            self.0.lock().unwrap().push(format!(
+                "request {}",
+                request.headers().contains_key("authorization")
+            ));
+        }
+
+        fn on_error(&self, request: &RequestInfo, _error: &anyhow::Error, _elapsed: Duration) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("error {} {}", request.method, request.url));
+        }
+
+        fn on_event(&self, _event: &ClientEvent) {
+            self.0.lock().unwrap().push("event".to_string());
+        }
+    }
+
+    let recorder = Arc::new(Recorder::default());
+    let client = DifyClient::new("app-key", Some("http://127.0.0.1:1/v1"))
+        .with_interceptor(recorder.clone());
+
+    assert!(client
+        .request(reqwest::Method::GET, "/info", None, None)
+        .await
+        .is_err());
+    assert_eq!(
+        *recorder.0.lock().unwrap(),
+        ["request true", "error GET http://127.0.0.1:1/v1/info"]
+    );
+}
+
+#[tokio::test]
+async fn test_custom_transport() {
+    use dify_client_rust::raw::HttpTransport;
+    use futures_util::future::BoxFuture;
+
+    #[derive(Clone, Default)]
+    struct Offline(Arc<Mutex<Vec<String>>>);
+
+    impl HttpTransport for Offline {
+        fn execute(
+            &self,
+            request: reqwest::Request,
+        ) -> BoxFuture<'_, anyhow::Result<reqwest::Response>> {
+            self.0.lock().unwrap().push(request.url().to_string());
+            Box::pin(async { Err(anyhow::anyhow!("offline")) })
+        }
+    }
+
+    let transport = Offline::default();
+    let client = DifyClient::builder("app-key")
+        .retry(RetryConfig {
+            max_retries: 2,
+            initial_backoff_ms: 1,
+            max_backoff_ms: 2,
+            ..Default::default()
+        })
+        .transport(transport.clone())
+        .build()
+        .unwrap();
+
+    let err = client
+        .request(reqwest::Method::GET, "/info", None, None)
+        .await
+        .unwrap_err();
+    assert_eq!(err.to_string(), "offline");
+    // errors without a reqwest cause are not retried
+    assert_eq!(
+        *transport.0.lock().unwrap(),
+        ["https://api.dify.ai/v1/info"]
+    );
+}
+
+#[tokio::test]
+async fn test_rate_limit() {
+    use dify_client_rust::raw::HttpTransport;
+    use futures_util::future::BoxFuture;
+    use std::time::{Duration, Instant};
+
+    struct Offline;
+
+    impl HttpTransport for Offline {
+        fn execute(
+            &self,
+            _request: reqwest::Request,
+        ) -> BoxFuture<'_, anyhow::Result<reqwest::Response>> {
+            Box::pin(async { Err(anyhow::anyhow!("offline")) })
+        }
+    }
+
+    let client = DifyClient::builder("app-key")
+        .max_requests_per_minute(60)
+        .transport(Offline)
+        .build()
+        .unwrap();
+
+    let started = Instant::now();
+    for _ in 0..60 {
+        let _ = client
+            .request(reqwest::Method::GET, "/info", None, None)
+            .await;
+    }
+    assert!(started.elapsed() < Duration::from_millis(500));
+
+    let _ = client
+        .request(reqwest::Method::GET, "/info", None, None)
+        .await;
+    assert!(started.elapsed() >= Duration::from_millis(900));
+}
+
+#[tokio::test]
+async fn test_client_stats() {
+    use dify_client_rust::raw::HttpTransport;
+    use futures_util::future::BoxFuture;
+
+    struct Offline;
+
+    impl HttpTransport for Offline {
+        fn execute(
+            &self,
+            _request: reqwest::Request,
+        ) -> BoxFuture<'_, anyhow::Result<reqwest::Response>> {
+            Box::pin(async { Err(anyhow::anyhow!("offline")) })
+        }
+    }
+
+    let client = DifyClient::builder("app-key")
+        .transport(Offline)
+        .build()
+        .unwrap();
+    for dataset in ["3f2a9c1e-7b4d-4e8a-9c1f-2d6b8e0a5f13", "42"] {
+        let _ = client
+            .request(
+                reqwest::Method::GET,
+                &format!("/datasets/{}/documents?page=1", dataset),
+                None,
+                None,
+            )
+            .await;
+    }
+
+    let stats = client.stats();
+    assert_eq!(
+        stats.endpoints.keys().collect::<Vec<_>>(),
+        ["GET /datasets/:id/documents"]
+    );
+    let documents = &stats.endpoints["GET /datasets/:id/documents"];
+    assert_eq!(documents.requests, 2);
+    assert_eq!(documents.errors, 2);
+    assert_eq!(documents.error_rate, 1.0);
+}
@@ -0,0 +1,51 @@
+use dify_client_rust::FileSource;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+#[test]
+#[cfg(feature = "fs")]
+fn test_file_source_mime_type() {
+    let source = FileSource::from(Path::new("/tmp/report.PDF"));
+    assert_eq!(source.filename().as_deref(), Some("report.PDF"));
+    assert_eq!(source.mime_type(), Some("application/pdf"));
+}
+
+#[test]
+fn test_file_source_in_memory() {
+    let source = FileSource::bytes("upload", b"hello".to_vec()).with_mime_type("text/plain");
+    assert_eq!(source.mime_type(), Some("text/plain"));
+
+    let source = FileSource::reader("notes.md", std::io::Cursor::new(b"# notes".to_vec()));
+    assert_eq!(source.filename().as_deref(), Some("notes.md"));
+    assert_eq!(source.mime_type(), Some("text/markdown"));
+    assert!(format!("{:?}", source).contains("reader"));
+
+    let source = FileSource::bytes("notes.docx", b"".to_vec());
+    assert_eq!(
+        source.mime_type(),
+        Some("application/vnd.openxmlformats-officedocument.wordprocessingml.document")
+    );
+
+    assert_eq!(FileSource::bytes("blob", b"".to_vec()).mime_type(), None);
+}
+
+#[test]
+#[cfg(feature = "fs")]
+fn test_file_source_mime_override() {
+    let source = FileSource::from(Path::new("/tmp/photo.JPG"));
+    assert_eq!(source.mime_type(), Some("image/jpeg"));
+
+    let source = FileSource::from(Path::new("/tmp/export.dat")).with_mime_type("text/csv");
+    assert_eq!(source.filename().as_deref(), Some("export.dat"));
+    assert_eq!(source.mime_type(), Some("text/csv"));
+}
+
+#[test]
+#[cfg(feature = "fs")]
+fn test_file_source_progress() {
+    let source = FileSource::path("/tmp/audio.mp3").with_progress(|sent, total| {
+        assert!(total.is_none_or(|total| sent <= total));
+    });
+    assert_eq!(source.mime_type(), Some("audio/mpeg"));
+    assert!(format!("{:?}", source).contains("progress: true"));
+}
@@ -0,0 +1,28 @@
+#![cfg(feature = "test-fixtures")]
+
+use dify_client_rust::fixtures::{chat_stream, chat_stream_events, Fixture};
+use dify_client_rust::models::{AppInfo, AppMeta, AppSite, ChatMessageResponse};
+use dify_client_rust::stream::{MessageEndEvent, MessageEvent};
+use futures_util::StreamExt;
+
+#[test]
+fn test_model_fixtures() {
+    assert_eq!(AppInfo::fixture().name, "My App");
+    assert_eq!(AppMeta::fixture().tool_icons.len(), 2);
+    assert_eq!(AppSite::fixture().default_language, "en-US");
+    assert_eq!(
+        ChatMessageResponse::fixture().conversation_id,
+        "45701982-8118-4bc5-8e9b-64562b4555f2"
+    );
+    assert_eq!(MessageEvent::fixture().answer, "Hi");
+    assert_eq!(
+        MessageEndEvent::fixture().task_id,
+        "900bbd43-dc0b-4383-a372-aa6e6c414227"
+    );
+}
+
+#[tokio::test]
+async fn test_chat_stream_fixture() {
+    let events: Vec<_> = chat_stream().map(|event| event.unwrap()).collect().await;
+    assert_eq!(events.len(), chat_stream_events().len());
+}
@@ -0,0 +1,14 @@
+#![cfg(feature = "chat")]
+
+use dify_client_rust::{ChatClient, DifyConfig};
+
+#[test]
+fn test_global_client() {
+    assert!(dify_client_rust::try_global().is_none());
+
+    dify_client_rust::init(&DifyConfig::new("app-key")).unwrap();
+    assert!(dify_client_rust::try_global().is_some());
+    assert!(dify_client_rust::init(&DifyConfig::new("app-key")).is_err());
+
+    let _chat = ChatClient::from(dify_client_rust::global().clone());
+}
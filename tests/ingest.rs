@@ -0,0 +1,29 @@
+#![cfg(feature = "ingest")]
+
+use dify_client_rust::ext::ingest::extract_text;
+
+#[test]
+fn test_extract_text() {
+    let html = r#"<!DOCTYPE html>
+<html>
+<head><title> Refund policy &amp; FAQ </title><style>p { color: red }</style></head>
+<body>
+  <nav><ul><li>Home</li><li>Docs</li></ul></nav>
+  <main>
+    <h1>Refunds</h1>
+    <p>Refunds take   5 business days.<br>Contact&nbsp;support &lt;help@example.com&gt;.</p>
+    <!-- tracking pixel -->
+    <script>track("view")</script>
+    <p>Questions? See the &#x201C;FAQ&#8221;.</p>
+  </main>
+  <footer>Copyright</footer>
+</body>
+</html>"#;
+
+    let page = extract_text(html);
+    assert_eq!(page.title.as_deref(), Some("Refund policy & FAQ"));
+    assert_eq!(
+        page.text,
+        "Refunds\nRefunds take 5 business days.\nContact support <help@example.com>.\nQuestions? See the \u{201C}FAQ\u{201D}."
+    );
+}
@@ -0,0 +1,30 @@
+use dify_client_rust::inputs::{inputs_from_json_str, load_inputs_list};
+use serde_json::json;
+
+#[test]
+fn test_inputs_validation() {
+    let inputs = inputs_from_json_str(r#"{"topic": "rust", "count": 3}"#).unwrap();
+    assert_eq!(inputs, json!({"topic": "rust", "count": 3}));
+
+    let err = inputs_from_json_str(r#"["rust"]"#).unwrap_err();
+    assert!(err.to_string().contains("found a list"));
+
+    let err = inputs_from_json_str(r#"{"topic": null}"#).unwrap_err();
+    assert!(err.to_string().contains("inputs.topic"));
+}
+
+#[test]
+fn test_load_inputs_list() {
+    let path = std::env::temp_dir().join("dify-client-rust-inputs-list.json");
+    std::fs::write(
+        &path,
+        r#"{"inputs": [{"topic": "rust"}, {"topic": "dify"}]}"#,
+    )
+    .unwrap();
+
+    let list = load_inputs_list(&path).unwrap();
+    assert_eq!(list.len(), 2);
+    assert_eq!(list[1], json!({"topic": "dify"}));
+
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,252 @@
+#![cfg(feature = "knowledge")]
+
+use dify_client_rust::knowledge::split_text;
+
+mod common;
+
+#[test]
+fn test_split_text_prefers_paragraphs() {
+    let text = "first paragraph\n\nsecond paragraph\nwith two lines";
+    let parts = split_text(text, 24);
+    assert_eq!(
+        parts,
+        [
+            "first paragraph\n\n",
+            "second paragraph\n",
+            "with two lines"
+        ]
+    );
+    assert_eq!(parts.concat(), text);
+}
+
+#[test]
+fn test_split_text_long_words() {
+    assert_eq!(split_text("abcdefgh", 3), ["abc", "def", "gh"]);
+    assert_eq!(split_text("héllo", 2), ["h", "é", "ll", "o"]);
+    assert_eq!(split_text("short", 100), ["short"]);
+    assert_eq!(split_text("", 10), [""]);
+}
+
+#[test]
+fn test_reembed_dataset_request() {
+    use dify_client_rust::{
+        ext::reembed::{reembed_dataset_request, EmbeddingModel},
+        knowledge::models::{Dataset, DatasetPermission},
+    };
+    use serde_json::json;
+
+    let source = Dataset {
+        name: "docs".to_string(),
+        description: Some("product docs".to_string()),
+        permission: DatasetPermission::AllTeamMembers,
+        embedding_model: Some("text-embedding-ada-002".to_string()),
+        retrieval_model_dict: Some(json!({
+            "search_method": "hybrid_search",
+            "reranking_enable": false,
+            "top_k": 5,
+            "score_threshold_enabled": false,
+        })),
+        ..Default::default()
+    };
+    let request = reembed_dataset_request(
+        &source,
+        "docs (v2)",
+        &EmbeddingModel::new("openai", "text-embedding-3-large"),
+    );
+
+    let request = serde_json::to_value(&request).unwrap();
+    assert_eq!(request["name"], "docs (v2)");
+    assert_eq!(request["description"], "product docs");
+    assert_eq!(request["indexing_technique"], "high_quality");
+    assert_eq!(request["permission"], "all_team_members");
+    assert_eq!(request["embedding_model_provider"], "openai");
+    assert_eq!(request["embedding_model"], "text-embedding-3-large");
+    assert_eq!(request["retrieval_model"]["search_method"], "hybrid_search");
+    assert_eq!(request["retrieval_model"]["top_k"], 5);
+}
+
+#[tokio::test]
+async fn test_lock_dataset() {
+    use dify_client_rust::{DatasetBusy, DifyClient, KnowledgeBaseClient};
+    use std::{sync::Arc, time::Duration};
+
+    let client = Arc::new(
+        DifyClient::builder("dataset-key")
+            .dataset_write_wait_secs(1)
+            .build()
+            .unwrap(),
+    );
+    let knowledge = KnowledgeBaseClient::with_client(client.clone(), Some("dataset-1"));
+    let other = KnowledgeBaseClient::with_client(client, Some("dataset-2"));
+
+    let guard = knowledge.lock_dataset().await.unwrap();
+    let _other_guard = other.lock_dataset().await.unwrap();
+    let err = knowledge.clone().lock_dataset().await.unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<DatasetBusy>(),
+        Some(&DatasetBusy {
+            dataset_id: "dataset-1".to_string(),
+            waited: Duration::from_secs(1),
+        })
+    );
+
+    drop(guard);
+    assert!(knowledge.lock_dataset().await.is_ok());
+
+    let unlocked = KnowledgeBaseClient::new("dataset-key", None, Some("dataset-1"));
+    let _first = unlocked.lock_dataset().await.unwrap();
+    assert!(unlocked.lock_dataset().await.is_ok());
+}
+
+#[tokio::test]
+async fn test_split_documents_failure_keeps_created_parts() {
+    use common::{MockServer, Reply};
+    use dify_client_rust::{
+        knowledge::models::{IndexingTechnique, ProcessRule, SplitDocumentsFailed},
+        DifyError, KnowledgeBaseClient,
+    };
+
+    let server = MockServer::start(vec![
+        Reply::json(include_str!("../fixtures/document_response.json")),
+        Reply::status(
+            400,
+            r#"{"code": "invalid_param", "message": "name exists", "status": 400}"#,
+        ),
+    ]);
+    let client = KnowledgeBaseClient::new("dataset-key", Some(server.base_url()), Some("d-1"));
+
+    let err = client
+        .create_documents_by_text_split(
+            "notes",
+            "first part\n\nsecond part\n\nthird part",
+            ProcessRule::Automatic,
+            IndexingTechnique::Economy,
+            12,
+            &[],
+        )
+        .await
+        .unwrap_err();
+
+    let failed = err.downcast_ref::<SplitDocumentsFailed>().unwrap();
+    assert_eq!(failed.parts, 3);
+    assert_eq!(failed.documents.len(), 1);
+    assert_eq!(failed.documents[0].name, "notes (1/3)");
+    assert_eq!(err.to_string(), "created 1 of 3 documents");
+    assert_eq!(
+        DifyError::find(&err).and_then(DifyError::status_code),
+        Some(reqwest::StatusCode::BAD_REQUEST)
+    );
+
+    let requests = server.finish();
+    assert_eq!(
+        requests[1].line,
+        "POST /v1/datasets/d-1/document/create-by-text HTTP/1.1"
+    );
+}
+
+#[tokio::test]
+async fn test_list_update_and_delete_documents() {
+    use common::{MockServer, Reply};
+    use dify_client_rust::KnowledgeBaseClient;
+
+    let document_response = include_str!("../fixtures/document_response.json");
+    let document: serde_json::Value = serde_json::from_str(document_response).unwrap();
+    let page = serde_json::json!({
+        "data": [document["document"]],
+        "has_more": false,
+        "limit": 20,
+        "total": 1,
+        "page": 1
+    });
+    let server = MockServer::start(vec![
+        Reply::json(page.to_string()),
+        Reply::json(document_response),
+        Reply::status(204, ""),
+    ]);
+    let client = KnowledgeBaseClient::new("dataset-key", Some(server.base_url()), Some("d-1"));
+
+    let page = client.list_documents(Some("faq"), 1, 20).await.unwrap();
+    assert_eq!(page.total, 1);
+    assert_eq!(page.data[0].name, "text.txt");
+
+    let updated = client
+        .update_document_by_text("doc-1", Some("faq.md"), Some("Q: ..."), None)
+        .await
+        .unwrap();
+    assert_eq!(updated.batch, "20230921150000123456");
+
+    client.delete_document("doc-1").await.unwrap();
+
+    let requests = server.finish();
+    assert_eq!(
+        requests[0].line,
+        "GET /v1/datasets/d-1/documents?keyword=faq&limit=20&page=1 HTTP/1.1"
+    );
+    assert_eq!(
+        requests[1].line,
+        "POST /v1/datasets/d-1/documents/doc-1/update-by-text HTTP/1.1"
+    );
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&requests[1].body).unwrap(),
+        serde_json::json!({"name": "faq.md", "text": "Q: ..."})
+    );
+    assert_eq!(
+        requests[2].line,
+        "DELETE /v1/datasets/d-1/documents/doc-1 HTTP/1.1"
+    );
+}
+
+#[tokio::test]
+async fn test_wait_until_indexed() {
+    use common::{MockServer, Reply};
+    use dify_client_rust::{
+        knowledge::models::IndexingFailed, models::IndexingStatus, KnowledgeBaseClient,
+    };
+    use std::time::Duration;
+
+    let server = MockServer::start(vec![
+        Reply::json(
+            r#"{"data": [{"id": "doc-1", "indexing_status": "indexing", "completed_segments": 1, "total_segments": 4}]}"#,
+        ),
+        Reply::json(
+            r#"{"data": [{"id": "doc-1", "indexing_status": "completed", "completed_segments": 4, "total_segments": 4}]}"#,
+        ),
+        Reply::json(
+            r#"{"data": [{"id": "doc-2", "indexing_status": "error", "error": "file is empty"}, {"id": "doc-3", "indexing_status": "paused"}, {"id": "doc-4", "indexing_status": "completed"}]}"#,
+        ),
+    ]);
+    let client = KnowledgeBaseClient::new("dataset-key", Some(server.base_url()), Some("d-1"));
+
+    let documents = client
+        .wait_until_indexed("b-1", Duration::from_millis(1), Duration::from_secs(5))
+        .await
+        .unwrap();
+    assert_eq!(documents[0].indexing_status, IndexingStatus::Completed);
+
+    let err = client
+        .wait_until_indexed("b-2", Duration::from_millis(1), Duration::from_secs(5))
+        .await
+        .unwrap_err();
+    let failed = err.downcast_ref::<IndexingFailed>().unwrap();
+    assert_eq!(failed.batch, "b-2");
+    let statuses: Vec<_> = failed
+        .documents
+        .iter()
+        .map(|document| document.indexing_status.clone())
+        .collect();
+    assert_eq!(statuses, [IndexingStatus::Error, IndexingStatus::Paused]);
+    assert_eq!(
+        err.to_string(),
+        "indexing of batch b-2 stopped: document doc-2 failed: file is empty; document doc-3 was paused"
+    );
+
+    let requests = server.finish();
+    assert_eq!(
+        requests[0].line,
+        "GET /v1/datasets/d-1/documents/b-1/indexing-status HTTP/1.1"
+    );
+    assert_eq!(
+        requests[2].line,
+        "GET /v1/datasets/d-1/documents/b-2/indexing-status HTTP/1.1"
+    );
+}
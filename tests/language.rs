@@ -0,0 +1,37 @@
+#![cfg(feature = "lang-detect")]
+
+use dify_client_rust::ext::language::{detect_language, language_instruction, matches_locale};
+
+#[test]
+fn test_detect_language() {
+    assert_eq!(
+        detect_language("Please let me know if there is anything else I can help you with.")
+            .as_deref(),
+        Some("en")
+    );
+    assert_eq!(
+        detect_language(
+            "Le renard brun rapide saute par-dessus le chien paresseux et continue de courir"
+        )
+        .as_deref(),
+        Some("fr")
+    );
+    assert_eq!(detect_language(""), None);
+}
+
+#[test]
+fn test_matches_locale() {
+    let answer = "Der schnelle braune Fuchs springt über den faulen Hund und läuft weiter";
+    assert_eq!(matches_locale(answer, "de-DE"), Some(true));
+    assert_eq!(matches_locale(answer, "en_US"), Some(false));
+    assert_eq!(matches_locale("ok", "en"), None);
+}
+
+#[test]
+fn test_language_instruction() {
+    assert_eq!(language_instruction("fr-CA"), "Please answer in French.");
+    assert_eq!(
+        language_instruction("tlh"),
+        "Please answer in the language of the locale tlh."
+    );
+}
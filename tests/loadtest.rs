@@ -0,0 +1,64 @@
+#![cfg(feature = "loadtest")]
+
+use dify_client_rust::ext::loadtest::{LatencyStats, LoadTest};
+use dify_client_rust::ChatClient;
+use serde_json::json;
+use std::time::Duration;
+
+#[test]
+fn test_latency_stats_percentiles() {
+    let samples = (1..=100)
+        .map(|ms| (Duration::from_millis(ms), ms % 10 != 0))
+        .collect();
+    let stats = LatencyStats::from_samples(samples);
+
+    assert_eq!(stats.count, 100);
+    assert_eq!(stats.errors, 10);
+    assert_eq!(stats.error_rate(), 0.1);
+    assert_eq!(stats.p50, Duration::from_millis(50));
+    assert_eq!(stats.p90, Duration::from_millis(90));
+    assert_eq!(stats.p99, Duration::from_millis(99));
+    assert_eq!(stats.max, Duration::from_millis(100));
+    assert_eq!(
+        LatencyStats::from_samples(Vec::new()),
+        LatencyStats::default()
+    );
+}
+
+#[tokio::test]
+async fn test_load_test_reports_errors() {
+    let chat = ChatClient::new("app-test", Some("http://127.0.0.1:9"));
+    let report = LoadTest::new(Duration::from_millis(100))
+        .concurrency(2)
+        .chat(&chat, "hello", json!({}), 1)
+        .run()
+        .await
+        .unwrap();
+
+    assert!(report.overall.count > 0);
+    assert_eq!(report.overall.error_rate(), 1.0);
+    assert_eq!(report.by_kind["chat"].count, report.overall.count);
+}
+
+#[tokio::test]
+async fn test_load_test_cuts_off_at_the_deadline() {
+    use std::{net::TcpListener, time::Instant};
+
+    // connections are queued but never answered
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}/v1", listener.local_addr().unwrap());
+    let chat = ChatClient::new("app-test", Some(&base_url));
+
+    let started = Instant::now();
+    let report = LoadTest::new(Duration::from_millis(100))
+        .concurrency(2)
+        .chat(&chat, "hello", json!({}), 1)
+        .run()
+        .await
+        .unwrap();
+
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert_eq!(report.cut_off, 2);
+    assert_eq!(report.overall.count, 0);
+    drop(listener);
+}
@@ -0,0 +1,665 @@
+#![cfg(feature = "knowledge")]
+
+use dify_client_rust::models::{AppMeta, AppSite, ToolIcon};
+use serde_json::json;
+
+#[test]
+fn test_app_meta_tool_icons() {
+    let meta: AppMeta = serde_json::from_value(json!({
+        "tool_icons": {
+            "dalle2": "https://cloud.dify.ai/console/api/workspaces/current/tool-provider/builtin/dalle/icon",
+            "api_tool": { "background": "#252525", "content": "😁" }
+        }
+    }))
+    .unwrap();
+
+    assert!(matches!(meta.tool_icons["dalle2"], ToolIcon::Url(_)));
+    assert_eq!(
+        meta.tool_icons["api_tool"],
+        ToolIcon::Emoji {
+            background: "#252525".to_string(),
+            content: "😁".to_string()
+        }
+    );
+}
+
+#[test]
+fn test_app_site() {
+    let site: AppSite = serde_json::from_value(json!({
+        "title": "My App",
+        "chat_color_theme": "#ff4a4a",
+        "chat_color_theme_inverted": false,
+        "icon_type": "emoji",
+        "icon": "😄",
+        "icon_background": "#FFEAD5",
+        "icon_url": null,
+        "description": "This is my app.",
+        "copyright": "all rights reserved",
+        "privacy_policy": "",
+        "custom_disclaimer": "All generated by AI",
+        "default_language": "en-US",
+        "show_workflow_steps": false,
+        "use_icon_as_answer_icon": false
+    }))
+    .unwrap();
+
+    assert_eq!(site.title, "My App");
+    assert_eq!(site.icon_url, None);
+    assert_eq!(site.default_language, "en-US");
+}
+
+fn assert_round_trip<T: serde::de::DeserializeOwned + serde::Serialize>(golden: &str) {
+    let expected: serde_json::Value = serde_json::from_str(golden).unwrap();
+    let model: T = serde_json::from_str(golden).unwrap();
+    assert_eq!(serde_json::to_value(model).unwrap(), expected);
+}
+
+#[test]
+fn test_models_round_trip() {
+    use dify_client_rust::models::{
+        AppInfo, AppParameters, AudioToTextResponse, ChatMessageResponse,
+        SuggestedQuestionsResponse, WorkflowRun,
+    };
+
+    assert_round_trip::<ChatMessageResponse>(include_str!(
+        "../fixtures/chat_message_response.json"
+    ));
+    assert_round_trip::<SuggestedQuestionsResponse>(include_str!(
+        "../fixtures/suggested_questions_response.json"
+    ));
+    assert_round_trip::<AudioToTextResponse>(include_str!(
+        "../fixtures/audio_to_text_response.json"
+    ));
+    assert_round_trip::<AppInfo>(include_str!("../fixtures/app_info.json"));
+    assert_round_trip::<AppMeta>(include_str!("../fixtures/app_meta.json"));
+    assert_round_trip::<AppSite>(include_str!("../fixtures/app_site.json"));
+    assert_round_trip::<AppParameters>(include_str!("../fixtures/app_parameters.json"));
+    assert_round_trip::<WorkflowRun>(include_str!("../fixtures/workflow_run.json"));
+
+    for line in include_str!("../fixtures/chat_stream.txt").lines() {
+        if let Some(data) = line.strip_prefix("data:") {
+            assert_round_trip::<dify_client_rust::ChatStreamEvent>(data.trim());
+        }
+    }
+}
+
+#[test]
+fn test_models_preserve_unknown_fields() {
+    let info: dify_client_rust::models::AppInfo = serde_json::from_value(json!({
+        "name": "My App",
+        "icon": "🤖"
+    }))
+    .unwrap();
+
+    assert_eq!(info.extra["icon"], json!("🤖"));
+}
+
+#[test]
+fn test_string_enums() {
+    use dify_client_rust::models::{AppMode, IndexingStatus};
+
+    let info: dify_client_rust::models::AppInfo =
+        serde_json::from_value(json!({"name": "My App", "mode": "advanced-chat"})).unwrap();
+    assert_eq!(info.mode, AppMode::AdvancedChat);
+
+    let status: IndexingStatus = serde_json::from_value(json!("re-indexing")).unwrap();
+    assert_eq!(status, IndexingStatus::Other("re-indexing".to_string()));
+    assert_eq!(serde_json::to_value(&status).unwrap(), json!("re-indexing"));
+    assert_eq!(IndexingStatus::Completed.to_string(), "completed");
+}
+
+#[test]
+fn test_timestamps() {
+    use dify_client_rust::models::{ChatMessageResponse, Timestamp};
+
+    let message: ChatMessageResponse =
+        serde_json::from_str(include_str!("../fixtures/chat_message_response.json")).unwrap();
+    assert_eq!(message.created_at, Timestamp(1705407629));
+
+    // null times are the epoch, or None where the field is optional
+    let null: Timestamp = serde_json::from_value(json!(null)).unwrap();
+    assert_eq!(null, Timestamp::default());
+    let null: Option<Timestamp> = serde_json::from_value(json!(null)).unwrap();
+    assert_eq!(null, None);
+
+    #[cfg(feature = "chrono")]
+    assert_eq!(
+        message.created_at.to_chrono().to_rfc3339(),
+        "2024-01-16T12:20:29+00:00"
+    );
+}
+
+#[test]
+fn test_message_metadata() {
+    use dify_client_rust::{
+        models::{ChatMessageResponse, MessageMetadata},
+        ChatStreamEvent,
+    };
+
+    let metadata = json!({
+        "usage": {"total_tokens": 12, "total_price": "0.0001", "currency": "USD"},
+        "retriever_resources": [],
+        "annotation_reply": {"id": "a-1", "account": {"id": "u-1", "name": "Dify user"}}
+    });
+    let message: ChatMessageResponse = serde_json::from_value(json!({
+        "message_id": "m-1",
+        "conversation_id": "c-1",
+        "answer": "Refunds take 5 days.",
+        "metadata": metadata,
+    }))
+    .unwrap();
+    let event: ChatStreamEvent = serde_json::from_value(json!({
+        "event": "message_end",
+        "message_id": "m-1",
+        "conversation_id": "c-1",
+        "metadata": metadata,
+    }))
+    .unwrap();
+    let ChatStreamEvent::MessageEnd(end) = event else {
+        panic!("expected a message_end event");
+    };
+
+    assert_eq!(message.metadata, end.metadata);
+    assert!(message.is_annotation_reply() && end.is_annotation_reply());
+    assert_eq!(end.metadata.annotation_id(), Some("a-1"));
+    assert!(!MessageMetadata::default().is_annotation_reply());
+    let annotation = message.metadata.annotation_reply.as_ref().unwrap();
+    assert_eq!(annotation.id.as_str(), "a-1");
+    assert_eq!(annotation.account.as_ref().unwrap().name, "Dify user");
+    assert_eq!(message.metadata.usage.unwrap().total_tokens, 12);
+    assert_eq!(
+        serde_json::to_value(MessageMetadata::default()).unwrap(),
+        json!({"usage": null, "retriever_resources": []})
+    );
+}
+
+#[test]
+fn test_dataset_page() {
+    use dify_client_rust::knowledge::models::{Dataset, DatasetPermission};
+    use dify_client_rust::models::Page;
+
+    let golden = include_str!("../fixtures/dataset_page.json");
+    let page: Page<Dataset> = serde_json::from_str(golden).unwrap();
+    assert!(page.has_more);
+    assert_eq!(page.data[0].permission, DatasetPermission::OnlyMe);
+    assert_eq!(page.data[0].indexing_technique, None);
+
+    assert_round_trip::<Page<Dataset>>(golden);
+}
+
+#[test]
+fn test_create_dataset_request() {
+    use dify_client_rust::knowledge::models::{
+        CreateDatasetRequest, IndexingTechnique, RetrievalModel, SearchMethod,
+    };
+
+    let request = CreateDatasetRequest {
+        indexing_technique: Some(IndexingTechnique::HighQuality),
+        retrieval_model: Some(RetrievalModel {
+            search_method: SearchMethod::HybridSearch,
+            ..Default::default()
+        }),
+        ..CreateDatasetRequest::new("faq")
+    };
+
+    assert_eq!(
+        serde_json::to_value(&request).unwrap(),
+        json!({
+            "name": "faq",
+            "indexing_technique": "high_quality",
+            "retrieval_model": {
+                "search_method": "hybrid_search",
+                "reranking_enable": false,
+                "top_k": 3,
+                "score_threshold_enabled": false
+            }
+        })
+    );
+}
+
+#[test]
+fn test_ids() {
+    use dify_client_rust::models::{ChatMessageResponse, Id};
+
+    let message: ChatMessageResponse =
+        serde_json::from_str(include_str!("../fixtures/chat_message_response.json")).unwrap();
+    assert_eq!(
+        message.conversation_id,
+        "45701982-8118-4bc5-8e9b-64562b4555f2"
+    );
+
+    #[cfg(feature = "uuid")]
+    {
+        assert!(message.conversation_id.uuid().is_some());
+        assert_eq!(Id::from("not-a-uuid").uuid(), None);
+    }
+    assert_eq!(Id::from("not-a-uuid").as_str(), "not-a-uuid");
+}
+
+#[test]
+fn test_process_rule() {
+    use dify_client_rust::knowledge::models::{
+        DocumentResponse, PreProcessingRule, PreProcessingRuleId, ProcessRule, ProcessRuleRules,
+        Segmentation,
+    };
+    use dify_client_rust::models::{DisplayStatus, IndexingStatus};
+
+    assert_eq!(
+        serde_json::to_value(ProcessRule::Automatic).unwrap(),
+        json!({"mode": "automatic"})
+    );
+    let custom = ProcessRule::Custom {
+        rules: ProcessRuleRules {
+            pre_processing_rules: vec![PreProcessingRule {
+                id: PreProcessingRuleId::RemoveExtraSpaces,
+                enabled: true,
+                extra: Default::default(),
+            }],
+            segmentation: Segmentation::default(),
+            extra: Default::default(),
+        },
+    };
+    assert_eq!(
+        serde_json::to_value(custom).unwrap(),
+        json!({
+            "mode": "custom",
+            "rules": {
+                "pre_processing_rules": [{"id": "remove_extra_spaces", "enabled": true}],
+                "segmentation": {"separator": "\n", "max_tokens": 500}
+            }
+        })
+    );
+
+    let golden = include_str!("../fixtures/document_response.json");
+    let response: DocumentResponse = serde_json::from_str(golden).unwrap();
+    assert_eq!(response.document.indexing_status, IndexingStatus::Waiting);
+    assert_eq!(
+        response.document.display_status,
+        Some(DisplayStatus::Queuing)
+    );
+    assert_round_trip::<DocumentResponse>(golden);
+}
+
+#[test]
+fn test_segments() {
+    use dify_client_rust::knowledge::models::{NewSegment, SegmentsResponse, UpdateSegmentRequest};
+    use dify_client_rust::models::SegmentStatus;
+
+    let segment = NewSegment {
+        keywords: vec!["refund".to_string()],
+        ..NewSegment::new("Refunds take 5 days.")
+    };
+    assert_eq!(
+        serde_json::to_value(&segment).unwrap(),
+        json!({ "content": "Refunds take 5 days.", "keywords": ["refund"] })
+    );
+
+    let update = UpdateSegmentRequest {
+        enabled: Some(false),
+        ..UpdateSegmentRequest::new("Refunds take 3 days.")
+    };
+    assert_eq!(
+        serde_json::to_value(&update).unwrap(),
+        json!({ "content": "Refunds take 3 days.", "enabled": false })
+    );
+
+    let response: SegmentsResponse = serde_json::from_value(json!({
+        "data": [{
+            "id": "c4bcc6a2-6d57-4a3b-9c6f-1f7e5b1b1a01",
+            "position": 1,
+            "document_id": "887a1bd6-3bde-4bca-8cb8-7b4c3a1e4f2d",
+            "content": "Refunds take 5 days.",
+            "answer": null,
+            "word_count": 20,
+            "tokens": 6,
+            "keywords": ["refund"],
+            "index_node_id": "a1b2",
+            "index_node_hash": "c3d4",
+            "hit_count": 0,
+            "enabled": true,
+            "disabled_at": null,
+            "disabled_by": null,
+            "status": "completed",
+            "created_by": "5c1b1e8f-0a52-4c87-9d8f-0c3a4e9b7e11",
+            "created_at": 1695312007,
+            "indexing_at": 1695312007,
+            "completed_at": 1695312007,
+            "error": null,
+            "stopped_at": null
+        }],
+        "doc_form": "text_model"
+    }))
+    .unwrap();
+
+    assert_eq!(response.data.len(), 1);
+    assert_eq!(response.data[0].status, SegmentStatus::Completed);
+    assert_eq!(response.data[0].keywords, ["refund"]);
+}
+
+#[test]
+fn test_child_chunk() {
+    use dify_client_rust::knowledge::models::{ChildChunkResponse, ChildChunkType};
+
+    let response: ChildChunkResponse = serde_json::from_value(json!({
+        "data": {
+            "id": "7f3f9b0e-9a4e-4a5e-8e0a-3f6b2c1d0e9f",
+            "segment_id": "c4bcc6a2-6d57-4a3b-9c6f-1f7e5b1b1a01",
+            "content": "Refunds take 5 days.",
+            "position": 1,
+            "word_count": 20,
+            "type": "customized",
+            "created_at": 1695312007,
+            "updated_at": 1695312007
+        }
+    }))
+    .unwrap();
+
+    assert_eq!(response.data.chunk_type, ChildChunkType::Customized);
+    assert_eq!(
+        serde_json::to_value(&response.data).unwrap()["type"],
+        json!("customized")
+    );
+}
+
+#[test]
+fn test_retrieve_response() {
+    use dify_client_rust::knowledge::models::RetrieveResponse;
+
+    let golden = include_str!("../fixtures/retrieve_response.json");
+    assert_round_trip::<RetrieveResponse>(golden);
+
+    let response: RetrieveResponse = serde_json::from_str(golden).unwrap();
+    let record = &response.records[0];
+    assert_eq!(record.score, Some(0.8731));
+    assert_eq!(
+        record.segment.document.as_ref().unwrap().name,
+        "refund-policy.md"
+    );
+}
+
+#[test]
+fn test_retrieved_document() {
+    use dify_client_rust::knowledge::models::{RetrieveResponse, RetrievedDocument};
+
+    let response: RetrieveResponse =
+        serde_json::from_str(include_str!("../fixtures/retrieve_response.json")).unwrap();
+    let document = RetrievedDocument::from(response.records[0].clone());
+
+    assert_eq!(document.content, "Refunds take 5 business days.");
+    assert_eq!(document.score, Some(0.8731));
+    assert_eq!(
+        document.metadata["document_name"],
+        json!("refund-policy.md")
+    );
+    assert_eq!(document.metadata["keywords"], json!(["refund", "days"]));
+    assert!(!document.metadata.contains_key("answer"));
+}
+
+#[test]
+fn test_dataset_metadata() {
+    use dify_client_rust::knowledge::models::{
+        DatasetMetadata, DocumentMetadata, MetadataType, MetadataValue,
+    };
+
+    let metadata: DatasetMetadata = serde_json::from_value(json!({
+        "doc_metadata": [
+            { "id": "f2a1", "name": "author", "type": "string", "count": 4 },
+            { "id": "f2a2", "name": "published", "type": "time", "count": 0 }
+        ],
+        "built_in_field_enabled": true
+    }))
+    .unwrap();
+    assert!(metadata.built_in_field_enabled);
+    assert_eq!(metadata.doc_metadata[0].field_type, MetadataType::String);
+    assert_eq!(metadata.doc_metadata[1].count, Some(0));
+
+    let update = DocumentMetadata {
+        document_id: "887a".to_string(),
+        metadata_list: vec![MetadataValue {
+            id: "f2a1".to_string(),
+            name: "author".to_string(),
+            value: json!("Ada"),
+            ..Default::default()
+        }],
+    };
+    assert_eq!(
+        serde_json::to_value(&update).unwrap(),
+        json!({
+            "document_id": "887a",
+            "metadata_list": [{ "id": "f2a1", "name": "author", "value": "Ada" }]
+        })
+    );
+}
+
+#[test]
+fn test_knowledge_tag_binding_count() {
+    use dify_client_rust::knowledge::models::KnowledgeTag;
+
+    let tags: Vec<KnowledgeTag> = serde_json::from_value(json!([
+        { "id": "t-1", "name": "faq", "type": "knowledge", "binding_count": "2" },
+        { "id": "t-2", "name": "legal", "type": "knowledge", "binding_count": 0 }
+    ]))
+    .unwrap();
+
+    assert_eq!(tags[0].binding_count, 2);
+    assert_eq!(tags[1].binding_count, 0);
+    assert_eq!(tags[1].tag_type, "knowledge");
+}
+
+#[test]
+fn test_app_parameters_capabilities() {
+    use dify_client_rust::models::AppParameters;
+
+    let parameters: AppParameters =
+        serde_json::from_str(include_str!("../fixtures/app_parameters.json")).unwrap();
+    assert!(parameters.supports_file_upload());
+    assert!(parameters.image_upload_enabled());
+    assert!(parameters.suggested_questions_enabled());
+    assert!(!parameters.speech_to_text_enabled());
+    assert!(parameters.text_to_speech_enabled());
+    assert!(parameters.retriever_resource_enabled());
+    assert!(!parameters.annotation_reply_enabled());
+
+    let parameters: AppParameters = serde_json::from_value(json!({
+        "file_upload": { "enabled": true, "allowed_file_types": ["document"] }
+    }))
+    .unwrap();
+    assert!(parameters.supports_file_upload());
+    assert!(!parameters.image_upload_enabled());
+    assert!(!AppParameters::default().supports_file_upload());
+}
+
+#[test]
+fn test_app_parameters_introduction() {
+    use dify_client_rust::models::AppParameters;
+
+    let parameters: AppParameters =
+        serde_json::from_str(include_str!("../fixtures/app_parameters.json")).unwrap();
+    assert_eq!(
+        parameters.opening_statement(),
+        Some("Hi, I can answer questions about your orders.")
+    );
+    let introduction = parameters.introduction().unwrap();
+    assert_eq!(
+        introduction.suggested_questions,
+        parameters.opening_questions()
+    );
+
+    let parameters: AppParameters = serde_json::from_value(json!({
+        "opening_statement": "  ",
+        "suggested_questions": []
+    }))
+    .unwrap();
+    assert_eq!(parameters.opening_statement(), None);
+    assert!(parameters.introduction().is_none());
+}
+
+#[test]
+fn test_upload_file() {
+    use dify_client_rust::knowledge::models::UploadFile;
+
+    let file: UploadFile = serde_json::from_value(json!({
+        "id": "f-1",
+        "name": "refund-policy.md",
+        "size": 2048,
+        "extension": "md",
+        "url": "/files/f-1/preview",
+        "download_url": "/files/f-1/preview?as_attachment=true",
+        "mime_type": "text/markdown",
+        "created_by": "u-1",
+        "created_at": 1728734540.0
+    }))
+    .unwrap();
+
+    assert_eq!(file.id, "f-1");
+    assert_eq!(file.size, 2048);
+    assert_eq!(file.created_at.0, 1728734540);
+}
+
+#[test]
+fn test_file_input() {
+    use dify_client_rust::models::{FileInput, FileType};
+
+    let file = FileInput::local_file(FileType::Image, "f-1");
+    assert_eq!(
+        serde_json::to_value(&file).unwrap(),
+        json!({ "transfer_method": "local_file", "type": "image", "upload_file_id": "f-1" })
+    );
+
+    let file: FileInput = serde_json::from_value(json!({
+        "type": "spreadsheet",
+        "transfer_method": "remote_url",
+        "url": "https://example.com/a.xlsx"
+    }))
+    .unwrap();
+    assert_eq!(file.file_type(), &FileType::Other("spreadsheet".into()));
+}
+
+#[test]
+fn test_file_type_from_mime_type() {
+    use dify_client_rust::models::FileType;
+
+    assert_eq!(FileType::from_mime_type("image/png"), FileType::Image);
+    assert_eq!(FileType::from_mime_type("audio/mpeg"), FileType::Audio);
+    assert_eq!(FileType::from_mime_type("video/mp4"), FileType::Video);
+    assert_eq!(
+        FileType::from_mime_type("application/pdf"),
+        FileType::Document
+    );
+}
+
+#[test]
+fn test_rating() {
+    use dify_client_rust::Rating;
+
+    assert_eq!(serde_json::to_value(Rating::Like).unwrap(), json!("like"));
+    assert_eq!(serde_json::to_value(Rating::Revoke).unwrap(), json!(null));
+    assert_eq!(
+        serde_json::from_value::<Option<Rating>>(json!("dislike")).unwrap(),
+        Some(Rating::Dislike)
+    );
+    assert_eq!(
+        serde_json::from_value::<Option<Rating>>(json!(null)).unwrap(),
+        None
+    );
+    for rating in [Rating::Like, Rating::Dislike, Rating::Revoke] {
+        let value = serde_json::to_value(rating).unwrap();
+        assert_eq!(serde_json::from_value::<Rating>(value).unwrap(), rating);
+    }
+    assert!(serde_json::from_value::<Rating>(json!("love")).is_err());
+}
+
+#[test]
+fn test_app_feedbacks() {
+    use dify_client_rust::{
+        models::{MessageFeedback, Page, Timestamp},
+        Rating,
+    };
+
+    let page: Page<MessageFeedback> = serde_json::from_value(json!({
+        "data": [{
+            "id": "8c0fbed8",
+            "app_id": "f252d396",
+            "conversation_id": "2397604b",
+            "message_id": "709c0b0f",
+            "rating": "like",
+            "content": "great answer",
+            "from_source": "api",
+            "from_end_user_id": "74286412",
+            "from_account_id": null,
+            "created_at": "2025-04-24T09:24:38",
+            "updated_at": "2025-04-24T09:24:38+08:00"
+        }, {
+            "id": "9d1acfe9",
+            "message_id": "81ad1c10",
+            "rating": null,
+            "content": null
+        }]
+    }))
+    .unwrap();
+
+    assert_eq!(page.data[1].rating, Rating::Revoke);
+    let feedback = &page.data[0];
+    assert_eq!(feedback.rating, Rating::Like);
+    assert_eq!(feedback.content.as_deref(), Some("great answer"));
+    assert_eq!(feedback.created_at, Timestamp(1745486678));
+    assert_eq!(feedback.updated_at, Timestamp(1745457878));
+}
+
+#[cfg(feature = "annotations")]
+#[test]
+fn test_annotation_reply_settings_keep_unknown_fields() {
+    use dify_client_rust::AnnotationReplySettings;
+
+    let payload = json!({
+        "embedding_provider_name": "openai",
+        "embedding_model_name": "text-embedding-3-small",
+        "score_threshold": 0.9,
+        "top_k": 3
+    });
+    let settings: AnnotationReplySettings = serde_json::from_value(payload.clone()).unwrap();
+
+    assert_eq!(settings.extra["top_k"], json!(3));
+    assert_eq!(serde_json::to_value(&settings).unwrap(), payload);
+}
+
+#[test]
+fn test_process_rule_keeps_unknown_fields() {
+    use dify_client_rust::knowledge::models::ProcessRule;
+
+    let payload = json!({
+        "mode": "custom",
+        "rules": {
+            "pre_processing_rules": [{"id": "remove_extra_spaces", "enabled": true, "order": 1}],
+            "segmentation": {"separator": "\n", "max_tokens": 500, "keep_separator": true},
+            "parent_mode": "paragraph"
+        }
+    });
+    let rule: ProcessRule = serde_json::from_value(payload.clone()).unwrap();
+
+    let ProcessRule::Custom { rules } = &rule else {
+        panic!("not a custom rule: {:?}", rule);
+    };
+    assert_eq!(rules.extra["parent_mode"], json!("paragraph"));
+    assert_eq!(rules.segmentation.extra["keep_separator"], json!(true));
+    assert_eq!(serde_json::to_value(&rule).unwrap(), payload);
+}
+
+#[test]
+fn test_page_keeps_unknown_fields() {
+    use dify_client_rust::models::{AnnotationAccount, Page};
+
+    let payload = json!({
+        "data": [{"id": "acc-1", "name": "Ada", "email": "ada@example.com"}],
+        "has_more": false,
+        "limit": 20,
+        "total": 1,
+        "page": 1,
+        "cursor": "c-2"
+    });
+    let page: Page<AnnotationAccount> = serde_json::from_value(payload.clone()).unwrap();
+
+    assert_eq!(page.extra["cursor"], json!("c-2"));
+    assert_eq!(page.data[0].extra["email"], json!("ada@example.com"));
+    assert_eq!(serde_json::to_value(&page).unwrap(), payload);
+}
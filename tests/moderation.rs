@@ -0,0 +1,59 @@
+#![cfg(all(feature = "chat", feature = "completion"))]
+
+use dify_client_rust::{ChatClient, DifyClient, InputRejected, Moderation, ResponseMode};
+use serde_json::json;
+
+#[tokio::test]
+async fn test_denied_query_is_not_sent() {
+    let client =
+        DifyClient::new("app-test", Some("http://127.0.0.1:9")).with_moderator(|query: &str| {
+            if query.contains("password") {
+                Moderation::Deny {
+                    reason: "credentials".to_string(),
+                }
+            } else {
+                Moderation::Allow
+            }
+        });
+    let chat = ChatClient::from(client);
+
+    let err = chat
+        .create_chat_message(
+            json!({}),
+            "my password is hunter2",
+            "user-1",
+            ResponseMode::Block,
+            None,
+            None,
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err.downcast_ref::<InputRejected>(),
+        Some(&InputRejected {
+            reason: "credentials".to_string()
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_unusual_inputs_do_not_panic() {
+    use dify_client_rust::CompletionClient;
+
+    let client = DifyClient::new("app-test", Some("http://127.0.0.1:9"))
+        .with_moderator(|query: &str| Moderation::Transform(query.to_uppercase()));
+    let completion = CompletionClient::from(client);
+
+    for inputs in [
+        json!(null),
+        json!("text"),
+        json!([1, 2]),
+        json!({"query": 42}),
+    ] {
+        let result = completion
+            .create_completion_message(inputs, ResponseMode::Block, "user-1", None)
+            .await;
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,43 @@
+use dify_client_rust::{
+    ext::openai::{from_openai, to_openai, OpenAiMessage, Role},
+    models::HistoryMessage,
+};
+use serde_json::json;
+
+#[test]
+fn test_history_to_openai() {
+    let history: Vec<HistoryMessage> = serde_json::from_value(json!([
+        { "id": "m1", "query": "hi", "answer": "hello!" },
+        { "id": "m2", "query": "still there?", "answer": "" }
+    ]))
+    .unwrap();
+
+    let messages = to_openai(&history);
+    assert_eq!(
+        serde_json::to_value(&messages).unwrap(),
+        json!([
+            { "role": "user", "content": "hi" },
+            { "role": "assistant", "content": "hello!" },
+            { "role": "user", "content": "still there?" }
+        ])
+    );
+}
+
+#[test]
+fn test_openai_to_history() {
+    let messages: Vec<OpenAiMessage> = serde_json::from_value(json!([
+        { "role": "system", "content": "be brief" },
+        { "role": "user", "content": [{ "type": "text", "text": "hi" }] },
+        { "role": "assistant", "content": "hello!" },
+        { "role": "user", "content": "bye" }
+    ]))
+    .unwrap();
+    assert_eq!(messages[1], OpenAiMessage::new(Role::User, "hi"));
+
+    let history = from_openai(&messages);
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].query, "hi");
+    assert_eq!(history[0].answer, "hello!");
+    assert_eq!(history[1].query, "bye");
+    assert!(history[1].answer.is_empty());
+}
@@ -0,0 +1,29 @@
+use dify_client_rust::OutputProcessor;
+
+struct MaskLinks;
+
+impl OutputProcessor for MaskLinks {
+    fn process_answer(&self, answer: &str) -> String {
+        answer.replace("http://", "hxxp://")
+    }
+
+    fn process_delta(&self, delta: &str) -> String {
+        self.process_answer(delta)
+    }
+}
+
+#[test]
+fn test_closure_processor_leaves_deltas() {
+    let upper = |answer: &str| answer.to_uppercase();
+
+    assert_eq!(upper.process_answer("hello"), "HELLO");
+    assert_eq!(upper.process_delta("hel"), "hel");
+}
+
+#[test]
+fn test_processor_deltas() {
+    assert_eq!(
+        MaskLinks.process_delta("see http://example.com"),
+        "see hxxp://example.com"
+    );
+}
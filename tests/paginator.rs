@@ -0,0 +1,47 @@
+use dify_client_rust::{models::Page, Paginator};
+use futures_util::{StreamExt, TryStreamExt};
+
+fn page(data: Vec<u32>, has_more: bool) -> Page<u32> {
+    Page {
+        data,
+        has_more,
+        limit: 2,
+        total: 5,
+        page: 0,
+        extra: Default::default(),
+    }
+}
+
+#[tokio::test]
+async fn test_paginator_by_page() {
+    let pages = Paginator::by_page(|number| async move {
+        Ok(match number {
+            1 => page(vec![1, 2], true),
+            2 => page(vec![3, 4], true),
+            3 => page(vec![5], false),
+            _ => panic!("requested page {} after the last one", number),
+        })
+    });
+
+    let items: Vec<u32> = pages.try_collect().await.unwrap();
+    assert_eq!(items, [1, 2, 3, 4, 5]);
+}
+
+#[tokio::test]
+async fn test_paginator_by_last_id() {
+    let mut items = Paginator::by_last_id(
+        |item: &u32| item.to_string(),
+        |last_id| async move {
+            match last_id.as_deref() {
+                None => Ok(page(vec![1, 2], true)),
+                Some("2") => Err(anyhow::anyhow!("server error")),
+                Some(other) => panic!("unexpected last_id {}", other),
+            }
+        },
+    );
+
+    assert_eq!(items.next().await.unwrap().unwrap(), 1);
+    assert_eq!(items.next().await.unwrap().unwrap(), 2);
+    assert!(items.next().await.unwrap().is_err());
+    assert!(items.next().await.is_none());
+}
@@ -0,0 +1,83 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+use dify_client_rust::{parameters::MIN_REFRESH_INTERVAL, DifyClient, ParametersCache};
+use std::time::{Duration, Instant};
+
+mod common;
+
+use common::{MockServer, Reply};
+
+const PARAMETERS: &str = include_str!("../fixtures/app_parameters.json");
+
+/// The golden parameters with another opening statement.
+fn changed_parameters() -> String {
+    let mut parameters: serde_json::Value = serde_json::from_str(PARAMETERS).unwrap();
+    parameters["opening_statement"] = "Hello again.".into();
+    parameters.to_string()
+}
+
+fn client(server: &MockServer) -> DifyClient {
+    DifyClient::builder("app-key")
+        .base_url(server.base_url())
+        .build()
+        .unwrap()
+}
+
+/// Long enough for exactly one more refresh.
+fn after_one_refresh() -> Duration {
+    MIN_REFRESH_INTERVAL + MIN_REFRESH_INTERVAL / 2
+}
+
+#[tokio::test]
+async fn test_parameters_refresh_after_interval() {
+    let server = MockServer::start(vec![
+        Reply::json(PARAMETERS),
+        Reply::json(changed_parameters()),
+    ]);
+    let mut cache = ParametersCache::spawn(client(&server), "user-1", Duration::from_millis(1));
+
+    let started = Instant::now();
+    let first = cache.changed().await.unwrap();
+    assert_eq!(
+        first.opening_statement,
+        "Hi, I can answer questions about your orders."
+    );
+    let second = cache.changed().await.unwrap();
+    assert_eq!(second.opening_statement, "Hello again.");
+    // the interval is raised to the minimum
+    assert!(started.elapsed() >= MIN_REFRESH_INTERVAL);
+    assert_eq!(cache.latest(), Some(second));
+
+    drop(cache);
+    let requests = server.finish();
+    assert_eq!(requests[1].line, "GET /v1/parameters?user=user-1 HTTP/1.1");
+}
+
+#[tokio::test]
+async fn test_parameters_keep_stale_copy_on_error() {
+    let server = MockServer::start(vec![
+        Reply::json(PARAMETERS),
+        Reply::status(503, r#"{"code": "unavailable", "message": "busy"}"#),
+    ]);
+    let mut cache = ParametersCache::spawn(client(&server), "user-1", MIN_REFRESH_INTERVAL);
+
+    let first = cache.changed().await.unwrap();
+    tokio::time::sleep(after_one_refresh()).await;
+    assert_eq!(cache.latest(), Some(first));
+
+    drop(cache);
+    assert_eq!(server.finish().len(), 2);
+}
+
+#[tokio::test]
+async fn test_parameters_refresh_stops_on_drop() {
+    let server = MockServer::start(vec![Reply::json(PARAMETERS)]);
+    let mut cache = ParametersCache::spawn(client(&server), "user-1", MIN_REFRESH_INTERVAL);
+
+    assert!(cache.changed().await.is_some());
+    drop(cache);
+    tokio::time::sleep(after_one_refresh()).await;
+
+    // fails when the dropped cache asked again
+    assert_eq!(server.finish().len(), 1);
+}
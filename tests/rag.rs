@@ -0,0 +1,28 @@
+#![cfg(all(feature = "chat", feature = "knowledge"))]
+
+use dify_client_rust::{ext::rag::format_context, knowledge::models::RetrievedDocument};
+use serde_json::json;
+
+#[test]
+fn test_format_context() {
+    let documents: Vec<RetrievedDocument> = serde_json::from_value(json!([
+        {
+            "content": "Refunds take 5 business days.",
+            "metadata": { "document_name": "Refund policy" },
+            "score": 0.9
+        },
+        {
+            "content": "Can I pay with cash?",
+            "metadata": { "answer": "No, cards only." },
+            "score": 0.7
+        }
+    ]))
+    .unwrap();
+
+    assert_eq!(
+        format_context(&documents),
+        "[1] Refund policy\nRefunds take 5 business days.\n\n\
+         [2]\nCan I pay with cash?\nNo, cards only."
+    );
+    assert_eq!(format_context(&[]), "");
+}
@@ -0,0 +1,34 @@
+use dify_client_rust::ext::replay::{parse_transcript, replay, TranscriptRecorder};
+use dify_client_rust::{ChatStream, ChatStreamEvent};
+use futures_util::StreamExt;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_replay_recorded_transcript() {
+    let mut recorder = TranscriptRecorder::new();
+    recorder
+        .record(&json!({"event": "message", "task_id": "t-1", "answer": "Hel"}))
+        .unwrap();
+    recorder
+        .record(&json!({"event": "message", "task_id": "t-1", "answer": "lo"}))
+        .unwrap();
+    recorder
+        .record(&json!({"event": "message_end", "task_id": "t-1"}))
+        .unwrap();
+
+    let entries = parse_transcript(&recorder.to_jsonl().unwrap()).unwrap();
+    assert_eq!(entries, recorder.entries());
+
+    let stream: ChatStream = replay(entries, f64::INFINITY);
+    let events: Vec<ChatStreamEvent> = stream.map(|event| event.unwrap()).collect().await;
+
+    let answer: String = events
+        .iter()
+        .filter_map(|event| match event {
+            ChatStreamEvent::Message(message) => Some(message.answer.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(answer, "Hello");
+    assert!(matches!(events[2], ChatStreamEvent::MessageEnd(_)));
+}
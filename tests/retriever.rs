@@ -0,0 +1,30 @@
+#![cfg(feature = "knowledge")]
+
+use anyhow::Result;
+use dify_client_rust::{knowledge::models::RetrievedDocument, Retriever};
+use futures_util::future::BoxFuture;
+use std::sync::Arc;
+
+struct StaticRetriever(Vec<RetrievedDocument>);
+
+impl Retriever for StaticRetriever {
+    fn retrieve_documents<'a>(
+        &'a self,
+        _query: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<RetrievedDocument>>> {
+        Box::pin(async move { Ok(self.0.clone()) })
+    }
+}
+
+#[tokio::test]
+async fn test_retriever_behind_trait_object() {
+    let retriever: Arc<dyn Retriever> = Arc::new(StaticRetriever(vec![RetrievedDocument {
+        content: "Refunds take 5 business days.".to_string(),
+        score: Some(0.9),
+        ..Default::default()
+    }]));
+
+    let documents = retriever.retrieve_documents("refunds").await.unwrap();
+    assert_eq!(documents.len(), 1);
+    assert_eq!(documents[0].score, Some(0.9));
+}
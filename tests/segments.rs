@@ -0,0 +1,65 @@
+#![cfg(feature = "knowledge")]
+
+use dify_client_rust::ext::segments::segments_from_csv;
+
+#[test]
+fn test_segments_from_csv() {
+    let csv = "\u{feff}Question,Answer,Keywords\r\n\
+        How long do refunds take?,5 business days,\"refund, days\"\r\n\
+        \"Can I pay with \"\"cash\"\"?\",\"No.\nCards only.\",payment;cash\r\n\
+        ,orphan answer,\r\n";
+
+    let segments = segments_from_csv(csv).unwrap();
+    assert_eq!(segments.len(), 2);
+    assert_eq!(segments[0].content, "How long do refunds take?");
+    assert_eq!(segments[0].answer.as_deref(), Some("5 business days"));
+    assert_eq!(segments[0].keywords, ["refund", "days"]);
+    assert_eq!(segments[1].content, "Can I pay with \"cash\"?");
+    assert_eq!(segments[1].answer.as_deref(), Some("No.\nCards only."));
+    assert_eq!(segments[1].keywords, ["payment", "cash"]);
+}
+
+#[test]
+fn test_segments_from_csv_requires_content() {
+    let err = segments_from_csv("answer\nyes\n").unwrap_err();
+    assert!(err.to_string().contains("`content` or `question`"));
+    assert!(segments_from_csv("content\n\"open").is_err());
+}
+
+#[test]
+fn test_exported_segment() {
+    use dify_client_rust::{ext::segments::ExportedSegment, knowledge::models::Segment};
+    use serde_json::json;
+
+    let segment: Segment = serde_json::from_value(json!({
+        "id": "s-1",
+        "position": 3,
+        "document_id": "d-1",
+        "content": "How long do refunds take?",
+        "answer": "5 business days",
+        "keywords": ["refund"],
+        "tokens": 12,
+        "status": "completed",
+        "enabled": true
+    }))
+    .unwrap();
+
+    assert_eq!(
+        serde_json::to_value(ExportedSegment::from(&segment)).unwrap(),
+        json!({
+            "id": "s-1",
+            "content": "How long do refunds take?",
+            "answer": "5 business days",
+            "keywords": ["refund"],
+            "metadata": {
+                "document_id": "d-1",
+                "position": 3,
+                "word_count": 0,
+                "tokens": 12,
+                "hit_count": 0,
+                "enabled": true,
+                "status": "completed"
+            }
+        })
+    );
+}
@@ -0,0 +1,70 @@
+#![cfg(all(
+    feature = "chat",
+    feature = "completion",
+    feature = "workflow",
+    feature = "knowledge",
+    feature = "audio"
+))]
+
+//! Clients and helpers are shared between tasks (e.g. in Axum state), so they
+//! must be `Send + Sync`. Streams are owned by one task at a time and only
+//! need to be `Send`.
+
+use dify_client_rust::{
+    ext::{replay::TranscriptRecorder, upload_files::UploadFileCache},
+    AudioStream, BatchRunner, ChatClient, ChatStream, CompletionClient, Conversation,
+    ConversationManager, DifyClient, DifyConfig, DifyError, EventStream, InMemoryConversationStore,
+    KnowledgeBaseClient, ParametersCache, ResponseMode, WorkflowClient, WorkflowRunRequest,
+    WorkflowStream,
+};
+use serde_json::{json, Value};
+use static_assertions::assert_impl_all;
+
+assert_impl_all!(DifyClient: Send, Sync, Clone);
+assert_impl_all!(ChatClient: Send, Sync);
+assert_impl_all!(CompletionClient: Send, Sync);
+assert_impl_all!(WorkflowClient: Send, Sync);
+assert_impl_all!(KnowledgeBaseClient: Send, Sync);
+assert_impl_all!(Conversation: Send, Sync);
+assert_impl_all!(ConversationManager<InMemoryConversationStore>: Send, Sync);
+assert_impl_all!(ParametersCache: Send, Sync);
+assert_impl_all!(UploadFileCache: Send, Sync);
+assert_impl_all!(BatchRunner<'static>: Send, Sync);
+assert_impl_all!(TranscriptRecorder: Send, Sync);
+assert_impl_all!(KnowledgeBaseClient: dify_client_rust::Retriever);
+assert_impl_all!(DifyConfig: Send, Sync);
+assert_impl_all!(WorkflowRunRequest: Send, Sync);
+assert_impl_all!(DifyError: Send, Sync, std::error::Error);
+
+assert_impl_all!(ChatStream: Send, Unpin);
+assert_impl_all!(WorkflowStream: Send, Unpin);
+assert_impl_all!(EventStream<Value>: Send, Unpin);
+assert_impl_all!(AudioStream: Send, Unpin);
+assert_impl_all!(dify_client_rust::ChatSession: Send, Unpin);
+
+fn assert_send<T: Send>(_: &T) {}
+
+#[test]
+fn test_request_futures_are_send() {
+    let chat = ChatClient::new("app-test", None);
+    assert_send(&chat.create_chat_message(
+        json!({}),
+        "hi",
+        "user-1",
+        ResponseMode::Block,
+        None,
+        None,
+    ));
+    assert_send(&chat.create_chat_message_stream(json!({}), "hi", "user-1", None, None));
+
+    let workflow = WorkflowClient::new("app-test", None);
+    assert_send(&workflow.run_stream(json!({}), "user-1"));
+    assert_send(&workflow.get_run("r-1"));
+
+    let knowledge = KnowledgeBaseClient::new("dataset-test", None, Some("d-1"));
+    assert_send(&knowledge.retrieve("refunds", None));
+    assert_send(&knowledge.list_documents(None, 1, 20));
+
+    let mut conversation = Conversation::new(chat.into(), "user-1", None);
+    assert_send(&conversation.send("hi", json!({})));
+}
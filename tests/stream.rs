@@ -0,0 +1,447 @@
+#![cfg(feature = "chat")]
+
+use bytes::Bytes;
+use dify_client_rust::{ChatStream, ChatStreamEvent};
+use futures_util::{stream, StreamExt};
+
+mod common;
+
+use common::{read_request, MockServer, Reply};
+
+#[tokio::test]
+async fn test_chat_stream_events() {
+    let chunks: Vec<reqwest::Result<Bytes>> = vec![
+        Ok(Bytes::from_static(
+            b"data: {\"event\": \"message\", \"task_id\": \"t-1\", \"answer\": \"Hel\"}\n\n",
+        )),
+        Ok(Bytes::from_static(b"event: ping\n\ndata: {\"event\": \"mess")),
+        Ok(Bytes::from_static(
+            b"age\", \"task_id\": \"t-1\", \"answer\": \"lo\"}\n\ndata: {\"event\": \"tts_message\"}\n\n",
+        )),
+        Ok(Bytes::from_static(
+            b"data: {\"event\": \"message_end\", \"task_id\": \"t-1\", \"conversation_id\": \"c-1\"}",
+        )),
+    ];
+    let events: Vec<ChatStreamEvent> = ChatStream::from_bytes_stream(stream::iter(chunks))
+        .map(|event| event.unwrap())
+        .collect()
+        .await;
+
+    assert_eq!(events.len(), 4);
+    assert!(events.iter().all(|event| match event {
+        ChatStreamEvent::Unknown => event.task_id().is_none(),
+        _ => event.task_id() == Some("t-1"),
+    }));
+
+    let answer: String = events
+        .iter()
+        .filter_map(|event| match event {
+            ChatStreamEvent::Message(message) => Some(message.answer.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(answer, "Hello");
+    assert!(matches!(events[3], ChatStreamEvent::MessageEnd(_)));
+}
+
+#[tokio::test]
+async fn test_agent_stream_events() {
+    let chunks: Vec<reqwest::Result<Bytes>> = vec![Ok(Bytes::from_static(
+        concat!(
+            "data: {\"event\": \"agent_thought\", \"id\": \"th-1\", \"task_id\": \"t-1\", ",
+            "\"message_id\": \"m-1\", \"position\": 1, \"thought\": \"I should draw it\", ",
+            "\"observation\": \"image created\", \"tool\": \"dalle3; search\", ",
+            "\"tool_input\": \"{\\\"dalle3\\\": {\\\"prompt\\\": \\\"a cat\\\"}}\", ",
+            "\"message_files\": [\"f-1\"], \"created_at\": 1705395332}\n\n",
+            "data: {\"event\": \"message_file\", \"id\": \"f-1\", \"type\": \"image\", ",
+            "\"belongs_to\": \"assistant\", \"url\": \"https://example.com/f-1.png\", ",
+            "\"conversation_id\": \"c-1\"}\n\n",
+            "data: {\"event\": \"agent_message\", \"task_id\": \"t-1\", \"answer\": \"Here\"}\n\n",
+        )
+        .as_bytes(),
+    ))];
+    let events: Vec<ChatStreamEvent> = ChatStream::from_bytes_stream(stream::iter(chunks))
+        .map(|event| event.unwrap())
+        .collect()
+        .await;
+
+    match &events[0] {
+        ChatStreamEvent::AgentThought(thought) => {
+            assert_eq!(thought.tools().collect::<Vec<_>>(), ["dalle3", "search"]);
+            assert_eq!(thought.observation, "image created");
+            assert_eq!(thought.message_files, ["f-1"]);
+        }
+        event => panic!("unexpected event {:?}", event),
+    }
+    match &events[1] {
+        ChatStreamEvent::MessageFile(file) => {
+            assert_eq!(file.file_type, "image");
+            assert_eq!(file.url, "https://example.com/f-1.png");
+        }
+        event => panic!("unexpected event {:?}", event),
+    }
+    assert!(
+        matches!(&events[2], ChatStreamEvent::AgentMessage(message) if message.answer == "Here")
+    );
+    assert_eq!(events[2].task_id(), Some("t-1"));
+}
+
+#[tokio::test]
+async fn test_stream_idle_timeout() {
+    use std::time::Duration;
+
+    let chunks: Vec<reqwest::Result<Bytes>> = vec![Ok(Bytes::from_static(
+        b"data: {\"event\": \"message\", \"task_id\": \"t-1\", \"answer\": \"Hi\"}\n\n",
+    ))];
+    let mut events = ChatStream::from_bytes_stream(stream::iter(chunks).chain(stream::pending()))
+        .with_idle_timeout(Duration::from_millis(20));
+
+    assert!(matches!(
+        events.next().await,
+        Some(Ok(ChatStreamEvent::Message(_)))
+    ));
+    let err = events.next().await.unwrap().unwrap_err();
+    assert!(err.to_string().starts_with("no stream data received"));
+    assert!(events.next().await.is_none());
+}
+
+#[tokio::test]
+async fn test_cancel_stream() {
+    use dify_client_rust::{compat::BoxFuture, raw::HttpTransport, ChatClient, DifyClient};
+    use std::sync::{Arc, Mutex};
+
+    /// Records the requests and fails them.
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    impl HttpTransport for Recorder {
+        fn execute(
+            &self,
+            request: reqwest::Request,
+        ) -> BoxFuture<'_, anyhow::Result<reqwest::Response>> {
+            self.0
+                .lock()
+                .unwrap()
+                .push(request.url().path().to_string());
+            Box::pin(async { Err(anyhow::anyhow!("offline")) })
+        }
+    }
+
+    let chunks: Vec<reqwest::Result<Bytes>> = vec![Ok(Bytes::from_static(
+        b"data: {\"event\": \"message\", \"task_id\": \"t-1\", \"answer\": \"Hel\"}\n\n",
+    ))];
+    let recorder = Recorder::default();
+    let chat = ChatClient::from(
+        DifyClient::builder("app-key")
+            .transport(recorder.clone())
+            .build()
+            .unwrap(),
+    );
+    let (cancel, cancelled) = tokio::sync::oneshot::channel::<()>();
+    let mut events = chat.stop_on_cancel(
+        ChatStream::from_bytes_stream(stream::iter(chunks).chain(stream::pending())),
+        "user-1",
+        async move {
+            let _ = cancelled.await;
+        },
+    );
+
+    assert!(matches!(
+        events.next().await,
+        Some(Ok(ChatStreamEvent::Message(_)))
+    ));
+    cancel.send(()).unwrap();
+    assert!(events.next().await.is_none());
+    assert!(events.is_cancelled());
+    assert_eq!(*recorder.0.lock().unwrap(), ["/v1/chat-messages/t-1/stop"]);
+
+    let mut events = ChatStream::from_bytes_stream(stream::pending()).with_cancel(async {});
+    assert!(events.next().await.is_none());
+    assert!(events.is_cancelled());
+}
+
+#[tokio::test]
+async fn test_stream_reconnects_before_the_first_event() {
+    use dify_client_rust::{ChatClient, DifyClient, RetryConfig, StreamInterrupted};
+    use std::{io::Write, net::TcpListener};
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    // both responses break off: the first before any event, the second
+    // after one message
+    let server = std::thread::spawn(move || {
+        let message =
+            "data: {\"event\": \"message\", \"task_id\": \"t-1\", \"answer\": \"Hel\"}\n\n";
+        for body in ["", message] {
+            let (mut socket, _) = listener.accept().unwrap();
+            read_request(&mut socket);
+            let mut response = "HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\n\
+                transfer-encoding: chunked\r\n\r\n"
+                .to_string();
+            if !body.is_empty() {
+                response.push_str(&format!("{:x}\r\n{}\r\n", body.len(), body));
+            }
+            socket.write_all(response.as_bytes()).unwrap();
+        }
+    });
+
+    let chat = ChatClient::from(
+        DifyClient::builder("app-key")
+            .base_url(&format!("http://{}/v1", address))
+            .retry(RetryConfig {
+                stream_reconnects: 1,
+                ..Default::default()
+            })
+            .build()
+            .unwrap(),
+    );
+    let mut events = chat
+        .create_chat_message_stream(serde_json::json!({}), "hello", "user-1", None, None)
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        events.next().await,
+        Some(Ok(ChatStreamEvent::Message(_)))
+    ));
+    let err = events.next().await.unwrap().unwrap_err();
+    let interrupted = err.downcast_ref::<StreamInterrupted>().unwrap();
+    assert_eq!(interrupted.partial_answer, "Hel");
+    assert!(events.next().await.is_none());
+    server.join().unwrap();
+}
+
+#[tokio::test]
+async fn test_stream_does_not_reconnect_after_an_event() {
+    use dify_client_rust::{ChatClient, DifyClient, RetryConfig, StreamInterrupted};
+    use std::{io::Write, net::TcpListener};
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    // the response breaks off after a ping, before any answer text
+    let server = std::thread::spawn(move || {
+        let ping = "data: {\"event\": \"ping\"}\n\n";
+        let (mut socket, _) = listener.accept().unwrap();
+        read_request(&mut socket);
+        let response = format!(
+            "HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\n\
+            transfer-encoding: chunked\r\n\r\n{:x}\r\n{}\r\n",
+            ping.len(),
+            ping
+        );
+        socket.write_all(response.as_bytes()).unwrap();
+        listener
+    });
+
+    let chat = ChatClient::from(
+        DifyClient::builder("app-key")
+            .base_url(&format!("http://{}/v1", address))
+            .retry(RetryConfig {
+                stream_reconnects: 2,
+                ..Default::default()
+            })
+            .build()
+            .unwrap(),
+    );
+    let mut events = chat
+        .create_chat_message_stream(serde_json::json!({}), "hello", "user-1", None, None)
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        events.next().await,
+        Some(Ok(ChatStreamEvent::Ping))
+    ));
+    let err = events.next().await.unwrap().unwrap_err();
+    let interrupted = err.downcast_ref::<StreamInterrupted>().unwrap();
+    assert_eq!(interrupted.partial_answer, "");
+    assert!(events.next().await.is_none());
+
+    // reconnects were left, but the run had started
+    let listener = server.join().unwrap();
+    listener.set_nonblocking(true).unwrap();
+    assert!(listener.accept().is_err());
+}
+
+#[tokio::test]
+async fn test_stream_idle_timeout_is_an_interruption() {
+    use dify_client_rust::{ChatClient, DifyClient, StreamInterrupted};
+    use std::{io::Write, net::TcpListener};
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    // sends one message, then keeps the connection open without data
+    let (done, finished) = std::sync::mpsc::channel::<()>();
+    let server = std::thread::spawn(move || {
+        let (mut socket, _) = listener.accept().unwrap();
+        read_request(&mut socket);
+        let body = "data: {\"event\": \"message\", \"task_id\": \"t-1\", \"answer\": \"Hel\"}\n\n";
+        let response = format!(
+            "HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\n\
+            transfer-encoding: chunked\r\n\r\n{:x}\r\n{}\r\n",
+            body.len(),
+            body
+        );
+        socket.write_all(response.as_bytes()).unwrap();
+        let _ = finished.recv();
+    });
+
+    let chat = ChatClient::from(
+        DifyClient::builder("app-key")
+            .base_url(&format!("http://{}/v1", address))
+            .read_timeout_secs(1)
+            .build()
+            .unwrap(),
+    );
+    let mut events = chat
+        .create_chat_message_stream(serde_json::json!({}), "hello", "user-1", None, None)
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        events.next().await,
+        Some(Ok(ChatStreamEvent::Message(_)))
+    ));
+    let err = events.next().await.unwrap().unwrap_err();
+    let interrupted = err.downcast_ref::<StreamInterrupted>().unwrap();
+    assert_eq!(interrupted.partial_answer, "Hel");
+    assert!(events.next().await.is_none());
+
+    done.send(()).unwrap();
+    server.join().unwrap();
+}
+
+#[tokio::test]
+async fn test_stream_metadata() {
+    let chunks: Vec<reqwest::Result<Bytes>> = vec![
+        Ok(Bytes::from_static(
+            b"data: {\"event\": \"message\", \"task_id\": \"t-1\", \"answer\": \"Hi\"}\n\n",
+        )),
+        Ok(Bytes::from_static(
+            br#"data: {"event": "message_end", "task_id": "t-1", "metadata": {"usage": {"prompt_tokens": 12, "completion_tokens": 3, "total_tokens": 15, "total_price": "0.0000450", "currency": "USD", "latency": 0.8}, "retriever_resources": [{"position": 1, "dataset_id": "d-1", "document_name": "faq.md", "segment_id": "s-1", "score": 0.92, "content": "Hi there"}]}}
+"#,
+        )),
+    ];
+
+    let mut events = ChatStream::from_bytes_stream(stream::iter(chunks));
+    assert!(events.metadata().is_none());
+    assert!(matches!(
+        events.next().await,
+        Some(Ok(ChatStreamEvent::Message(_)))
+    ));
+
+    let metadata = events.finish().await.unwrap().unwrap();
+    let usage = metadata.usage.unwrap();
+    assert_eq!(usage.total_tokens, 15);
+    assert_eq!(usage.currency, "USD");
+    assert_eq!(usage.total_cost(), Some(0.000045));
+    assert_eq!(metadata.retriever_resources.len(), 1);
+    assert_eq!(metadata.retriever_resources[0].document_name, "faq.md");
+    assert_eq!(metadata.retriever_resources[0].score, Some(0.92));
+}
+
+#[tokio::test]
+async fn test_stream_suggested_questions() {
+    use dify_client_rust::{ChatClient, DifyClient};
+
+    let server = MockServer::start(vec![
+        Reply::event_stream(
+            "data: {\"event\": \"message\", \"answer\": \"Hi\"}\n\n\
+            data: {\"event\": \"message_end\", \"message_id\": \"m-1\"}\n\n",
+        ),
+        Reply::json("{\"result\": \"success\", \"data\": [\"Why?\", \"How?\"]}"),
+    ]);
+    let chat = ChatClient::from(
+        DifyClient::builder("app-key")
+            .base_url(server.base_url())
+            .build()
+            .unwrap(),
+    );
+    let mut events = chat
+        .create_chat_message_stream(serde_json::json!({}), "hello", "user-1", None, None)
+        .await
+        .unwrap();
+    assert!(events.suggested_questions().await.is_err());
+    while let Some(event) = events.next().await {
+        event.unwrap();
+    }
+
+    assert_eq!(
+        events.suggested_questions().await.unwrap(),
+        ["Why?", "How?"]
+    );
+    let requests = server.finish();
+    assert_eq!(
+        requests[1].line,
+        "GET /v1/messages/m-1/suggested?user=user-1 HTTP/1.1"
+    );
+}
+
+#[tokio::test]
+async fn test_simple_chat() {
+    use dify_client_rust::{ChatClient, DifyClient};
+
+    let server = MockServer::start(vec![
+        Reply::event_stream(
+            "data: {\"event\": \"message\", \"conversation_id\": \"c-1\", \"answer\": \"Hel\"}\n\n\
+            data: {\"event\": \"message\", \"conversation_id\": \"c-1\", \"answer\": \"lo\"}\n\n\
+            data: {\"event\": \"message_end\", \"conversation_id\": \"c-1\"}\n\n",
+        ),
+        Reply::event_stream(
+            "data: {\"event\": \"error\", \"code\": \"quota_exceeded\", \"message\": \"No quota\"}\n\n",
+        ),
+    ]);
+    let chat = ChatClient::from(
+        DifyClient::builder("app-key")
+            .base_url(server.base_url())
+            .build()
+            .unwrap(),
+    );
+
+    let (answer, conversation_id) = chat.simple_chat("hello", "user-1", None).await.unwrap();
+    assert_eq!(answer, "Hello");
+    assert_eq!(conversation_id.as_str(), "c-1");
+
+    let err = chat
+        .simple_chat("again", "user-1", Some(&conversation_id))
+        .await
+        .unwrap_err();
+    assert_eq!(err.to_string(), "quota_exceeded: No quota");
+    server.finish();
+}
+
+#[tokio::test]
+async fn test_stream_holds_its_concurrency_permit() {
+    use dify_client_rust::{ChatClient, DifyClient};
+    use std::time::Duration;
+
+    let server = MockServer::start(vec![
+        Reply::event_stream(
+            "data: {\"event\": \"message\", \"task_id\": \"t-1\", \"answer\": \"Hi\"}\n\n",
+        ),
+        Reply::json(include_str!("../fixtures/app_info.json")),
+    ]);
+    let chat = ChatClient::from(
+        DifyClient::builder("app-key")
+            .base_url(server.base_url())
+            .max_concurrent(1)
+            .build()
+            .unwrap(),
+    );
+    let mut events = chat
+        .create_chat_message_stream(serde_json::json!({}), "hello", "user-1", None, None)
+        .await
+        .unwrap();
+    assert!(matches!(
+        events.next().await,
+        Some(Ok(ChatStreamEvent::Message(_)))
+    ));
+
+    // the open stream still counts against the limit
+    let info = tokio::time::timeout(Duration::from_millis(200), chat.dify_client().get_info());
+    assert!(info.await.is_err());
+
+    drop(events);
+    chat.dify_client().get_info().await.unwrap();
+    server.finish();
+}
@@ -0,0 +1,53 @@
+#![cfg(feature = "chat")]
+
+use dify_client_rust::{
+    compat::BoxFuture, raw::HttpTransport, with_timeout, ChatClient, DifyClient,
+};
+use serde_json::json;
+use std::time::Duration;
+
+/// Never answers, like an upstream stuck before the first byte.
+struct Hanging;
+
+impl HttpTransport for Hanging {
+    fn execute(
+        &self,
+        _request: reqwest::Request,
+    ) -> BoxFuture<'_, anyhow::Result<reqwest::Response>> {
+        Box::pin(std::future::pending())
+    }
+}
+
+#[tokio::test]
+async fn test_with_timeout_bounds_streaming_calls() {
+    let client = DifyClient::builder("app-key")
+        .transport(Hanging)
+        .build()
+        .unwrap();
+    let chat = ChatClient::from(client);
+
+    let err = with_timeout(
+        Duration::from_millis(20),
+        chat.create_chat_message_stream(json!({}), "hello", "user-1", None, None),
+    )
+    .await
+    .err()
+    .unwrap();
+    assert_eq!(err.to_string(), "no response received within 20ms");
+}
+
+#[tokio::test]
+async fn test_first_byte_timeout() {
+    let client = DifyClient::builder("app-key")
+        .first_byte_timeout_secs(1)
+        .transport(Hanging)
+        .build()
+        .unwrap();
+
+    let err = ChatClient::from(client)
+        .create_chat_message_stream(json!({}), "hello", "user-1", None, None)
+        .await
+        .err()
+        .unwrap();
+    assert_eq!(err.to_string(), "no response received within 1s");
+}
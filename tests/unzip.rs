@@ -0,0 +1,57 @@
+#![cfg(feature = "unzip")]
+
+use dify_client_rust::ext::unzip::{extract_zip, is_zip, ExtractLimits};
+use std::io::{Cursor, Write};
+use std::path::PathBuf;
+use zip::write::SimpleFileOptions;
+
+fn archive(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, contents) in files {
+        writer
+            .start_file(*name, SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(contents).unwrap();
+    }
+    writer.finish().unwrap().into_inner()
+}
+
+#[test]
+fn test_extract_zip() {
+    let bytes = archive(&[("export/a.txt", b"alpha"), ("b.json", b"{}")]);
+    assert!(is_zip(&bytes));
+
+    let entries = extract_zip(&bytes, &ExtractLimits::default()).unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].path, PathBuf::from("export/a.txt"));
+    assert_eq!(entries[0].contents, b"alpha");
+}
+
+#[test]
+fn test_extract_zip_limits() {
+    let bytes = archive(&[("a.txt", &[0u8; 4096]), ("b.txt", b"b")]);
+
+    let limits = ExtractLimits {
+        max_entry_size: 1024,
+        ..Default::default()
+    };
+    let err = extract_zip(&bytes, &limits).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("a.txt exceeds the limit of 1024 bytes"));
+
+    let limits = ExtractLimits {
+        max_entries: 1,
+        ..Default::default()
+    };
+    assert!(extract_zip(&bytes, &limits).is_err());
+
+    let limits = ExtractLimits {
+        max_total_size: 4096,
+        ..Default::default()
+    };
+    assert!(extract_zip(&bytes, &limits).is_err());
+
+    let bytes = archive(&[("../escape.txt", b"x")]);
+    assert!(extract_zip(&bytes, &ExtractLimits::default()).is_err());
+}
@@ -0,0 +1,260 @@
+#![cfg(feature = "workflow")]
+
+use dify_client_rust::{ResponseMode, WorkflowRunRequest};
+use serde_json::json;
+
+mod common;
+
+#[test]
+fn test_workflow_run_request() {
+    let request = WorkflowRunRequest::new(json!({"topic": "rust"}), "mock-user")
+        .response_mode(ResponseMode::Stream)
+        .workflow_id("wf-v2");
+
+    assert_eq!(request.workflow_id.as_deref(), Some("wf-v2"));
+    let stored = serde_json::to_value(&request).unwrap();
+    assert_eq!(
+        stored,
+        json!({
+            "inputs": {"topic": "rust"},
+            "response_mode": "streaming",
+            "user": "mock-user",
+            "workflow_id": "wf-v2"
+        })
+    );
+    let loaded: WorkflowRunRequest = serde_json::from_value(stored).unwrap();
+    assert_eq!(loaded.workflow_id.as_deref(), Some("wf-v2"));
+}
+
+#[tokio::test]
+async fn test_workflow_run_pinned_version() {
+    use common::{MockServer, Reply};
+    use dify_client_rust::{DifyClient, WorkflowClient};
+
+    let server = MockServer::start(vec![Reply::json(include_str!(
+        "../fixtures/workflow_run.json"
+    ))]);
+    let workflow = WorkflowClient::from(
+        DifyClient::builder("app-key")
+            .base_url(server.base_url())
+            .build()
+            .unwrap(),
+    );
+
+    let request = WorkflowRunRequest::new(json!({"topic": "rust"}), "mock-user")
+        .trace_id("trace-1")
+        .workflow_id("wf-v2");
+    workflow.run_request(&request).await.unwrap();
+
+    let request = &server.finish()[0];
+    assert_eq!(request.line, "POST /v1/workflows/wf-v2/run HTTP/1.1");
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&request.body).unwrap(),
+        json!({
+            "inputs": {"topic": "rust"},
+            "response_mode": "blocking",
+            "user": "mock-user",
+            "trace_id": "trace-1"
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_trace_id_scope() {
+    use dify_client_rust::raw::current_trace_id;
+
+    assert_eq!(current_trace_id(), None);
+    let trace_id = dify_client_rust::with_trace_id("trace-1", async { current_trace_id() }).await;
+    assert_eq!(trace_id.as_deref(), Some("trace-1"));
+}
+
+#[test]
+fn test_response_mode_display() {
+    assert_eq!(ResponseMode::Block.to_string(), "blocking");
+    assert_eq!(ResponseMode::Stream.to_string(), "streaming");
+    assert_eq!(ResponseMode::default(), ResponseMode::Block);
+    assert_eq!(
+        "streaming".parse::<ResponseMode>().unwrap(),
+        ResponseMode::Stream
+    );
+    assert!("stream".parse::<ResponseMode>().is_err());
+}
+
+#[tokio::test]
+async fn test_workflow_stream_events() {
+    use bytes::Bytes;
+    use dify_client_rust::{models::WorkflowStatus, WorkflowStream, WorkflowStreamEvent};
+    use futures_util::{stream, StreamExt};
+
+    let body = concat!(
+        "data: {\"event\": \"workflow_started\", \"task_id\": \"t-1\", \"workflow_run_id\": \"r-1\", ",
+        "\"data\": {\"id\": \"r-1\", \"workflow_id\": \"wf-1\", \"sequence_number\": 7, \"created_at\": 1705407629}}\n\n",
+        "data: {\"event\": \"text_chunk\", \"task_id\": \"t-1\", \"workflow_run_id\": \"r-1\", ",
+        "\"data\": {\"text\": \"Hi\", \"from_variable_selector\": [\"llm\", \"text\"]}}\n\n",
+        "event: ping\n\n",
+        "data: {\"event\": \"workflow_finished\", \"task_id\": \"t-1\", \"workflow_run_id\": \"r-1\", ",
+        "\"data\": {\"id\": \"r-1\", \"workflow_id\": \"wf-1\", \"status\": \"succeeded\", ",
+        "\"outputs\": {\"text\": \"Hi\"}, \"elapsed_time\": 0.32, \"total_tokens\": 12, \"total_steps\": 3, ",
+        "\"created_at\": 1705407629, \"finished_at\": 1705407630}}\n\n",
+    );
+    let chunks: Vec<reqwest::Result<Bytes>> = vec![Ok(Bytes::from_static(body.as_bytes()))];
+    let events: Vec<WorkflowStreamEvent> = WorkflowStream::from_bytes_stream(stream::iter(chunks))
+        .map(|event| event.unwrap())
+        .collect()
+        .await;
+
+    assert_eq!(events.len(), 3);
+    assert!(events.iter().all(|event| event.task_id() == Some("t-1")));
+    match &events[1] {
+        WorkflowStreamEvent::TextChunk(chunk) => assert_eq!(chunk.data.text, "Hi"),
+        event => panic!("unexpected event {:?}", event),
+    }
+    match &events[2] {
+        WorkflowStreamEvent::WorkflowFinished(finished) => {
+            assert_eq!(finished.data.status, WorkflowStatus::Succeeded);
+            assert_eq!(finished.data.total_tokens, 12);
+        }
+        event => panic!("unexpected event {:?}", event),
+    }
+}
+
+#[test]
+fn test_workflow_error_event_task_id() {
+    use dify_client_rust::WorkflowStreamEvent;
+
+    let event: WorkflowStreamEvent = serde_json::from_value(json!({
+        "event": "error",
+        "task_id": "t-2",
+        "status": 400,
+        "code": "invalid_param",
+        "message": "topic is required"
+    }))
+    .unwrap();
+
+    assert_eq!(event.task_id(), Some("t-2"));
+}
+
+#[test]
+fn test_workflow_logs_page() {
+    use dify_client_rust::models::{Page, WorkflowLog, WorkflowStatus};
+
+    let page: Page<WorkflowLog> = serde_json::from_value(json!({
+        "page": 1,
+        "limit": 20,
+        "total": 1,
+        "has_more": false,
+        "data": [{
+            "id": "log-1",
+            "workflow_run": {
+                "id": "r-1",
+                "version": "2024-11-04 09:12:33.000000",
+                "status": "failed",
+                "error": "node llm timed out",
+                "elapsed_time": 30.2,
+                "total_tokens": 0,
+                "total_steps": 2,
+                "created_at": 1730711553,
+                "finished_at": 1730711583
+            },
+            "created_from": "service-api",
+            "created_by_role": "end_user",
+            "created_by_account": null,
+            "created_by_end_user": {
+                "id": "eu-1",
+                "type": "service_api",
+                "is_anonymous": false,
+                "session_id": "mock-user"
+            },
+            "created_at": 1730711553
+        }]
+    }))
+    .unwrap();
+
+    let log = &page.data[0];
+    assert_eq!(log.workflow_run.status, WorkflowStatus::Failed);
+    assert_eq!(
+        log.created_by_end_user.as_ref().unwrap().session_id,
+        "mock-user"
+    );
+}
+
+#[test]
+fn test_workflow_run_request_validation() {
+    use dify_client_rust::ConfigProblem;
+
+    assert!(WorkflowRunRequest::new(json!({}), "mock-user")
+        .validate()
+        .is_ok());
+
+    let err = WorkflowRunRequest::new(json!(["rust"]), "")
+        .workflow_id("")
+        .validate()
+        .unwrap_err();
+    assert_eq!(
+        err.problems,
+        [
+            ConfigProblem::InputsNotObject,
+            ConfigProblem::EmptyField { field: "user" },
+            ConfigProblem::EmptyField {
+                field: "workflow_id"
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_workflow_run_and_stop() {
+    use common::{MockServer, Reply};
+    use dify_client_rust::{DifyClient, WorkflowClient};
+
+    let server = MockServer::start(vec![
+        Reply::json(include_str!("../fixtures/workflow_run.json")),
+        Reply::json("{\"result\": \"success\"}"),
+    ]);
+    let workflow = WorkflowClient::from(
+        DifyClient::builder("app-key")
+            .base_url(server.base_url())
+            .build()
+            .unwrap(),
+    );
+
+    workflow
+        .run(json!({"topic": "rust"}), ResponseMode::Block, "user-1")
+        .await
+        .unwrap();
+    let response = workflow.stop("t-1", "user-1").await.unwrap();
+    assert_eq!(response.status(), 200);
+
+    let requests = server.finish();
+    assert_eq!(requests[0].line, "POST /v1/workflows/run HTTP/1.1");
+    let body: serde_json::Value = serde_json::from_str(&requests[0].body).unwrap();
+    assert_eq!(body["user"], "user-1");
+    assert_eq!(
+        requests[1].line,
+        "POST /v1/workflows/tasks/t-1/stop HTTP/1.1"
+    );
+}
+
+#[tokio::test]
+async fn test_workflow_run_raw_default_user() {
+    use common::{MockServer, Reply};
+    use dify_client_rust::{DifyClient, WorkflowClient};
+
+    let server = MockServer::start(vec![Reply::status(400, "{}")]);
+    let workflow = WorkflowClient::from(
+        DifyClient::builder("app-key")
+            .base_url(server.base_url())
+            .build()
+            .unwrap(),
+    );
+
+    let response = workflow
+        .run_raw(json!({}), ResponseMode::Block, None)
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+
+    let requests = server.finish();
+    let body: serde_json::Value = serde_json::from_str(&requests[0].body).unwrap();
+    assert_eq!(body["user"], "abc-123");
+}